@@ -1,9 +1,17 @@
 use std::convert::{TryFrom, TryInto};
 use std::error::Error;
+use std::io::{Read, Write};
+use std::pin::Pin;
 use std::result::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
 
 use async_trait::async_trait;
 use tonic::transport::Channel;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
 use tonic::Status;
 use uuid::Uuid;
 use virt::domain::{VIR_DOMAIN_PAUSED, VIR_DOMAIN_RUNNING};
@@ -16,20 +24,103 @@ use crate::protoc::libvirt_api::libvirt_api_client::*;
 use crate::protoc::libvirt_api::*;
 
 mod protoc;
-mod schema;
+
+pub type USBDeviceEventHandler =
+    dyn FnMut(schema::schema::USBDeviceAction, schema::schema::USBDevice) -> bool + Send;
+
+pub type ConsoleOutputStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, GRPCAPIError>> + Send>>;
+
+pub type GuestExecStream =
+    Pin<Box<dyn Stream<Item = Result<schema::schema::GuestExecOutput, GRPCAPIError>> + Send>>;
+
+pub type DomainEventStream =
+    Pin<Box<dyn Stream<Item = Result<schema::schema::DomainEvent, GRPCAPIError>> + Send>>;
 
 #[async_trait]
 pub trait LibvirtAPIClient {
     async fn list_domains(
         &mut self,
     ) -> Result<Vec<schema::schema::Domain>, libvirt_grpc_api::GRPCAPIError>;
-    async fn create_domain(&mut self, uuid: Uuid) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
-    async fn destroy_domain(&mut self, uuid: Uuid) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+    async fn create_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, libvirt_grpc_api::GRPCAPIError>;
+    async fn destroy_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, libvirt_grpc_api::GRPCAPIError>;
+    async fn shutdown_domain(
+        &mut self,
+        uuid: Uuid,
+        modes: &[schema::schema::ShutdownMode],
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, libvirt_grpc_api::GRPCAPIError>;
+    async fn reboot_domain(
+        &mut self,
+        uuid: Uuid,
+        modes: &[schema::schema::ShutdownMode],
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, libvirt_grpc_api::GRPCAPIError>;
+    async fn reset_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, libvirt_grpc_api::GRPCAPIError>;
+    async fn suspend_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, libvirt_grpc_api::GRPCAPIError>;
+    async fn resume_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, libvirt_grpc_api::GRPCAPIError>;
+    async fn define_domain(&mut self, xml: &String) -> Result<Uuid, libvirt_grpc_api::GRPCAPIError>;
+    async fn undefine_domain(
+        &mut self,
+        uuid: Uuid,
+        options: schema::schema::UndefineOptions,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+    async fn create_transient_domain(
+        &mut self,
+        xml: &String,
+        start_paused: bool,
+    ) -> Result<Uuid, libvirt_grpc_api::GRPCAPIError>;
+    async fn get_domain_xml(
+        &mut self,
+        uuid: Uuid,
+        inactive: bool,
+    ) -> Result<String, libvirt_grpc_api::GRPCAPIError>;
+    async fn update_domain_xml(
+        &mut self,
+        uuid: Uuid,
+        xml: &String,
+    ) -> Result<schema::schema::DomainXMLUpdate, libvirt_grpc_api::GRPCAPIError>;
+    async fn get_domain_xml_o(
+        &mut self,
+        uuid: Uuid,
+        inactive: bool,
+    ) -> Result<schema::domain_xml::DomainXML, libvirt_grpc_api::GRPCAPIError>;
+    async fn update_domain_xml_o(
+        &mut self,
+        uuid: Uuid,
+        domain: &schema::domain_xml::DomainXML,
+    ) -> Result<schema::domain_xml::DomainXML, libvirt_grpc_api::GRPCAPIError>;
 
     async fn list_usb_devices(
         &mut self,
     ) -> Result<Vec<schema::schema::USBDevice>, libvirt_grpc_api::GRPCAPIError>;
 
+    // Calls `on_event` with the current inventory, then with every hotplug event, until it
+    // returns false or the server closes the stream.
+    async fn watch_usb_devices(
+        &mut self,
+        on_event: &mut USBDeviceEventHandler,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
     async fn attach_usb_device(
         &mut self,
         uuid: Uuid,
@@ -55,6 +146,520 @@ pub trait LibvirtAPIClient {
         uuid: Uuid,
         device: &schema::schema::USBDevice,
     ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn attach_usb_device_by(
+        &mut self,
+        uuid: Uuid,
+        selector: &schema::schema::USBDeviceSelector,
+        scope: schema::schema::DeviceScope,
+        force: bool,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn detach_usb_device_by(
+        &mut self,
+        uuid: Uuid,
+        selector: &schema::schema::USBDeviceSelector,
+        scope: schema::schema::DeviceScope,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn list_usb_rules(
+        &mut self,
+    ) -> Result<Vec<schema::schema::USBRule>, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn create_usb_rule(
+        &mut self,
+        rule: &schema::schema::USBRule,
+    ) -> Result<schema::schema::USBRule, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn update_usb_rule(
+        &mut self,
+        rule: &schema::schema::USBRule,
+    ) -> Result<schema::schema::USBRule, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn delete_usb_rule(&mut self, id: Uuid) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    // Events for the given domain, or for all domains if None, for as long as the stream is kept.
+    async fn watch_domain_events(
+        &mut self,
+        uuid: Option<Uuid>,
+    ) -> Result<DomainEventStream, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn create_snapshot(
+        &mut self,
+        uuid: Uuid,
+        options: &schema::schema::SnapshotOptions,
+    ) -> Result<String, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn list_snapshots(
+        &mut self,
+        uuid: Uuid,
+    ) -> Result<Vec<schema::schema::Snapshot>, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn revert_to_snapshot(
+        &mut self,
+        uuid: Uuid,
+        name: &str,
+        options: schema::schema::SnapshotRevertOptions,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn delete_snapshot(
+        &mut self,
+        uuid: Uuid,
+        name: &str,
+        options: schema::schema::SnapshotDeleteOptions,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn get_snapshot_xml(
+        &mut self,
+        uuid: Uuid,
+        name: &str,
+    ) -> Result<String, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn managed_save(
+        &mut self,
+        uuid: Uuid,
+        options: schema::schema::SaveOptions,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn has_managed_save(
+        &mut self,
+        uuid: Uuid,
+    ) -> Result<bool, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn remove_managed_save(
+        &mut self,
+        uuid: Uuid,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    // `path` is on the server's host and must be absolute.
+    async fn save_domain(
+        &mut self,
+        uuid: Uuid,
+        path: &str,
+        options: schema::schema::SaveOptions,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn restore_domain(
+        &mut self,
+        path: &str,
+        xml: Option<&str>,
+        options: schema::schema::SaveOptions,
+    ) -> Result<(), libvirt_grpc_api::GRPCAPIError>;
+
+    // Bytes sent on the returned sender are typed into the console; dropping it closes the
+    // console. `dev_name` picks a console or serial alias, None for the first console.
+    async fn open_console(
+        &mut self,
+        uuid: Uuid,
+        dev_name: Option<&str>,
+        force: bool,
+    ) -> Result<(mpsc::Sender<Vec<u8>>, ConsoleOutputStream), libvirt_grpc_api::GRPCAPIError>;
+
+    async fn get_graphics_info(
+        &mut self,
+        uuid: Uuid,
+    ) -> Result<Vec<schema::schema::GraphicsDevice>, libvirt_grpc_api::GRPCAPIError>;
+
+    // A single-use token for the server's websocket graphics proxy.
+    async fn create_graphics_token(
+        &mut self,
+        uuid: Uuid,
+        index: u32,
+    ) -> Result<schema::schema::GraphicsToken, libvirt_grpc_api::GRPCAPIError>;
+
+    // The given screen of a running domain as a PNG image.
+    async fn get_screenshot(
+        &mut self,
+        uuid: Uuid,
+        screen: u32,
+    ) -> Result<Vec<u8>, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn get_interface_addresses(
+        &mut self,
+        uuid: Uuid,
+        source: schema::schema::InterfaceAddressSource,
+    ) -> Result<Vec<schema::schema::GuestInterface>, libvirt_grpc_api::GRPCAPIError>;
+
+    // Dropping the stream before the exit status arrives kills the process.
+    async fn guest_exec(
+        &mut self,
+        uuid: Uuid,
+        command: &schema::schema::GuestCommand,
+    ) -> Result<GuestExecStream, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn guest_read_file(
+        &mut self,
+        uuid: Uuid,
+        path: &str,
+    ) -> Result<Vec<u8>, libvirt_grpc_api::GRPCAPIError>;
+
+    // Returns the number of bytes written. Without `append` the file is truncated before any data
+    // is sent, so a failed upload does not leave the old contents in place.
+    async fn guest_write_file(
+        &mut self,
+        uuid: Uuid,
+        path: &str,
+        data: &[u8],
+        append: bool,
+    ) -> Result<u64, libvirt_grpc_api::GRPCAPIError>;
+
+    // Both return how many filesystems were frozen or thawed; an empty `mountpoints` means all.
+    // Thawing always covers every frozen filesystem, as QEMU cannot thaw only some of them.
+    async fn fs_freeze(
+        &mut self,
+        uuid: Uuid,
+        mountpoints: &[String],
+    ) -> Result<u32, libvirt_grpc_api::GRPCAPIError>;
+
+    async fn fs_thaw(&mut self, uuid: Uuid) -> Result<u32, libvirt_grpc_api::GRPCAPIError>;
+
+    // Returns the snapshot's name. The filesystems are thawed again even if the call fails.
+    async fn create_quiesced_snapshot(
+        &mut self,
+        uuid: Uuid,
+        options: &schema::schema::QuiescedSnapshotOptions,
+    ) -> Result<String, libvirt_grpc_api::GRPCAPIError>;
+}
+
+fn to_proto_save_options(options: schema::schema::SaveOptions) -> SaveOptions {
+    SaveOptions {
+        bypass_cache: options.bypass_cache,
+        running: options.running,
+        paused: options.paused,
+    }
+}
+
+fn from_proto_guest_exec_output(
+    output: GuestExecOutput,
+) -> Result<schema::schema::GuestExecOutput, GRPCAPIError> {
+    match output.output {
+        Some(guest_exec_output::Output::Stdout(x)) => {
+            Ok(schema::schema::GuestExecOutput::Stdout(x))
+        }
+        Some(guest_exec_output::Output::Stderr(x)) => {
+            Ok(schema::schema::GuestExecOutput::Stderr(x))
+        }
+        Some(guest_exec_output::Output::Exit(x)) => Ok(schema::schema::GuestExecOutput::Exit {
+            exit_code: x.exit_code,
+            signal: x.signal,
+            timed_out: x.timed_out,
+        }),
+        None => Err(GRPCAPIError::new("empty guest exec output".to_string())),
+    }
+}
+
+fn from_proto_state(state: i32) -> DomainState {
+    match protoc::libvirt_api::DomainState::from_i32(state) {
+        Some(libvirt_api::DomainState::Unspecified) => DomainState::Unspecified,
+        Some(libvirt_api::DomainState::Nostate) => DomainState::NoState,
+        Some(libvirt_api::DomainState::Running) => DomainState::Running,
+        Some(libvirt_api::DomainState::Blocked) => DomainState::Blocked,
+        Some(libvirt_api::DomainState::Paused) => DomainState::Paused,
+        Some(libvirt_api::DomainState::Shutdown) => DomainState::ShutDown,
+        Some(libvirt_api::DomainState::Shutoff) => DomainState::ShutOff,
+        Some(libvirt_api::DomainState::Crashed) => DomainState::Crashed,
+        Some(libvirt_api::DomainState::Pmsuspended) => DomainState::PMSuspended,
+        None => schema::schema::DomainState::Unspecified,
+    }
+}
+
+fn to_proto_wait(wait: Option<schema::schema::WaitForState>) -> Option<WaitForState> {
+    wait.map(|x| WaitForState {
+        state: x.state as i32,
+        timeout_ms: x.timeout.as_millis() as u32,
+    })
+}
+
+// A timed-out wait is an error that still carries the state the domain ended up in; see
+// GRPCAPIError::wait_result.
+fn lifecycle_result(
+    msg: SuccessResponse,
+) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
+    let wait_result = match (msg.state, msg.elapsed_ms) {
+        (Some(state), Some(elapsed_ms)) => Some(schema::schema::WaitResult {
+            state: from_proto_state(state),
+            elapsed: Duration::from_millis(elapsed_ms),
+        }),
+        _ => None,
+    };
+
+    if !msg.success {
+        return Err(match wait_result {
+            Some(x) => GRPCAPIError::new_with_wait_result(msg.error.unwrap(), x),
+            None => GRPCAPIError::new(msg.error.unwrap()),
+        });
+    }
+
+    return Ok(wait_result);
+}
+
+fn from_proto_usb_device(device: UsbDevice) -> schema::schema::USBDevice {
+    return schema::schema::USBDevice {
+        device: device.device,
+        vendor_id: device.vendor_id,
+        product_id: device.product_id,
+        model: device.model,
+        vendor_name: device.vendor_name,
+        model_name: device.model_name,
+        bus: device.bus,
+        device_number: device.device_number,
+        port_path: device.port_path,
+        serial: device.serial,
+        attached_to: match device.attached_to.is_empty() {
+            true => None,
+            false => Some(byte_vec_to_uuid(device.attached_to).unwrap()),
+        },
+    };
+}
+
+fn from_proto_usb_rule(rule: UsbRule) -> schema::schema::USBRule {
+    schema::schema::USBRule {
+        id: byte_vec_to_uuid(rule.id).unwrap(),
+        domain_uuid: byte_vec_to_uuid(rule.domain_uuid).unwrap(),
+        vendor_id: rule.vendor_id,
+        product_id: rule.product_id,
+        serial: rule.serial,
+        port_path: rule.port_path,
+    }
+}
+
+fn to_proto_usb_rule(rule: &schema::schema::USBRule) -> UsbRule {
+    UsbRule {
+        id: rule.id.as_bytes().to_vec(),
+        domain_uuid: rule.domain_uuid.as_bytes().to_vec(),
+        vendor_id: rule.vendor_id.clone(),
+        product_id: rule.product_id.clone(),
+        serial: rule.serial.clone(),
+        port_path: rule.port_path.clone(),
+    }
+}
+
+fn usb_rule_result(msg: UsbRuleResponse) -> Result<schema::schema::USBRule, GRPCAPIError> {
+    if !msg.success {
+        return Err(GRPCAPIError::new(msg.error.unwrap()));
+    }
+
+    return Ok(from_proto_usb_rule(msg.rule.unwrap()));
+}
+
+fn from_proto_domain_event(event: DomainEvent) -> schema::schema::DomainEvent {
+    use domain_event::Event;
+    use schema::schema::DomainEventKind;
+
+    let kind = match event.event {
+        Some(Event::Lifecycle(x)) => DomainEventKind::Lifecycle {
+            event: match DomainLifecycleEventType::from_i32(x.r#type) {
+                Some(DomainLifecycleEventType::Defined) => {
+                    schema::schema::DomainLifecycleEventType::Defined
+                }
+                Some(DomainLifecycleEventType::Undefined) => {
+                    schema::schema::DomainLifecycleEventType::Undefined
+                }
+                Some(DomainLifecycleEventType::Started) => {
+                    schema::schema::DomainLifecycleEventType::Started
+                }
+                Some(DomainLifecycleEventType::Suspended) => {
+                    schema::schema::DomainLifecycleEventType::Suspended
+                }
+                Some(DomainLifecycleEventType::Resumed) => {
+                    schema::schema::DomainLifecycleEventType::Resumed
+                }
+                Some(DomainLifecycleEventType::Stopped) => {
+                    schema::schema::DomainLifecycleEventType::Stopped
+                }
+                Some(DomainLifecycleEventType::Shutdown) => {
+                    schema::schema::DomainLifecycleEventType::Shutdown
+                }
+                Some(DomainLifecycleEventType::Pmsuspended) => {
+                    schema::schema::DomainLifecycleEventType::PMSuspended
+                }
+                Some(DomainLifecycleEventType::Crashed) => {
+                    schema::schema::DomainLifecycleEventType::Crashed
+                }
+                _ => schema::schema::DomainLifecycleEventType::Unknown,
+            },
+            detail: x.detail,
+        },
+        Some(Event::Reboot(_)) => DomainEventKind::Reboot,
+        Some(Event::DeviceAdded(x)) => DomainEventKind::DeviceAdded { alias: x.alias },
+        Some(Event::DeviceRemoved(x)) => DomainEventKind::DeviceRemoved { alias: x.alias },
+        Some(Event::Watchdog(x)) => DomainEventKind::Watchdog {
+            action: match WatchdogAction::from_i32(x.action) {
+                Some(WatchdogAction::Pause) => schema::schema::WatchdogAction::Pause,
+                Some(WatchdogAction::Reset) => schema::schema::WatchdogAction::Reset,
+                Some(WatchdogAction::Poweroff) => schema::schema::WatchdogAction::PowerOff,
+                Some(WatchdogAction::Shutdown) => schema::schema::WatchdogAction::Shutdown,
+                Some(WatchdogAction::Debug) => schema::schema::WatchdogAction::Debug,
+                Some(WatchdogAction::Injectnmi) => schema::schema::WatchdogAction::InjectNMI,
+                _ => schema::schema::WatchdogAction::None,
+            },
+        },
+        Some(Event::IoError(x)) => DomainEventKind::IOError {
+            src_path: x.src_path,
+            dev_alias: x.dev_alias,
+            action: match IoErrorAction::from_i32(x.action) {
+                Some(IoErrorAction::Pause) => schema::schema::IOErrorAction::Pause,
+                Some(IoErrorAction::Report) => schema::schema::IOErrorAction::Report,
+                _ => schema::schema::IOErrorAction::None,
+            },
+        },
+        Some(Event::AgentLifecycle(x)) => DomainEventKind::AgentLifecycle {
+            connected: x.state == AgentState::Connected as i32,
+        },
+        None => DomainEventKind::Unknown,
+    };
+
+    schema::schema::DomainEvent {
+        uuid: byte_vec_to_uuid(event.uuid).unwrap(),
+        name: event.name,
+        timestamp: UNIX_EPOCH + Duration::from_millis(event.timestamp_ms),
+        kind,
+    }
+}
+
+// Splits a selector into the request's vendor_id/product_id fields and its oneof.
+fn to_proto_selector(
+    selector: &schema::schema::USBDeviceSelector,
+) -> (String, String, Option<UsbDeviceSelector>) {
+    use libvirt_api::usb_device_selector::Selector;
+    use schema::schema::USBDeviceSelector::*;
+
+    let selector = match selector {
+        VendorProduct {
+            vendor_id,
+            product_id,
+        } => return (vendor_id.clone(), product_id.clone(), None),
+        BusAddress { bus, device } => Selector::BusAddress(UsbBusAddress {
+            bus: *bus,
+            device: *device,
+        }),
+        PortPath(x) => Selector::PortPath(x.clone()),
+        Serial(x) => Selector::Serial(x.clone()),
+    };
+
+    return (
+        "".to_string(),
+        "".to_string(),
+        Some(UsbDeviceSelector {
+            selector: Some(selector),
+        }),
+    );
+}
+
+// Ctrl-], as in `virsh console`.
+const CONSOLE_ESCAPE: u8 = 0x1d;
+
+// How long the console's stdin thread waits for input before checking whether it should stop.
+const CONSOLE_STDIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn stdin_readable(timeout: Duration) -> bool {
+    let mut fd = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) > 0 }
+}
+
+// Puts the local terminal into raw mode for as long as it is held.
+struct RawTerminal {
+    original: libc::termios,
+}
+
+impl RawTerminal {
+    fn enable() -> std::io::Result<RawTerminal> {
+        unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+
+            let mut raw = original;
+            libc::cfmakeraw(&mut raw);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+
+            Ok(RawTerminal { original })
+        }
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+// Connects the local terminal to a domain's console until Ctrl-] is pressed or the console closes.
+pub async fn attach_console<C: LibvirtAPIClient + Send>(
+    client: &mut C,
+    uuid: Uuid,
+    dev_name: Option<&str>,
+    force: bool,
+) -> Result<(), GRPCAPIError> {
+    let (tx, mut output) = client.open_console(uuid, dev_name, force).await?;
+    let _raw = RawTerminal::enable().map_err(|e| {
+        GRPCAPIError::new(format!("failed to put the terminal into raw mode: {}", e))
+    })?;
+
+    // Reading stdin blocks, so it gets its own thread. It only reads once poll says input is
+    // there, and checks `done` in between, so it can be stopped before the terminal is restored
+    // instead of swallowing whatever the caller types next.
+    let done = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let done = done.clone();
+
+        std::thread::spawn(move || {
+            let mut stdin = std::io::stdin();
+            let mut buf = [0; 1024];
+
+            while !done.load(Ordering::Relaxed) {
+                if !stdin_readable(CONSOLE_STDIN_POLL_INTERVAL) {
+                    continue;
+                }
+
+                let n = match stdin.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+
+                let (data, escaped) = match buf[..n].iter().position(|x| *x == CONSOLE_ESCAPE) {
+                    Some(i) => (&buf[..i], true),
+                    None => (&buf[..n], false),
+                };
+
+                if !data.is_empty() && tx.blocking_send(data.to_vec()).is_err() {
+                    break;
+                }
+                if escaped {
+                    break;
+                }
+            }
+        })
+    };
+
+    let mut stdout = std::io::stdout();
+    let mut result = Ok(());
+    while let Some(data) = output.next().await {
+        if let Err(e) = data.and_then(|x| {
+            stdout
+                .write_all(&x)
+                .and_then(|_| stdout.flush())
+                .map_err(|e| GRPCAPIError::new(e.to_string()))
+        }) {
+            result = Err(e);
+            break;
+        }
+    }
+
+    done.store(true, Ordering::Relaxed);
+    let _ = tokio::task::spawn_blocking(move || reader.join()).await;
+
+    return result;
 }
 
 pub struct GRPCLibvirtAPIClient {
@@ -89,133 +694,373 @@ impl LibvirtAPIClient for GRPCLibvirtAPIClient {
                 name: domain.name,
                 hostname: domain.hostname,
                 os_type: domain.os_type,
-                state: match protoc::libvirt_api::DomainState::from_i32(domain.state) {
-                    Some(libvirt_api::DomainState::Unspecified) => DomainState::Unspecified,
-                    Some(libvirt_api::DomainState::Nostate) => DomainState::NoState,
-                    Some(libvirt_api::DomainState::Running) => DomainState::Running,
-                    Some(libvirt_api::DomainState::Blocked) => DomainState::Blocked,
-                    Some(libvirt_api::DomainState::Paused) => DomainState::Paused,
-                    Some(libvirt_api::DomainState::Shutdown) => DomainState::ShutDown,
-                    Some(libvirt_api::DomainState::Shutoff) => DomainState::ShutOff,
-                    Some(libvirt_api::DomainState::Crashed) => DomainState::Crashed,
-                    Some(libvirt_api::DomainState::Pmsuspended) => DomainState::PMSuspended,
-                    None => schema::schema::DomainState::Unspecified,
-                },
+                state: from_proto_state(domain.state),
                 memory: domain.memory,
                 memory_max: domain.memory_max,
                 virt_cpu_num: domain.virt_cpu_num,
                 virt_cpu_time: domain.virt_cpu_time,
+                has_managed_save: domain.has_managed_save,
             })
         }
 
         return Ok(res);
     }
 
-    async fn create_domain(&mut self, uuid: Uuid) -> Result<(), GRPCAPIError> {
+    async fn create_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
         let response = self
             .client
             .create_domain(CreateDomainRequest {
                 uuid: uuid.as_bytes().to_vec(),
+                wait: to_proto_wait(wait),
             })
             .await?;
 
-        let msg = response.into_inner();
-
-        if !msg.success {
-            return Err(GRPCAPIError::new(msg.error.unwrap()));
-        }
-
-        return Ok(());
+        return lifecycle_result(response.into_inner());
     }
 
-    async fn destroy_domain(&mut self, uuid: Uuid) -> Result<(), GRPCAPIError> {
+    async fn destroy_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
         let response = self
             .client
             .destroy_domain(DestroyDomainRequest {
                 uuid: uuid.as_bytes().to_vec(),
+                wait: to_proto_wait(wait),
             })
             .await?;
 
-        let msg = response.into_inner();
-
-        if !msg.success {
-            return Err(GRPCAPIError::new(msg.error.unwrap()));
-        }
-
-        return Ok(());
+        return lifecycle_result(response.into_inner());
     }
 
-    async fn list_usb_devices(&mut self) -> Result<Vec<schema::schema::USBDevice>, GRPCAPIError> {
-        let mut stream = self
+    async fn shutdown_domain(
+        &mut self,
+        uuid: Uuid,
+        modes: &[schema::schema::ShutdownMode],
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
+        let response = self
             .client
-            .list_usb_devices(ListUsbDevicesRequest {})
-            .await?
-            .into_inner();
-
-        let mut res: Vec<schema::schema::USBDevice> = Vec::new();
-
-        while let Some(device) = stream.message().await? {
-            res.push(schema::schema::USBDevice {
-                device: device.device,
-                vendor_id: device.vendor_id,
-                product_id: device.product_id,
-                model: device.model,
-                vendor_name: device.vendor_name,
-                model_name: device.model_name,
+            .shutdown_domain(ShutdownDomainRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                modes: modes.iter().map(|x| *x as i32).collect(),
+                wait: to_proto_wait(wait),
             })
-        }
+            .await?;
 
-        return Ok(res);
+        return lifecycle_result(response.into_inner());
     }
 
-    async fn attach_usb_device(
+    async fn reboot_domain(
         &mut self,
         uuid: Uuid,
-        vendor: &String,
-        product: &String,
-    ) -> Result<(), GRPCAPIError> {
+        modes: &[schema::schema::ShutdownMode],
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
         let response = self
             .client
-            .attach_device(AttachDeviceRequest {
-                domain_uuid: uuid.as_bytes().to_vec(),
-                vendor_id: vendor.clone(),
-                product_id: product.clone(),
+            .reboot_domain(RebootDomainRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                modes: modes.iter().map(|x| *x as i32).collect(),
+                wait: to_proto_wait(wait),
             })
             .await?;
 
-        let msg = response.into_inner();
-        if !msg.success {
-            return Err(GRPCAPIError::new(msg.error.unwrap()));
-        }
-
-        return Ok(());
+        return lifecycle_result(response.into_inner());
     }
 
-    async fn attach_usb_device_o(
+    async fn reset_domain(
         &mut self,
         uuid: Uuid,
-        device: &schema::schema::USBDevice,
-    ) -> Result<(), GRPCAPIError> {
-        self.attach_usb_device(uuid, &device.vendor_id, &device.product_id)
-            .await
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
+        let response = self
+            .client
+            .reset_domain(ResetDomainRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                wait: to_proto_wait(wait),
+            })
+            .await?;
+
+        return lifecycle_result(response.into_inner());
     }
 
-    async fn detach_usb_device(
+    async fn suspend_domain(
         &mut self,
         uuid: Uuid,
-        vendor: &String,
-        product: &String,
-    ) -> Result<(), GRPCAPIError> {
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
         let response = self
             .client
-            .detach_device(DetachDeviceRequest {
-                domain_uuid: uuid.as_bytes().to_vec(),
-                vendor_id: vendor.clone(),
-                product_id: product.clone(),
+            .suspend_domain(SuspendDomainRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                wait: to_proto_wait(wait),
             })
             .await?;
 
-        let msg = response.into_inner();
+        return lifecycle_result(response.into_inner());
+    }
+
+    async fn resume_domain(
+        &mut self,
+        uuid: Uuid,
+        wait: Option<schema::schema::WaitForState>,
+    ) -> Result<Option<schema::schema::WaitResult>, GRPCAPIError> {
+        let response = self
+            .client
+            .resume_domain(ResumeDomainRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                wait: to_proto_wait(wait),
+            })
+            .await?;
+
+        return lifecycle_result(response.into_inner());
+    }
+
+    async fn define_domain(&mut self, xml: &String) -> Result<Uuid, GRPCAPIError> {
+        let response = self
+            .client
+            .define_domain(DefineDomainRequest {
+                xml: xml.clone(),
+                validate: true,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(byte_vec_to_uuid(msg.uuid).unwrap());
+    }
+
+    async fn undefine_domain(
+        &mut self,
+        uuid: Uuid,
+        options: schema::schema::UndefineOptions,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .undefine_domain(UndefineDomainRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                managed_save: options.managed_save,
+                snapshots_metadata: options.snapshots_metadata,
+                nvram: options.nvram,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn create_transient_domain(
+        &mut self,
+        xml: &String,
+        start_paused: bool,
+    ) -> Result<Uuid, GRPCAPIError> {
+        let response = self
+            .client
+            .create_transient_domain(CreateTransientDomainRequest {
+                xml: xml.clone(),
+                start_paused,
+                autodestroy: false,
+                validate: true,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(byte_vec_to_uuid(msg.uuid).unwrap());
+    }
+
+    async fn get_domain_xml(&mut self, uuid: Uuid, inactive: bool) -> Result<String, GRPCAPIError> {
+        let response = self
+            .client
+            .get_domain_xml(GetDomainXmlRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                inactive,
+                secure: false,
+                migratable: false,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.xml);
+    }
+
+    async fn update_domain_xml(
+        &mut self,
+        uuid: Uuid,
+        xml: &String,
+    ) -> Result<schema::schema::DomainXMLUpdate, GRPCAPIError> {
+        let response = self
+            .client
+            .update_domain_xml(UpdateDomainXmlRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                xml: xml.clone(),
+                validate: true,
+                secure: false,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(schema::schema::DomainXMLUpdate {
+            xml_before: msg.xml_before,
+            xml_after: msg.xml_after,
+        });
+    }
+
+    async fn get_domain_xml_o(
+        &mut self,
+        uuid: Uuid,
+        inactive: bool,
+    ) -> Result<schema::domain_xml::DomainXML, GRPCAPIError> {
+        let xml = self.get_domain_xml(uuid, inactive).await?;
+
+        return schema::domain_xml::XMLElement::from_xml(&xml)
+            .map_err(|e| GRPCAPIError::new(e.to_string()));
+    }
+
+    async fn update_domain_xml_o(
+        &mut self,
+        uuid: Uuid,
+        domain: &schema::domain_xml::DomainXML,
+    ) -> Result<schema::domain_xml::DomainXML, GRPCAPIError> {
+        let xml = schema::domain_xml::XMLElement::to_xml(domain)
+            .map_err(|e| GRPCAPIError::new(e.to_string()))?;
+
+        let update = self.update_domain_xml(uuid, &xml).await?;
+
+        return schema::domain_xml::XMLElement::from_xml(&update.xml_after)
+            .map_err(|e| GRPCAPIError::new(e.to_string()));
+    }
+
+    async fn list_usb_devices(&mut self) -> Result<Vec<schema::schema::USBDevice>, GRPCAPIError> {
+        let mut stream = self
+            .client
+            .list_usb_devices(ListUsbDevicesRequest {})
+            .await?
+            .into_inner();
+
+        let mut res: Vec<schema::schema::USBDevice> = Vec::new();
+
+        while let Some(device) = stream.message().await? {
+            res.push(from_proto_usb_device(device))
+        }
+
+        return Ok(res);
+    }
+
+    async fn watch_usb_devices(
+        &mut self,
+        on_event: &mut USBDeviceEventHandler,
+    ) -> Result<(), GRPCAPIError> {
+        let mut stream = self
+            .client
+            .watch_usb_devices(WatchUsbDevicesRequest {})
+            .await?
+            .into_inner();
+
+        while let Some(event) = stream.message().await? {
+            let action = match UsbDeviceAction::from_i32(event.action) {
+                Some(UsbDeviceAction::Present) => schema::schema::USBDeviceAction::Present,
+                Some(UsbDeviceAction::Add) => schema::schema::USBDeviceAction::Add,
+                Some(UsbDeviceAction::Change) => schema::schema::USBDeviceAction::Change,
+                Some(UsbDeviceAction::Remove) => schema::schema::USBDeviceAction::Remove,
+                _ => continue,
+            };
+
+            let device = match event.device {
+                Some(x) => from_proto_usb_device(x),
+                None => continue,
+            };
+
+            if !on_event(action, device) {
+                break;
+            }
+        }
+
+        return Ok(());
+    }
+
+    async fn attach_usb_device(
+        &mut self,
+        uuid: Uuid,
+        vendor: &String,
+        product: &String,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .attach_device(AttachDeviceRequest {
+                domain_uuid: uuid.as_bytes().to_vec(),
+                vendor_id: vendor.clone(),
+                product_id: product.clone(),
+                selector: None,
+                scope: DeviceScope::Unspecified as i32,
+                force: false,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn attach_usb_device_o(
+        &mut self,
+        uuid: Uuid,
+        device: &schema::schema::USBDevice,
+    ) -> Result<(), GRPCAPIError> {
+        self.attach_usb_device(uuid, &device.vendor_id, &device.product_id)
+            .await
+    }
+
+    async fn detach_usb_device(
+        &mut self,
+        uuid: Uuid,
+        vendor: &String,
+        product: &String,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .detach_device(DetachDeviceRequest {
+                domain_uuid: uuid.as_bytes().to_vec(),
+                vendor_id: vendor.clone(),
+                product_id: product.clone(),
+                selector: None,
+                scope: DeviceScope::Unspecified as i32,
+            })
+            .await?;
+
+        let msg = response.into_inner();
         if !msg.success {
             return Err(GRPCAPIError::new(msg.error.unwrap()));
         }
@@ -231,6 +1076,647 @@ impl LibvirtAPIClient for GRPCLibvirtAPIClient {
         self.detach_usb_device(uuid, &device.vendor_id, &device.product_id)
             .await
     }
+
+    async fn attach_usb_device_by(
+        &mut self,
+        uuid: Uuid,
+        selector: &schema::schema::USBDeviceSelector,
+        scope: schema::schema::DeviceScope,
+        force: bool,
+    ) -> Result<(), GRPCAPIError> {
+        let (vendor_id, product_id, selector) = to_proto_selector(selector);
+
+        let response = self
+            .client
+            .attach_device(AttachDeviceRequest {
+                domain_uuid: uuid.as_bytes().to_vec(),
+                vendor_id,
+                product_id,
+                selector,
+                scope: scope as i32,
+                force,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn detach_usb_device_by(
+        &mut self,
+        uuid: Uuid,
+        selector: &schema::schema::USBDeviceSelector,
+        scope: schema::schema::DeviceScope,
+    ) -> Result<(), GRPCAPIError> {
+        let (vendor_id, product_id, selector) = to_proto_selector(selector);
+
+        let response = self
+            .client
+            .detach_device(DetachDeviceRequest {
+                domain_uuid: uuid.as_bytes().to_vec(),
+                vendor_id,
+                product_id,
+                selector,
+                scope: scope as i32,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn list_usb_rules(&mut self) -> Result<Vec<schema::schema::USBRule>, GRPCAPIError> {
+        let mut stream = self
+            .client
+            .list_usb_rules(ListUsbRulesRequest {})
+            .await?
+            .into_inner();
+
+        let mut res: Vec<schema::schema::USBRule> = Vec::new();
+
+        while let Some(rule) = stream.message().await? {
+            res.push(from_proto_usb_rule(rule))
+        }
+
+        return Ok(res);
+    }
+
+    async fn create_usb_rule(
+        &mut self,
+        rule: &schema::schema::USBRule,
+    ) -> Result<schema::schema::USBRule, GRPCAPIError> {
+        let response = self
+            .client
+            .create_usb_rule(CreateUsbRuleRequest {
+                rule: Some(to_proto_usb_rule(rule)),
+            })
+            .await?;
+
+        return usb_rule_result(response.into_inner());
+    }
+
+    async fn update_usb_rule(
+        &mut self,
+        rule: &schema::schema::USBRule,
+    ) -> Result<schema::schema::USBRule, GRPCAPIError> {
+        let response = self
+            .client
+            .update_usb_rule(UpdateUsbRuleRequest {
+                rule: Some(to_proto_usb_rule(rule)),
+            })
+            .await?;
+
+        return usb_rule_result(response.into_inner());
+    }
+
+    async fn delete_usb_rule(&mut self, id: Uuid) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .delete_usb_rule(DeleteUsbRuleRequest {
+                id: id.as_bytes().to_vec(),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn watch_domain_events(
+        &mut self,
+        uuid: Option<Uuid>,
+    ) -> Result<DomainEventStream, GRPCAPIError> {
+        let stream = self
+            .client
+            .watch_domain_events(WatchDomainEventsRequest {
+                uuid: uuid.map(|x| x.as_bytes().to_vec()).unwrap_or_default(),
+            })
+            .await?
+            .into_inner();
+
+        return Ok(Box::pin(stream.map(|x| match x {
+            Ok(event) => Ok(from_proto_domain_event(event)),
+            Err(e) => Err(GRPCAPIError::from(e)),
+        })));
+    }
+
+    async fn create_snapshot(
+        &mut self,
+        uuid: Uuid,
+        options: &schema::schema::SnapshotOptions,
+    ) -> Result<String, GRPCAPIError> {
+        let response = self
+            .client
+            .create_snapshot(CreateSnapshotRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                xml: options.xml.clone().unwrap_or_default(),
+                name: options.name.clone(),
+                description: options.description.clone(),
+                disk_only: options.disk_only,
+                quiesce: options.quiesce,
+                atomic: options.atomic,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.name);
+    }
+
+    async fn list_snapshots(
+        &mut self,
+        uuid: Uuid,
+    ) -> Result<Vec<schema::schema::Snapshot>, GRPCAPIError> {
+        let mut stream = self
+            .client
+            .list_snapshots(ListSnapshotsRequest {
+                uuid: uuid.as_bytes().to_vec(),
+            })
+            .await?
+            .into_inner();
+
+        let mut res: Vec<schema::schema::Snapshot> = Vec::new();
+
+        while let Some(snapshot) = stream.message().await? {
+            res.push(schema::schema::Snapshot {
+                name: snapshot.name,
+                description: snapshot.description,
+                parent: snapshot.parent,
+                children: snapshot.children,
+                state: snapshot.state,
+                creation_time: UNIX_EPOCH
+                    + Duration::from_secs(snapshot.creation_time.max(0) as u64),
+                current: snapshot.current,
+            })
+        }
+
+        return Ok(res);
+    }
+
+    async fn revert_to_snapshot(
+        &mut self,
+        uuid: Uuid,
+        name: &str,
+        options: schema::schema::SnapshotRevertOptions,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .revert_to_snapshot(RevertToSnapshotRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                name: name.to_string(),
+                running: options.running,
+                paused: options.paused,
+                force: options.force,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn delete_snapshot(
+        &mut self,
+        uuid: Uuid,
+        name: &str,
+        options: schema::schema::SnapshotDeleteOptions,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .delete_snapshot(DeleteSnapshotRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                name: name.to_string(),
+                children: options.children,
+                children_only: options.children_only,
+                metadata_only: options.metadata_only,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn get_snapshot_xml(&mut self, uuid: Uuid, name: &str) -> Result<String, GRPCAPIError> {
+        let response = self
+            .client
+            .get_snapshot_xml(GetSnapshotXmlRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                name: name.to_string(),
+                secure: false,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.xml);
+    }
+
+    async fn managed_save(
+        &mut self,
+        uuid: Uuid,
+        options: schema::schema::SaveOptions,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .managed_save(ManagedSaveRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                options: Some(to_proto_save_options(options)),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn has_managed_save(&mut self, uuid: Uuid) -> Result<bool, GRPCAPIError> {
+        let response = self
+            .client
+            .has_managed_save(HasManagedSaveRequest {
+                uuid: uuid.as_bytes().to_vec(),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.has_managed_save);
+    }
+
+    async fn remove_managed_save(&mut self, uuid: Uuid) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .remove_managed_save(RemoveManagedSaveRequest {
+                uuid: uuid.as_bytes().to_vec(),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn save_domain(
+        &mut self,
+        uuid: Uuid,
+        path: &str,
+        options: schema::schema::SaveOptions,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .save_domain(SaveDomainRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                path: path.to_string(),
+                options: Some(to_proto_save_options(options)),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn restore_domain(
+        &mut self,
+        path: &str,
+        xml: Option<&str>,
+        options: schema::schema::SaveOptions,
+    ) -> Result<(), GRPCAPIError> {
+        let response = self
+            .client
+            .restore_domain(RestoreDomainRequest {
+                path: path.to_string(),
+                xml: xml.unwrap_or_default().to_string(),
+                options: Some(to_proto_save_options(options)),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(());
+    }
+
+    async fn open_console(
+        &mut self,
+        uuid: Uuid,
+        dev_name: Option<&str>,
+        force: bool,
+    ) -> Result<(mpsc::Sender<Vec<u8>>, ConsoleOutputStream), GRPCAPIError> {
+        let open = ConsoleInput {
+            input: Some(console_input::Input::Open(ConsoleOpen {
+                uuid: uuid.as_bytes().to_vec(),
+                dev_name: dev_name.unwrap_or_default().to_string(),
+                force,
+            })),
+        };
+
+        let (tx, rx) = mpsc::channel(4);
+        let input =
+            tokio_stream::once(open).chain(ReceiverStream::new(rx).map(|data| ConsoleInput {
+                input: Some(console_input::Input::Data(data)),
+            }));
+
+        let stream = self.client.open_console(input).await?.into_inner();
+
+        return Ok((
+            tx,
+            Box::pin(stream.map(|x| match x {
+                Ok(output) => Ok(output.data),
+                Err(e) => Err(GRPCAPIError::from(e)),
+            })),
+        ));
+    }
+
+    async fn get_graphics_info(
+        &mut self,
+        uuid: Uuid,
+    ) -> Result<Vec<schema::schema::GraphicsDevice>, GRPCAPIError> {
+        let response = self
+            .client
+            .get_graphics_info(GetGraphicsInfoRequest {
+                uuid: uuid.as_bytes().to_vec(),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg
+            .devices
+            .into_iter()
+            .map(|x| schema::schema::GraphicsDevice {
+                index: x.index,
+                graphics_type: x.r#type,
+                listen_type: x.listen_type,
+                listen_address: x.listen_address,
+                listen_socket: x.listen_socket,
+                port: x.port,
+                autoport: x.autoport,
+                tls_port: x.tls_port,
+                tls: x.tls,
+                password_set: x.password_set,
+            })
+            .collect());
+    }
+
+    async fn create_graphics_token(
+        &mut self,
+        uuid: Uuid,
+        index: u32,
+    ) -> Result<schema::schema::GraphicsToken, GRPCAPIError> {
+        let response = self
+            .client
+            .create_graphics_token(CreateGraphicsTokenRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                index,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(schema::schema::GraphicsToken {
+            token: msg.token,
+            expires_in: Duration::from_secs(msg.expires_in_s as u64),
+        });
+    }
+
+    async fn get_screenshot(&mut self, uuid: Uuid, screen: u32) -> Result<Vec<u8>, GRPCAPIError> {
+        let response = self
+            .client
+            .get_screenshot(GetScreenshotRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                screen,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.png);
+    }
+
+    async fn get_interface_addresses(
+        &mut self,
+        uuid: Uuid,
+        source: schema::schema::InterfaceAddressSource,
+    ) -> Result<Vec<schema::schema::GuestInterface>, GRPCAPIError> {
+        let source = match source {
+            schema::schema::InterfaceAddressSource::Lease => InterfaceAddressSource::Lease,
+            schema::schema::InterfaceAddressSource::Agent => InterfaceAddressSource::Agent,
+            schema::schema::InterfaceAddressSource::ARP => InterfaceAddressSource::Arp,
+        };
+
+        let response = self
+            .client
+            .get_interface_addresses(GetInterfaceAddressesRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                source: source as i32,
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg
+            .interfaces
+            .into_iter()
+            .map(|x| schema::schema::GuestInterface {
+                name: x.name,
+                mac: x.mac,
+                addresses: x
+                    .addresses
+                    .into_iter()
+                    .map(|a| schema::schema::InterfaceAddress {
+                        address: a.address,
+                        prefix: a.prefix,
+                        ipv6: a.ipv6,
+                    })
+                    .collect(),
+            })
+            .collect());
+    }
+
+    async fn guest_exec(
+        &mut self,
+        uuid: Uuid,
+        command: &schema::schema::GuestCommand,
+    ) -> Result<GuestExecStream, GRPCAPIError> {
+        let stream = self
+            .client
+            .guest_exec(GuestExecRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                path: command.path.clone(),
+                args: command.args.clone(),
+                env: command.env.clone(),
+                stdin: command.stdin.clone(),
+                timeout_s: command.timeout.map_or(0, |x| x.as_secs() as u32),
+            })
+            .await?
+            .into_inner();
+
+        return Ok(Box::pin(stream.map(|x| match x {
+            Ok(output) => from_proto_guest_exec_output(output),
+            Err(e) => Err(GRPCAPIError::from(e)),
+        })));
+    }
+
+    async fn guest_read_file(&mut self, uuid: Uuid, path: &str) -> Result<Vec<u8>, GRPCAPIError> {
+        let mut stream = self
+            .client
+            .guest_read_file(GuestReadFileRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                path: path.to_string(),
+            })
+            .await?
+            .into_inner();
+
+        let mut data = Vec::new();
+        while let Some(chunk) = stream.message().await? {
+            data.extend(chunk.data);
+        }
+
+        return Ok(data);
+    }
+
+    async fn guest_write_file(
+        &mut self,
+        uuid: Uuid,
+        path: &str,
+        data: &[u8],
+        append: bool,
+    ) -> Result<u64, GRPCAPIError> {
+        let open = GuestWriteFileRequest {
+            input: Some(guest_write_file_request::Input::Open(GuestWriteFileOpen {
+                uuid: uuid.as_bytes().to_vec(),
+                path: path.to_string(),
+                append,
+            })),
+        };
+
+        let requests: Vec<GuestWriteFileRequest> = std::iter::once(open)
+            .chain(data.chunks(64 * 1024).map(|x| GuestWriteFileRequest {
+                input: Some(guest_write_file_request::Input::Data(x.to_vec())),
+            }))
+            .collect();
+
+        let response = self
+            .client
+            .guest_write_file(tokio_stream::iter(requests))
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.bytes_written);
+    }
+
+    async fn fs_freeze(&mut self, uuid: Uuid, mountpoints: &[String]) -> Result<u32, GRPCAPIError> {
+        let response = self
+            .client
+            .fs_freeze(FsFreezeRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                mountpoints: mountpoints.to_vec(),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.filesystems);
+    }
+
+    async fn fs_thaw(&mut self, uuid: Uuid) -> Result<u32, GRPCAPIError> {
+        let response = self
+            .client
+            .fs_thaw(FsThawRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                mountpoints: vec![],
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.filesystems);
+    }
+
+    async fn create_quiesced_snapshot(
+        &mut self,
+        uuid: Uuid,
+        options: &schema::schema::QuiescedSnapshotOptions,
+    ) -> Result<String, GRPCAPIError> {
+        let response = self
+            .client
+            .create_quiesced_snapshot(CreateQuiescedSnapshotRequest {
+                uuid: uuid.as_bytes().to_vec(),
+                xml: options.xml.clone().unwrap_or_default(),
+                name: options.name.clone(),
+                description: options.description.clone(),
+                atomic: options.atomic,
+                mountpoints: options.mountpoints.clone(),
+            })
+            .await?;
+
+        let msg = response.into_inner();
+        if !msg.success {
+            return Err(GRPCAPIError::new(msg.error.unwrap()));
+        }
+
+        return Ok(msg.name);
+    }
 }
 
 #[tokio::main]
@@ -244,7 +1730,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let test_vm = domains.iter()
         .find(|x| x.name == "vm-i686").unwrap();
 
-    client.create_domain(test_vm.uuid).await.unwrap();
+    client.create_domain(test_vm.uuid, None).await.unwrap();
 
     //
 
@@ -257,7 +1743,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     //
 
-    client.destroy_domain(test_vm.uuid).await.unwrap();
+    client.destroy_domain(test_vm.uuid, None).await.unwrap();
 
     client.detach_usb_device_o(test_vm.uuid, bluetooth).await.unwrap();
 
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use std::thread;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Status, Streaming};
+
+use crate::protoc::libvirt_api;
+use crate::protoc::libvirt_api::console_input::Input;
+use crate::virt_sys::VirStream;
+
+const CONSOLE_BUFFER_SIZE: usize = 4096;
+
+// Relays bytes between the gRPC streams and the console until either side hangs up. Each
+// direction waits for its previous chunk to be taken before reading the next one, so a slow
+// guest or a slow client is pushed back on instead of being buffered for.
+pub fn relay_console(
+    console: VirStream,
+    mut input: Streaming<libvirt_api::ConsoleInput>,
+) -> ReceiverStream<Result<libvirt_api::ConsoleOutput, Status>> {
+    let console = Arc::new(console);
+    let (tx, rx) = mpsc::channel(4);
+
+    {
+        let console = console.clone();
+
+        thread::spawn(move || {
+            let mut buf = vec![0; CONSOLE_BUFFER_SIZE];
+
+            loop {
+                match console.recv(&mut buf) {
+                    Ok(0) => {
+                        let _ = console.finish();
+                        break;
+                    }
+                    Ok(n) => {
+                        let output = libvirt_api::ConsoleOutput {
+                            data: buf[..n].to_vec(),
+                        };
+                        if tx.blocking_send(Ok(output)).is_err() {
+                            console.abort();
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        // After our own abort the error is expected and nobody is listening.
+                        if !console.is_aborted() {
+                            let _ = tx.blocking_send(Err(Status::aborted(e)));
+                        }
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    tokio::spawn(async move {
+        loop {
+            let data = match input.message().await {
+                Ok(Some(libvirt_api::ConsoleInput {
+                    input: Some(Input::Data(x)),
+                })) => x,
+                Ok(Some(_)) => continue,
+                Ok(None) | Err(_) => break,
+            };
+
+            let console = console.clone();
+            let sent = tokio::task::spawn_blocking(move || console.send_all(&data)).await;
+            match sent {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    eprintln!("console: failed to send input: {}", e);
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+
+        // The client closed its side; tear the console down so the receiving thread exits too.
+        console.abort();
+    });
+
+    ReceiverStream::new(rx)
+}
@@ -0,0 +1,315 @@
+use crate::protoc::libvirt_api;
+use crate::protoc::libvirt_api::domain_device::Device as ProtoDevice;
+use crate::schema::domain_xml::{Address, Device, DomainXML, DomainXMLError, HostDev, XMLElement};
+
+// Flattens the devices in a domain's XML into their proto messages, skipping the device kinds the
+// API doesn't model. USB hostdevs are matched up with `host_devices` so callers get names.
+pub fn list_domain_devices(
+    xml: &str,
+    host_devices: &[libvirt_api::UsbDevice],
+) -> Result<Vec<libvirt_api::DomainDevice>, DomainXMLError> {
+    let domain = DomainXML::from_xml(xml)?;
+
+    let devices = match domain.devices {
+        Some(x) => x.devices,
+        None => vec![],
+    };
+
+    Ok(devices
+        .iter()
+        .filter_map(|device| match device {
+            Device::Disk(x) => Some(ProtoDevice::Disk(libvirt_api::DomainDisk {
+                r#type: x.disk_type.clone(),
+                device: x.device.clone(),
+                source: x.source.clone().unwrap_or_default().into_iter().collect(),
+                target_dev: x.target_dev.clone(),
+                target_bus: x.target_bus.clone(),
+                readonly: x.readonly,
+                address: x.address.as_ref().map(proto_address),
+            })),
+            Device::Interface(x) => Some(ProtoDevice::Interface(libvirt_api::DomainInterface {
+                r#type: x.interface_type.clone(),
+                mac: x.mac.clone(),
+                source: x.source.clone().unwrap_or_default().into_iter().collect(),
+                model: x.model.clone(),
+                target_dev: x.target_dev.clone(),
+                address: x.address.as_ref().map(proto_address),
+            })),
+            Device::HostDev(x) => Some(ProtoDevice::Hostdev(libvirt_api::DomainHostDev {
+                mode: x.mode.clone(),
+                r#type: x.hostdev_type.clone(),
+                vendor_id: x.source.vendor_id.clone(),
+                product_id: x.source.product_id.clone(),
+                source_address: x.source.address.as_ref().map(proto_address),
+                address: x.address.as_ref().map(proto_address),
+                host_device: find_host_device(x, host_devices),
+            })),
+            Device::Controller(x) => Some(ProtoDevice::Controller(libvirt_api::DomainController {
+                r#type: x.controller_type.clone(),
+                index: x.index,
+                model: x.model.clone(),
+                address: x.address.as_ref().map(proto_address),
+            })),
+            // Reported by GetGraphicsInfo.
+            Device::Graphics(_) => None,
+            Device::Other { .. } => None,
+        })
+        .map(|x| libvirt_api::DomainDevice { device: Some(x) })
+        .collect())
+}
+
+// The domain's graphics devices in document order, which is the index libvirt uses for them.
+pub fn list_graphics_devices(
+    xml: &str,
+) -> Result<Vec<libvirt_api::GraphicsDevice>, DomainXMLError> {
+    let domain = DomainXML::from_xml(xml)?;
+
+    let devices = match domain.devices {
+        Some(x) => x.devices,
+        None => vec![],
+    };
+
+    Ok(devices
+        .iter()
+        .filter_map(|device| match device {
+            Device::Graphics(x) => Some(x),
+            _ => None,
+        })
+        .enumerate()
+        .map(|(i, x)| {
+            let listen = |name: &str| x.listen.as_ref().and_then(|l| l.get(name).cloned());
+            let tls_port = x.tls_port.filter(|x| *x > 0).map(|x| x as u32);
+
+            libvirt_api::GraphicsDevice {
+                index: i as u32,
+                r#type: x.graphics_type.clone(),
+                listen_type: listen("type"),
+                listen_address: listen("address"),
+                listen_socket: listen("socket"),
+                port: x.port.filter(|x| *x > 0).map(|x| x as u32),
+                autoport: x.autoport.as_deref() == Some("yes"),
+                tls_port,
+                tls: tls_port.is_some(),
+                password_set: x.passwd.is_some(),
+            }
+        })
+        .collect())
+}
+
+fn proto_address(address: &Address) -> libvirt_api::DeviceAddress {
+    libvirt_api::DeviceAddress {
+        r#type: address.address_type.clone(),
+        attributes: address.attributes.clone().into_iter().collect(),
+    }
+}
+
+// libvirt writes hex IDs as "0x1234" and bus/device numbers in decimal, udev has bare hex IDs.
+fn same_id(hostdev_id: &Option<String>, udev_id: &str) -> bool {
+    match hostdev_id {
+        Some(id) => id.trim_start_matches("0x").eq_ignore_ascii_case(udev_id),
+        None => true,
+    }
+}
+
+fn address_number(address: &Address, name: &str) -> Option<u32> {
+    let value = address.attributes.get(name)?;
+
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+// Prefers the hostdev's bus/device address, which libvirt fills in for running domains, and falls
+// back to vendor:product when it identifies exactly one plugged-in device.
+pub fn find_host_device(
+    hostdev: &HostDev,
+    host_devices: &[libvirt_api::UsbDevice],
+) -> Option<libvirt_api::UsbDevice> {
+    if hostdev.hostdev_type != "usb" {
+        return None;
+    }
+
+    let bus_address = hostdev
+        .source
+        .address
+        .as_ref()
+        .and_then(|x| Some((address_number(x, "bus")?, address_number(x, "device")?)));
+
+    let matches = host_devices
+        .iter()
+        .filter(|x| same_id(&hostdev.source.vendor_id, &x.vendor_id))
+        .filter(|x| same_id(&hostdev.source.product_id, &x.product_id))
+        .filter(|x| match bus_address {
+            Some((bus, device)) => x.bus == bus && x.device_number == device,
+            None => true,
+        })
+        .collect::<Vec<_>>();
+
+    match matches.len() {
+        1 => Some(matches[0].clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOMAIN_XML: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/domain.xml"
+    ));
+
+    fn usb_device(
+        vendor_id: &str,
+        product_id: &str,
+        bus: u32,
+        device: u32,
+    ) -> libvirt_api::UsbDevice {
+        libvirt_api::UsbDevice {
+            vendor_id: vendor_id.to_string(),
+            product_id: product_id.to_string(),
+            bus,
+            device_number: device,
+            ..Default::default()
+        }
+    }
+
+    fn usb_hostdev(xml: &str) -> HostDev {
+        HostDev::from_xml(xml).unwrap()
+    }
+
+    #[test]
+    fn find_host_device_prefers_the_bus_address() {
+        let hostdev = usb_hostdev(
+            "<hostdev mode='subsystem' type='usb'><source>\
+             <vendor id='0x8087'/><product id='0x0029'/><address bus='1' device='4'/>\
+             </source></hostdev>",
+        );
+        let host_devices = [
+            usb_device("8087", "0029", 1, 3),
+            usb_device("8087", "0029", 1, 4),
+        ];
+
+        let found = find_host_device(&hostdev, &host_devices).unwrap();
+        assert_eq!((found.bus, found.device_number), (1, 4));
+    }
+
+    #[test]
+    fn find_host_device_falls_back_to_a_unique_id_match() {
+        let hostdev = usb_hostdev(
+            "<hostdev mode='subsystem' type='usb'><source>\
+             <vendor id='0x046D'/><product id='0xc52b'/>\
+             </source></hostdev>",
+        );
+
+        let found = find_host_device(
+            &hostdev,
+            &[
+                usb_device("046d", "c52b", 2, 7),
+                usb_device("8087", "0029", 1, 4),
+            ],
+        )
+        .unwrap();
+        assert_eq!((found.bus, found.device_number), (2, 7));
+
+        let ambiguous = [
+            usb_device("046d", "c52b", 2, 7),
+            usb_device("046d", "c52b", 2, 8),
+        ];
+        assert!(find_host_device(&hostdev, &ambiguous).is_none());
+    }
+
+    #[test]
+    fn find_host_device_ignores_other_hostdev_types() {
+        let hostdev = usb_hostdev(
+            "<hostdev mode='subsystem' type='pci'><source>\
+             <address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/>\
+             </source></hostdev>",
+        );
+
+        assert!(find_host_device(&hostdev, &[usb_device("8087", "0029", 1, 0)]).is_none());
+    }
+
+    #[test]
+    fn list_domain_devices_skips_unmodelled_kinds() {
+        let host_devices = [usb_device("8087", "0029", 1, 4)];
+        let devices = list_domain_devices(DOMAIN_XML, &host_devices).unwrap();
+
+        let kinds = devices
+            .iter()
+            .map(|x| match x.device.as_ref().unwrap() {
+                ProtoDevice::Disk(_) => "disk",
+                ProtoDevice::Interface(_) => "interface",
+                ProtoDevice::Hostdev(_) => "hostdev",
+                ProtoDevice::Controller(_) => "controller",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "disk",
+                "disk",
+                "controller",
+                "controller",
+                "interface",
+                "hostdev"
+            ]
+        );
+
+        match devices[5].device.as_ref().unwrap() {
+            ProtoDevice::Hostdev(x) => assert!(x.host_device.is_some()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn list_graphics_devices_reads_the_fixture() {
+        let devices = list_graphics_devices(DOMAIN_XML).unwrap();
+
+        assert_eq!(
+            devices,
+            vec![libvirt_api::GraphicsDevice {
+                index: 0,
+                r#type: "vnc".to_string(),
+                listen_type: Some("address".to_string()),
+                listen_address: Some("127.0.0.1".to_string()),
+                listen_socket: None,
+                port: Some(5900),
+                autoport: true,
+                tls_port: None,
+                tls: false,
+                password_set: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn list_graphics_devices_indexes_in_document_order() {
+        let xml = "<domain type='kvm'><name>vm</name><devices>\
+                   <graphics type='spice' port='-1' tlsPort='5901' autoport='yes' passwd='x'>\
+                   <listen type='socket' socket='/run/spice.sock'/></graphics>\
+                   <video><model type='qxl'/></video>\
+                   <graphics type='vnc' port='5902' autoport='no'/>\
+                   </devices></domain>";
+        let devices = list_graphics_devices(xml).unwrap();
+
+        assert_eq!(devices.len(), 2);
+
+        let spice = &devices[0];
+        assert_eq!((spice.index, spice.r#type.as_str()), (0, "spice"));
+        assert_eq!(spice.port, None);
+        assert_eq!(spice.tls_port, Some(5901));
+        assert!(spice.tls && spice.autoport && spice.password_set);
+        assert_eq!(spice.listen_type.as_deref(), Some("socket"));
+        assert_eq!(spice.listen_socket.as_deref(), Some("/run/spice.sock"));
+        assert_eq!(spice.listen_address, None);
+
+        let vnc = &devices[1];
+        assert_eq!((vnc.index, vnc.r#type.as_str()), (1, "vnc"));
+        assert_eq!(vnc.port, Some(5902));
+        assert!(!vnc.tls && !vnc.autoport && !vnc.password_set);
+        assert_eq!(vnc.listen_type, None);
+    }
+}
@@ -0,0 +1,256 @@
+// Bridges libvirt's domain event callbacks, which the virt crate does not wrap, to a broadcast
+// channel that any number of WatchDomainEvents streams can subscribe to.
+
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::broadcast;
+use virt::connect::Connect;
+
+use crate::protoc::libvirt_api;
+use crate::protoc::libvirt_api::domain_event::Event;
+use crate::virt_sys::{c_string, domain_name, domain_uuid, VirConnectPtr, VirDomainPtr};
+
+// Events a subscriber may fall behind by before it starts missing some.
+const EVENT_BUFFER: usize = 256;
+
+// How long the event loop waits after a failed iteration, doubling while failures continue.
+const EVENT_LOOP_MIN_BACKOFF: Duration = Duration::from_millis(100);
+const EVENT_LOOP_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+// virDomainEventID
+const VIR_DOMAIN_EVENT_ID_LIFECYCLE: c_int = 0;
+const VIR_DOMAIN_EVENT_ID_REBOOT: c_int = 1;
+const VIR_DOMAIN_EVENT_ID_WATCHDOG: c_int = 3;
+const VIR_DOMAIN_EVENT_ID_IO_ERROR: c_int = 4;
+const VIR_DOMAIN_EVENT_ID_DEVICE_REMOVED: c_int = 15;
+const VIR_DOMAIN_EVENT_ID_AGENT_LIFECYCLE: c_int = 18;
+const VIR_DOMAIN_EVENT_ID_DEVICE_ADDED: c_int = 19;
+
+// libvirt takes every event callback as this type and calls it with the signature that belongs
+// to the registered event ID, the way VIR_DOMAIN_EVENT_CALLBACK() casts callbacks in C.
+type VirConnectDomainEventGenericCallback = *const c_void;
+type FreeCallback = unsafe extern "C" fn(*mut c_void);
+
+#[link(name = "virt")]
+extern "C" {
+    fn virEventRegisterDefaultImpl() -> c_int;
+    fn virEventRunDefaultImpl() -> c_int;
+    fn virConnectDomainEventRegisterAny(
+        conn: VirConnectPtr,
+        dom: VirDomainPtr,
+        event_id: c_int,
+        cb: VirConnectDomainEventGenericCallback,
+        opaque: *mut c_void,
+        freecb: Option<FreeCallback>,
+    ) -> c_int;
+}
+
+// Installs libvirt's default event loop and runs it on its own thread. Must be called before the
+// connection whose events we want is opened.
+pub fn start_event_loop() {
+    if unsafe { virEventRegisterDefaultImpl() } < 0 {
+        panic!("failed to register the libvirt event loop");
+    }
+
+    thread::spawn(|| {
+        let mut backoff = EVENT_LOOP_MIN_BACKOFF;
+
+        loop {
+            if unsafe { virEventRunDefaultImpl() } < 0 {
+                eprintln!(
+                    "libvirt event loop iteration failed: {}; retrying in {:?}",
+                    virt::error::Error::new().message,
+                    backoff
+                );
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(EVENT_LOOP_MAX_BACKOFF);
+            } else {
+                backoff = EVENT_LOOP_MIN_BACKOFF;
+            }
+        }
+    });
+}
+
+pub struct DomainEvents {
+    sender: broadcast::Sender<libvirt_api::DomainEvent>,
+}
+
+impl DomainEvents {
+    // Registers callbacks for all supported event types on every domain of `conn`. They stay
+    // registered for as long as the connection is open.
+    pub fn register(conn: &Connect) -> Result<DomainEvents, String> {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+
+        // SAFETY: each handler is an extern "C" fn with exactly the signature libvirt documents for
+        // the event ID it is paired with here (virConnectDomainEventCallback,
+        // virConnectDomainEventWatchdogCallback, ...), so libvirt calling it back through the
+        // generic pointer matches the ABI it was defined with.
+        let callbacks: [(c_int, VirConnectDomainEventGenericCallback); 7] = [
+            (
+                VIR_DOMAIN_EVENT_ID_LIFECYCLE,
+                on_lifecycle as VirConnectDomainEventGenericCallback,
+            ),
+            (
+                VIR_DOMAIN_EVENT_ID_REBOOT,
+                on_reboot as VirConnectDomainEventGenericCallback,
+            ),
+            (
+                VIR_DOMAIN_EVENT_ID_WATCHDOG,
+                on_watchdog as VirConnectDomainEventGenericCallback,
+            ),
+            (
+                VIR_DOMAIN_EVENT_ID_IO_ERROR,
+                on_io_error as VirConnectDomainEventGenericCallback,
+            ),
+            (
+                VIR_DOMAIN_EVENT_ID_DEVICE_ADDED,
+                on_device_added as VirConnectDomainEventGenericCallback,
+            ),
+            (
+                VIR_DOMAIN_EVENT_ID_DEVICE_REMOVED,
+                on_device_removed as VirConnectDomainEventGenericCallback,
+            ),
+            (
+                VIR_DOMAIN_EVENT_ID_AGENT_LIFECYCLE,
+                on_agent_lifecycle as VirConnectDomainEventGenericCallback,
+            ),
+        ];
+
+        for (event_id, callback) in callbacks.iter() {
+            // Each registration owns a sender handle, freed by libvirt when it is deregistered.
+            let opaque = Box::into_raw(Box::new(sender.clone())) as *mut c_void;
+
+            let id = unsafe {
+                virConnectDomainEventRegisterAny(
+                    conn.as_ptr() as VirConnectPtr,
+                    ptr::null_mut(),
+                    *event_id,
+                    *callback,
+                    opaque,
+                    Some(free_sender),
+                )
+            };
+
+            if id < 0 {
+                unsafe { free_sender(opaque) };
+                return Err(format!("failed to register domain event {}", event_id));
+            }
+        }
+
+        Ok(DomainEvents { sender })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<libvirt_api::DomainEvent> {
+        self.sender.subscribe()
+    }
+}
+
+unsafe extern "C" fn free_sender(opaque: *mut c_void) {
+    drop(Box::from_raw(
+        opaque as *mut broadcast::Sender<libvirt_api::DomainEvent>,
+    ));
+}
+
+unsafe fn publish(dom: VirDomainPtr, opaque: *mut c_void, event: Event) {
+    let sender = &*(opaque as *const broadcast::Sender<libvirt_api::DomainEvent>);
+
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis() as u64)
+        .unwrap_or(0);
+
+    // Sending only fails when nobody is subscribed, in which case the event is simply dropped.
+    let _ = sender.send(libvirt_api::DomainEvent {
+        uuid: domain_uuid(dom).as_bytes().to_vec(),
+        name: domain_name(dom),
+        timestamp_ms,
+        event: Some(event),
+    });
+}
+
+unsafe extern "C" fn on_lifecycle(
+    _: VirConnectPtr,
+    dom: VirDomainPtr,
+    event: c_int,
+    detail: c_int,
+    opaque: *mut c_void,
+) -> c_int {
+    let event = Event::Lifecycle(libvirt_api::DomainLifecycleEvent {
+        r#type: event + 1,
+        detail,
+    });
+    publish(dom, opaque, event);
+    0
+}
+
+unsafe extern "C" fn on_reboot(_: VirConnectPtr, dom: VirDomainPtr, opaque: *mut c_void) {
+    publish(
+        dom,
+        opaque,
+        Event::Reboot(libvirt_api::DomainRebootEvent {}),
+    );
+}
+
+unsafe extern "C" fn on_watchdog(
+    _: VirConnectPtr,
+    dom: VirDomainPtr,
+    action: c_int,
+    opaque: *mut c_void,
+) {
+    let event = Event::Watchdog(libvirt_api::DomainWatchdogEvent { action });
+    publish(dom, opaque, event);
+}
+
+unsafe extern "C" fn on_io_error(
+    _: VirConnectPtr,
+    dom: VirDomainPtr,
+    src_path: *const c_char,
+    dev_alias: *const c_char,
+    action: c_int,
+    opaque: *mut c_void,
+) {
+    let event = Event::IoError(libvirt_api::DomainIoErrorEvent {
+        src_path: c_string(src_path),
+        dev_alias: c_string(dev_alias),
+        action,
+    });
+    publish(dom, opaque, event);
+}
+
+unsafe extern "C" fn on_device_added(
+    _: VirConnectPtr,
+    dom: VirDomainPtr,
+    alias: *const c_char,
+    opaque: *mut c_void,
+) {
+    let event = Event::DeviceAdded(libvirt_api::DomainDeviceEvent {
+        alias: c_string(alias),
+    });
+    publish(dom, opaque, event);
+}
+
+unsafe extern "C" fn on_device_removed(
+    _: VirConnectPtr,
+    dom: VirDomainPtr,
+    alias: *const c_char,
+    opaque: *mut c_void,
+) {
+    let event = Event::DeviceRemoved(libvirt_api::DomainDeviceEvent {
+        alias: c_string(alias),
+    });
+    publish(dom, opaque, event);
+}
+
+unsafe extern "C" fn on_agent_lifecycle(
+    _: VirConnectPtr,
+    dom: VirDomainPtr,
+    state: c_int,
+    reason: c_int,
+    opaque: *mut c_void,
+) {
+    let event = Event::AgentLifecycle(libvirt_api::DomainAgentLifecycleEvent { state, reason });
+    publish(dom, opaque, event);
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::domain_stats::{
+    VIR_CONNECT_GET_ALL_DOMAINS_STATS_ACTIVE, VIR_DOMAIN_STATS_BALLOON, VIR_DOMAIN_STATS_BLOCK,
+    VIR_DOMAIN_STATS_CPU_TOTAL, VIR_DOMAIN_STATS_INTERFACE, VIR_DOMAIN_STATS_VCPU,
+};
+use crate::protoc::libvirt_api;
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::virt_sys::{get_all_domain_stats, DomainStatsRecord};
+
+// Resolution of the shared sampler, and so the shortest interval a subscriber can ask for.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+const SAMPLED_STATS: u32 = VIR_DOMAIN_STATS_CPU_TOTAL
+    | VIR_DOMAIN_STATS_BALLOON
+    | VIR_DOMAIN_STATS_VCPU
+    | VIR_DOMAIN_STATS_INTERFACE
+    | VIR_DOMAIN_STATS_BLOCK;
+
+// Cumulative counters of every running domain at one point in time.
+pub struct Sample {
+    taken_at: Instant,
+    timestamp: SystemTime,
+    domains: HashMap<Uuid, DomainStatsRecord>,
+}
+
+// Polls libvirt once per SAMPLE_INTERVAL on a single thread, however many streams are open, and
+// publishes the latest sample. It idles while nobody is subscribed.
+pub struct MetricsSampler {
+    latest: watch::Receiver<Option<Arc<Sample>>>,
+    subscribers: Arc<AtomicUsize>,
+}
+
+// Keeps the sampler running for as long as it is held.
+pub struct MetricsSubscription {
+    pub samples: watch::Receiver<Option<Arc<Sample>>>,
+    subscribers: Arc<AtomicUsize>,
+}
+
+impl Drop for MetricsSubscription {
+    fn drop(&mut self) {
+        self.subscribers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl MetricsSampler {
+    pub fn start(conn: Arc<ThreadSafeVirtConn>) -> MetricsSampler {
+        let (tx, rx) = watch::channel(None);
+        let subscribers = Arc::new(AtomicUsize::new(0));
+
+        {
+            let subscribers = subscribers.clone();
+
+            thread::spawn(move || loop {
+                if subscribers.load(Ordering::SeqCst) == 0 {
+                    thread::sleep(SAMPLE_INTERVAL);
+                    continue;
+                }
+
+                let started = Instant::now();
+                let records = get_all_domain_stats(
+                    &conn.lock(),
+                    SAMPLED_STATS,
+                    VIR_CONNECT_GET_ALL_DOMAINS_STATS_ACTIVE,
+                );
+
+                match records {
+                    Ok(records) => {
+                        let _ = tx.send(Some(Arc::new(Sample {
+                            taken_at: started,
+                            timestamp: SystemTime::now(),
+                            domains: records.into_iter().map(|x| (x.uuid, x)).collect(),
+                        })));
+                    }
+                    Err(e) => eprintln!("metrics sampler: {}", e),
+                }
+
+                thread::sleep(SAMPLE_INTERVAL.saturating_sub(started.elapsed()));
+            });
+        }
+
+        MetricsSampler {
+            latest: rx,
+            subscribers,
+        }
+    }
+
+    pub fn subscribe(&self) -> MetricsSubscription {
+        self.subscribers.fetch_add(1, Ordering::SeqCst);
+
+        MetricsSubscription {
+            samples: self.latest.clone(),
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+// Per-second rate of a cumulative counter. Counters that went backwards were reset by a domain
+// restart and yield 0 rather than a huge bogus rate.
+fn rate(before: u64, after: u64, elapsed: f64) -> f64 {
+    after.saturating_sub(before) as f64 / elapsed
+}
+
+// Indexed entries ("block.<n>.*", "net.<n>.*") keyed by their name, since indices shift when
+// devices are hotplugged between samples.
+fn by_name(record: &DomainStatsRecord, prefix: &str) -> HashMap<String, u64> {
+    (0..record.count(prefix))
+        .filter_map(|i| Some((record.string(&format!("{}.{}.name", prefix, i))?, i)))
+        .collect()
+}
+
+fn disk_metrics(
+    before: &DomainStatsRecord,
+    after: &DomainStatsRecord,
+    elapsed: f64,
+) -> Vec<libvirt_api::DiskMetrics> {
+    let previous = by_name(before, "block");
+
+    let mut disks = by_name(after, "block")
+        .into_iter()
+        .filter_map(|(name, i)| {
+            let j = *previous.get(&name)?;
+            let counter = |field: &str| {
+                rate(
+                    before.u64_or_zero(&format!("block.{}.{}", j, field)),
+                    after.u64_or_zero(&format!("block.{}.{}", i, field)),
+                    elapsed,
+                )
+            };
+
+            Some(libvirt_api::DiskMetrics {
+                read_iops: counter("rd.reqs"),
+                write_iops: counter("wr.reqs"),
+                read_bytes_per_sec: counter("rd.bytes"),
+                write_bytes_per_sec: counter("wr.bytes"),
+                name,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    disks.sort_by(|a, b| a.name.cmp(&b.name));
+    disks
+}
+
+fn interface_metrics(
+    before: &DomainStatsRecord,
+    after: &DomainStatsRecord,
+    elapsed: f64,
+) -> Vec<libvirt_api::InterfaceMetrics> {
+    let previous = by_name(before, "net");
+
+    let mut interfaces = by_name(after, "net")
+        .into_iter()
+        .filter_map(|(name, i)| {
+            let j = *previous.get(&name)?;
+            let counter = |field: &str| {
+                rate(
+                    before.u64_or_zero(&format!("net.{}.{}", j, field)),
+                    after.u64_or_zero(&format!("net.{}.{}", i, field)),
+                    elapsed,
+                )
+            };
+
+            Some(libvirt_api::InterfaceMetrics {
+                rx_bytes_per_sec: counter("rx.bytes"),
+                tx_bytes_per_sec: counter("tx.bytes"),
+                rx_packets_per_sec: counter("rx.pkts"),
+                tx_packets_per_sec: counter("tx.pkts"),
+                name,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
+
+// Rates for every domain present in both samples, optionally restricted to `uuids`.
+pub fn domain_metrics(
+    before: &Sample,
+    after: &Sample,
+    uuids: &[Uuid],
+) -> Vec<libvirt_api::DomainMetrics> {
+    let elapsed = after.taken_at.duration_since(before.taken_at);
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 {
+        return vec![];
+    }
+
+    let timestamp_ms = after
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis() as u64)
+        .unwrap_or(0);
+
+    after
+        .domains
+        .iter()
+        .filter(|(uuid, _)| uuids.is_empty() || uuids.contains(uuid))
+        .filter_map(|(uuid, now)| {
+            let then = before.domains.get(uuid)?;
+
+            Some(libvirt_api::DomainMetrics {
+                uuid: uuid.as_bytes().to_vec(),
+                name: now.name.clone(),
+                timestamp_ms,
+                interval_ms: elapsed.as_millis() as u64,
+                // cpu.time is in nanoseconds, so this is 100 per fully busy host CPU.
+                cpu_percent: rate(
+                    then.u64_or_zero("cpu.time"),
+                    now.u64_or_zero("cpu.time"),
+                    seconds,
+                ) / 1e7,
+                vcpus: now.u64_or_zero("vcpu.current") as u32,
+                memory_kib: now.u64_or_zero("balloon.current"),
+                disks: disk_metrics(then, now, seconds),
+                interfaces: interface_metrics(then, now, seconds),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::virt_sys::TypedParam;
+
+    fn record(uuid: Uuid, params: &[(&str, TypedParam)]) -> DomainStatsRecord {
+        DomainStatsRecord {
+            uuid,
+            name: uuid.to_string(),
+            params: params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        }
+    }
+
+    fn sample(taken_at: Instant, records: Vec<DomainStatsRecord>) -> Sample {
+        Sample {
+            taken_at,
+            timestamp: SystemTime::now(),
+            domains: records.into_iter().map(|x| (x.uuid, x)).collect(),
+        }
+    }
+
+    fn disk(i: u64, name: &str, read_bytes: u64) -> Vec<(String, TypedParam)> {
+        vec![
+            (
+                format!("block.{}.name", i),
+                TypedParam::String(name.to_string()),
+            ),
+            (
+                format!("block.{}.rd.bytes", i),
+                TypedParam::UInt(read_bytes),
+            ),
+        ]
+    }
+
+    fn with_disks(uuid: Uuid, disks: Vec<Vec<(String, TypedParam)>>) -> DomainStatsRecord {
+        let mut params = vec![(
+            "block.count".to_string(),
+            TypedParam::UInt(disks.len() as u64),
+        )];
+        params.extend(disks.into_iter().flatten());
+
+        DomainStatsRecord {
+            uuid,
+            name: uuid.to_string(),
+            params: params.into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn rate_saturates_counter_resets_to_zero() {
+        assert_eq!(rate(100, 300, 2.0), 100.0);
+        assert_eq!(rate(300, 100, 2.0), 0.0);
+    }
+
+    #[test]
+    fn by_name_follows_hotplug_reindexing() {
+        let uuid = Uuid::nil();
+        let before = with_disks(uuid, vec![disk(0, "vda", 1000), disk(1, "vdb", 5000)]);
+        // vda was unplugged, so vdb moved down to index 0 and a new vdc took index 1.
+        let after = with_disks(uuid, vec![disk(0, "vdb", 7000), disk(1, "vdc", 10)]);
+
+        assert_eq!(by_name(&after, "block")["vdb"], 0);
+        assert_eq!(by_name(&before, "block")["vdb"], 1);
+
+        let disks = disk_metrics(&before, &after, 2.0);
+        assert_eq!(disks.len(), 1);
+        assert_eq!(disks[0].name, "vdb");
+        assert_eq!(disks[0].read_bytes_per_sec, 1000.0);
+    }
+
+    #[test]
+    fn domain_metrics_filters_by_uuid() {
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let started = Instant::now();
+        let cpu = |ns: u64| [("cpu.time", TypedParam::UInt(ns))];
+
+        let before = sample(started, vec![record(a, &cpu(0)), record(b, &cpu(0))]);
+        let after = sample(
+            started + Duration::from_secs(1),
+            vec![record(a, &cpu(500_000_000)), record(b, &cpu(0))],
+        );
+
+        let all = domain_metrics(&before, &after, &[]);
+        assert_eq!(all.len(), 2);
+
+        let only_a = domain_metrics(&before, &after, &[a]);
+        assert_eq!(only_a.len(), 1);
+        assert_eq!(only_a[0].uuid, a.as_bytes().to_vec());
+        assert_eq!(only_a[0].cpu_percent, 50.0);
+        assert_eq!(only_a[0].interval_ms, 1000);
+    }
+
+    #[test]
+    fn domain_metrics_skips_domains_missing_from_the_earlier_sample() {
+        let a = Uuid::from_u128(1);
+        let started = Instant::now();
+
+        let before = sample(started, vec![]);
+        let after = sample(started + Duration::from_secs(1), vec![record(a, &[])]);
+
+        assert!(domain_metrics(&before, &after, &[]).is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::protoc::libvirt_api;
+use crate::virt_sys::DomainStatsRecord;
+
+// virDomainStatsTypes
+const VIR_DOMAIN_STATS_STATE: u32 = 1 << 0;
+pub const VIR_DOMAIN_STATS_CPU_TOTAL: u32 = 1 << 1;
+pub const VIR_DOMAIN_STATS_BALLOON: u32 = 1 << 2;
+pub const VIR_DOMAIN_STATS_VCPU: u32 = 1 << 3;
+pub const VIR_DOMAIN_STATS_INTERFACE: u32 = 1 << 4;
+pub const VIR_DOMAIN_STATS_BLOCK: u32 = 1 << 5;
+
+pub const ALL_DOMAIN_STATS: u32 = VIR_DOMAIN_STATS_STATE
+    | VIR_DOMAIN_STATS_CPU_TOTAL
+    | VIR_DOMAIN_STATS_BALLOON
+    | VIR_DOMAIN_STATS_VCPU
+    | VIR_DOMAIN_STATS_INTERFACE
+    | VIR_DOMAIN_STATS_BLOCK;
+
+// virConnectGetAllDomainStatsFlags
+pub const VIR_CONNECT_GET_ALL_DOMAINS_STATS_ACTIVE: u32 = 1 << 0;
+
+impl DomainStatsRecord {
+    pub fn u64(&self, key: &str) -> Option<u64> {
+        self.params.get(key).and_then(|x| x.as_u64())
+    }
+
+    pub fn u64_or_zero(&self, key: &str) -> u64 {
+        self.u64(key).unwrap_or(0)
+    }
+
+    pub fn string(&self, key: &str) -> Option<String> {
+        self.params
+            .get(key)
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string())
+    }
+
+    // Number of entries in an indexed group such as "net.<n>.*", from its "<prefix>.count" field.
+    pub fn count(&self, prefix: &str) -> u64 {
+        self.u64_or_zero(&format!("{}.count", prefix))
+    }
+}
+
+fn cpu_stats(record: &DomainStatsRecord) -> Option<libvirt_api::DomainCpuStats> {
+    Some(libvirt_api::DomainCpuStats {
+        time_ns: record.u64("cpu.time")?,
+        user_ns: record.u64_or_zero("cpu.user"),
+        system_ns: record.u64_or_zero("cpu.system"),
+    })
+}
+
+fn balloon_stats(record: &DomainStatsRecord) -> Option<libvirt_api::DomainBalloonStats> {
+    Some(libvirt_api::DomainBalloonStats {
+        current_kib: record.u64("balloon.current")?,
+        maximum_kib: record.u64_or_zero("balloon.maximum"),
+        swap_in_kib: record.u64("balloon.swap_in"),
+        swap_out_kib: record.u64("balloon.swap_out"),
+        major_faults: record.u64("balloon.major_fault"),
+        minor_faults: record.u64("balloon.minor_fault"),
+        unused_kib: record.u64("balloon.unused"),
+        available_kib: record.u64("balloon.available"),
+        usable_kib: record.u64("balloon.usable"),
+        rss_kib: record.u64("balloon.rss"),
+    })
+}
+
+// vcpu.<n>.* is only reported for online vCPUs, so indices can have gaps up to vcpu.maximum.
+fn vcpu_stats(record: &DomainStatsRecord) -> Vec<libvirt_api::DomainVcpuStats> {
+    (0..record.u64_or_zero("vcpu.maximum"))
+        .filter_map(|i| {
+            let key = |field: &str| format!("vcpu.{}.{}", i, field);
+
+            Some(libvirt_api::DomainVcpuStats {
+                index: i as u32,
+                state: record.params.get(&key("state"))?.as_i64()? as i32,
+                time_ns: record.u64_or_zero(&key("time")),
+                wait_ns: record.u64(&key("wait")),
+            })
+        })
+        .collect()
+}
+
+fn interface_stats(record: &DomainStatsRecord) -> Vec<libvirt_api::DomainInterfaceStats> {
+    (0..record.count("net"))
+        .map(|i| {
+            let key = |field: &str| format!("net.{}.{}", i, field);
+
+            libvirt_api::DomainInterfaceStats {
+                name: record.string(&key("name")).unwrap_or_default(),
+                rx_bytes: record.u64_or_zero(&key("rx.bytes")),
+                rx_packets: record.u64_or_zero(&key("rx.pkts")),
+                rx_errors: record.u64_or_zero(&key("rx.errs")),
+                rx_drops: record.u64_or_zero(&key("rx.drop")),
+                tx_bytes: record.u64_or_zero(&key("tx.bytes")),
+                tx_packets: record.u64_or_zero(&key("tx.pkts")),
+                tx_errors: record.u64_or_zero(&key("tx.errs")),
+                tx_drops: record.u64_or_zero(&key("tx.drop")),
+            }
+        })
+        .collect()
+}
+
+fn block_stats(record: &DomainStatsRecord) -> Vec<libvirt_api::DomainBlockStats> {
+    (0..record.count("block"))
+        .map(|i| {
+            let key = |field: &str| format!("block.{}.{}", i, field);
+
+            libvirt_api::DomainBlockStats {
+                name: record.string(&key("name")).unwrap_or_default(),
+                path: record.string(&key("path")),
+                read_requests: record.u64_or_zero(&key("rd.reqs")),
+                read_bytes: record.u64_or_zero(&key("rd.bytes")),
+                read_time_ns: record.u64_or_zero(&key("rd.times")),
+                write_requests: record.u64_or_zero(&key("wr.reqs")),
+                write_bytes: record.u64_or_zero(&key("wr.bytes")),
+                write_time_ns: record.u64_or_zero(&key("wr.times")),
+                flush_requests: record.u64_or_zero(&key("fl.reqs")),
+                flush_time_ns: record.u64_or_zero(&key("fl.times")),
+                allocation: record.u64(&key("allocation")),
+                capacity: record.u64(&key("capacity")),
+                physical: record.u64(&key("physical")),
+            }
+        })
+        .collect()
+}
+
+pub fn to_proto_domain_stats(record: &DomainStatsRecord) -> libvirt_api::DomainStats {
+    libvirt_api::DomainStats {
+        uuid: record.uuid.as_bytes().to_vec(),
+        name: record.name.clone(),
+        // virDomainState starts at NOSTATE = 0, one below the proto enum.
+        state: record
+            .params
+            .get("state.state")
+            .and_then(|x| x.as_i64())
+            .map(|x| x as i32 + 1),
+        cpu: cpu_stats(record),
+        balloon: balloon_stats(record),
+        vcpu_current: record.u64_or_zero("vcpu.current") as u32,
+        vcpu_maximum: record.u64_or_zero("vcpu.maximum") as u32,
+        vcpus: vcpu_stats(record),
+        interfaces: interface_stats(record),
+        blocks: block_stats(record),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::virt_sys::TypedParam;
+
+    fn record(params: &[(&str, TypedParam)]) -> DomainStatsRecord {
+        DomainStatsRecord {
+            uuid: Uuid::nil(),
+            name: "vm".to_string(),
+            params: params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn state_is_offset_into_the_proto_enum() {
+        let running = record(&[("state.state", TypedParam::Int(1))]);
+        assert_eq!(
+            to_proto_domain_stats(&running).state,
+            Some(libvirt_api::DomainState::Running as i32)
+        );
+
+        let nostate = record(&[("state.state", TypedParam::Int(0))]);
+        assert_eq!(
+            to_proto_domain_stats(&nostate).state,
+            Some(libvirt_api::DomainState::Nostate as i32)
+        );
+
+        assert_eq!(to_proto_domain_stats(&record(&[])).state, None);
+    }
+
+    #[test]
+    fn vcpus_skip_offline_indices() {
+        let stats = to_proto_domain_stats(&record(&[
+            ("vcpu.current", TypedParam::UInt(2)),
+            ("vcpu.maximum", TypedParam::UInt(4)),
+            ("vcpu.0.state", TypedParam::Int(1)),
+            ("vcpu.0.time", TypedParam::UInt(100)),
+            ("vcpu.2.state", TypedParam::Int(1)),
+            ("vcpu.2.time", TypedParam::UInt(300)),
+            ("vcpu.2.wait", TypedParam::UInt(7)),
+        ]));
+
+        assert_eq!(stats.vcpu_current, 2);
+        assert_eq!(stats.vcpu_maximum, 4);
+        assert_eq!(
+            stats
+                .vcpus
+                .iter()
+                .map(|x| (x.index, x.time_ns, x.wait_ns))
+                .collect::<Vec<_>>(),
+            vec![(0, 100, None), (2, 300, Some(7))]
+        );
+    }
+
+    #[test]
+    fn groups_missing_from_the_record_are_left_out() {
+        let stats = to_proto_domain_stats(&record(&[
+            ("cpu.time", TypedParam::UInt(1000)),
+            ("net.count", TypedParam::UInt(1)),
+            ("net.0.name", TypedParam::String("vnet0".to_string())),
+            ("net.0.rx.bytes", TypedParam::UInt(42)),
+        ]));
+
+        assert_eq!(stats.cpu.unwrap().time_ns, 1000);
+        assert!(stats.balloon.is_none());
+        assert_eq!(stats.interfaces.len(), 1);
+        assert_eq!(stats.interfaces[0].name, "vnet0");
+        assert_eq!(stats.interfaces[0].rx_bytes, 42);
+        assert!(stats.blocks.is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use std::ffi::CString;
+use std::sync::Arc;
+
+use uuid::Uuid;
+use virt::domain::Domain;
+use virt::domain_snapshot::DomainSnapshot;
+
+use crate::guest_agent::AgentError;
+use crate::protoc::libvirt_api;
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::virt_sys::{fs_freeze, fs_thaw};
+
+fn c_mountpoints(mountpoints: &[String]) -> Result<Vec<CString>, AgentError> {
+    mountpoints
+        .iter()
+        .map(|x| {
+            CString::new(x.as_str())
+                .map_err(|_| AgentError::new(format!("mountpoint '{}' contains a NUL byte", x)))
+        })
+        .collect()
+}
+
+// An empty `mountpoints` freezes every guest filesystem. Returns how many were frozen.
+pub fn freeze_filesystems(domain: &Domain, mountpoints: &[String]) -> Result<u32, AgentError> {
+    Ok(fs_freeze(domain, &c_mountpoints(mountpoints)?)?)
+}
+
+// Thaws every frozen guest filesystem.
+pub fn thaw_filesystems(domain: &Domain) -> Result<u32, AgentError> {
+    Ok(fs_thaw(domain)?)
+}
+
+// Frozen guest filesystems, thawed on drop if `thaw` was never reached.
+struct Frozen<'a> {
+    domain: &'a Domain,
+    thawed: bool,
+}
+
+impl<'a> Frozen<'a> {
+    fn thaw(mut self) -> Result<u32, AgentError> {
+        self.thawed = true;
+        Ok(fs_thaw(self.domain)?)
+    }
+}
+
+impl<'a> Drop for Frozen<'a> {
+    fn drop(&mut self) {
+        if !self.thawed {
+            let _ = fs_thaw(self.domain);
+        }
+    }
+}
+
+fn snapshot_frozen(
+    domain: &Domain,
+    xml: &str,
+    flags: u32,
+    mountpoints: &[String],
+) -> libvirt_api::QuiescedSnapshotResponse {
+    let failure = |e: AgentError| libvirt_api::QuiescedSnapshotResponse {
+        success: false,
+        error: Some(e.message),
+        agent_not_connected: e.not_connected,
+        ..Default::default()
+    };
+
+    let mountpoints = match c_mountpoints(mountpoints) {
+        Ok(x) => x,
+        Err(e) => return failure(e),
+    };
+
+    // qemu-ga thaws whatever it froze when a freeze fails part way, so there is nothing to undo.
+    let frozen = match fs_freeze(domain, &mountpoints) {
+        Ok(x) => x,
+        Err(e) => return failure(AgentError::from(e)),
+    };
+    let guard = Frozen {
+        domain,
+        thawed: false,
+    };
+
+    let snapshot = DomainSnapshot::create_xml(domain, xml, flags).and_then(|x| x.get_name());
+    let thaw = guard.thaw();
+
+    let mut response = libvirt_api::QuiescedSnapshotResponse {
+        success: snapshot.is_ok() && thaw.is_ok(),
+        frozen,
+        thawed: *thaw.as_ref().unwrap_or(&0),
+        ..Default::default()
+    };
+
+    match (snapshot, thaw) {
+        (Ok(name), Ok(_)) => response.name = name,
+        (Ok(name), Err(e)) => {
+            response.error = Some(format!(
+                "snapshot '{}' was created but thawing failed: {}",
+                name, e.message
+            ));
+            response.agent_not_connected = e.not_connected;
+            response.name = name;
+        }
+        (Err(e), Ok(_)) => response.error = Some(e.message),
+        (Err(e), Err(thaw_e)) => {
+            response.error = Some(format!(
+                "{}; thawing afterwards also failed: {}",
+                e.message, thaw_e.message
+            ));
+            response.agent_not_connected = thaw_e.not_connected;
+        }
+    }
+
+    response
+}
+
+// Freezes, snapshots and thaws on a blocking task. Dropping the handler future when the client
+// disconnects does not cancel the task, so the guest is not left frozen. Nothing thaws it if the
+// server itself dies in between, as qemu-ga has no freeze timeout.
+pub async fn quiesced_snapshot(
+    conn: Arc<ThreadSafeVirtConn>,
+    uuid: Uuid,
+    xml: String,
+    flags: u32,
+    mountpoints: Vec<String>,
+) -> libvirt_api::QuiescedSnapshotResponse {
+    let task = tokio::task::spawn_blocking(move || {
+        let domain = match Domain::lookup_by_uuid_string(&conn.lock(), &uuid.to_string()) {
+            Ok(x) => x,
+            Err(e) => {
+                return libvirt_api::QuiescedSnapshotResponse {
+                    error: Some(format!(
+                        "failed to look up domain with UUID '{}': {}",
+                        uuid, e.message
+                    )),
+                    ..Default::default()
+                }
+            }
+        };
+
+        snapshot_frozen(&domain, &xml, flags, &mountpoints)
+    });
+
+    match task.await {
+        Ok(x) => x,
+        Err(e) => libvirt_api::QuiescedSnapshotResponse {
+            error: Some(e.to_string()),
+            ..Default::default()
+        },
+    }
+}
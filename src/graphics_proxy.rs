@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::os::unix::io::FromRawFd;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::{SinkExt, StreamExt};
+use http::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UnixStream};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::virt_sys::open_graphics_fd;
+
+// How long a token from CreateGraphicsToken can be used to open a connection.
+pub const GRAPHICS_TOKEN_TTL: Duration = Duration::from_secs(30);
+
+// virDomainOpenGraphicsFlags
+const VIR_DOMAIN_OPEN_GRAPHICS_SKIPAUTH: u32 = 1 << 0;
+
+const PROXY_BUFFER_SIZE: usize = 16 * 1024;
+
+struct GraphicsToken {
+    uuid: Uuid,
+    index: u32,
+    expires_at: Instant,
+}
+
+// Single-use tokens that each let one websocket connect to one graphics device.
+#[derive(Default)]
+pub struct GraphicsTokens {
+    tokens: Mutex<HashMap<String, GraphicsToken>>,
+}
+
+impl GraphicsTokens {
+    pub fn issue(&self, uuid: Uuid, index: u32) -> String {
+        let token = Uuid::new_v4().to_simple().to_string();
+        let now = Instant::now();
+
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.retain(|_, x| x.expires_at > now);
+        tokens.insert(
+            token.clone(),
+            GraphicsToken {
+                uuid,
+                index,
+                expires_at: now + GRAPHICS_TOKEN_TTL,
+            },
+        );
+
+        token
+    }
+
+    fn redeem(&self, token: &str) -> Option<(Uuid, u32)> {
+        let token = self.tokens.lock().unwrap().remove(token)?;
+
+        match token.expires_at > Instant::now() {
+            true => Some((token.uuid, token.index)),
+            false => None,
+        }
+    }
+}
+
+fn query_token(request: &Request) -> Option<String> {
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .filter_map(|x| x.split_once('='))
+        .find(|(k, _)| *k == "token")
+        .map(|(_, v)| v.to_string())
+}
+
+fn reject(status: StatusCode, message: &str) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(message.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    conn: Arc<ThreadSafeVirtConn>,
+    tokens: Arc<GraphicsTokens>,
+) -> Result<(), String> {
+    let mut target = None;
+
+    let websocket =
+        tokio_tungstenite::accept_hdr_async(stream, |request: &Request, mut response: Response| {
+            target = query_token(request).and_then(|x| tokens.redeem(&x));
+            if target.is_none() {
+                return Err(reject(StatusCode::FORBIDDEN, "invalid or expired token"));
+            }
+
+            // Older noVNC versions only speak the "binary" subprotocol and give up unless it is
+            // echoed back.
+            let binary = request
+                .headers()
+                .get_all("Sec-WebSocket-Protocol")
+                .iter()
+                .filter_map(|x| x.to_str().ok())
+                .flat_map(|x| x.split(','))
+                .any(|x| x.trim() == "binary");
+            if binary {
+                response
+                    .headers_mut()
+                    .insert("Sec-WebSocket-Protocol", "binary".parse().unwrap());
+            }
+
+            Ok(response)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (uuid, index) = target.unwrap();
+
+    // The token already authorised the caller, so the VNC password is skipped.
+    let fd = {
+        let conn = conn.lock();
+        let domain = virt::domain::Domain::lookup_by_uuid_string(&conn, &uuid.to_string())
+            .map_err(|e| e.message)?;
+        open_graphics_fd(&domain, index, VIR_DOMAIN_OPEN_GRAPHICS_SKIPAUTH)?
+    };
+
+    let socket = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd) };
+    socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let socket = UnixStream::from_std(socket).map_err(|e| e.to_string())?;
+
+    let (mut socket_rx, mut socket_tx) = socket.into_split();
+    let (mut websocket_tx, mut websocket_rx) = websocket.split();
+
+    let to_domain = async {
+        while let Some(message) = websocket_rx.next().await {
+            match message {
+                Ok(Message::Binary(data)) => {
+                    if socket_tx.write_all(&data).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(Message::Close(_)) | Err(_) => break,
+                Ok(_) => {}
+            }
+        }
+    };
+
+    let to_client = async {
+        let mut buf = vec![0; PROXY_BUFFER_SIZE];
+
+        loop {
+            let n = match socket_rx.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+
+            if websocket_tx
+                .send(Message::Binary(buf[..n].to_vec()))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+
+        let _ = websocket_tx.close().await;
+    };
+
+    // Whichever side hangs up first ends the session; dropping the halves closes the other.
+    tokio::select! {
+        _ = to_domain => {}
+        _ = to_client => {}
+    }
+
+    Ok(())
+}
+
+// Tunnels websocket connections to domains' VNC servers for clients like noVNC. Clients connect to
+// ws://<addr>/?token=<token> with a token from CreateGraphicsToken.
+pub async fn serve_graphics_proxy(
+    addr: SocketAddr,
+    conn: Arc<ThreadSafeVirtConn>,
+    tokens: Arc<GraphicsTokens>,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let conn = conn.clone();
+        let tokens = tokens.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, conn, tokens).await {
+                eprintln!("graphics proxy: connection from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str) -> Request {
+        Request::builder().uri(uri).body(()).unwrap()
+    }
+
+    #[test]
+    fn tokens_redeem_once() {
+        let tokens = GraphicsTokens::default();
+        let uuid = Uuid::new_v4();
+
+        let token = tokens.issue(uuid, 1);
+        assert_ne!(tokens.issue(uuid, 1), token);
+
+        assert_eq!(tokens.redeem(&token), Some((uuid, 1)));
+        assert_eq!(tokens.redeem(&token), None);
+        assert_eq!(tokens.redeem("unknown"), None);
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected_and_pruned() {
+        let tokens = GraphicsTokens::default();
+        let uuid = Uuid::new_v4();
+
+        for name in &["stale", "also-stale"] {
+            tokens.tokens.lock().unwrap().insert(
+                name.to_string(),
+                GraphicsToken {
+                    uuid,
+                    index: 0,
+                    expires_at: Instant::now() - Duration::from_secs(1),
+                },
+            );
+        }
+        assert_eq!(tokens.redeem("stale"), None);
+
+        // Issuing a token sweeps out the ones that can no longer be redeemed.
+        let token = tokens.issue(uuid, 0);
+        let issued = tokens.tokens.lock().unwrap();
+        assert_eq!(issued.keys().collect::<Vec<_>>(), vec![&token]);
+    }
+
+    #[test]
+    fn query_token_finds_the_token_parameter() {
+        assert_eq!(
+            query_token(&request("/?token=abc")),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            query_token(&request("/websockify?path=x&token=abc&y=1")),
+            Some("abc".to_string())
+        );
+        assert_eq!(query_token(&request("/?tokens=abc")), None);
+        assert_eq!(query_token(&request("/?token")), None);
+        assert_eq!(query_token(&request("/")), None);
+    }
+}
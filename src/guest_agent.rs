@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+use tonic::Status;
+use uuid::Uuid;
+use virt::domain::Domain;
+
+use crate::protoc::libvirt_api;
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::virt_sys::{get_guest_info, interface_addresses, qemu_agent_command, TypedParam};
+
+// virErrorNumber
+const VIR_ERR_AGENT_UNRESPONSIVE: i32 = 86;
+const VIR_ERR_AGENT_UNSYNCED: i32 = 97;
+
+// virDomainQemuAgentCommandTimeoutValues
+const VIR_DOMAIN_QEMU_AGENT_COMMAND_DEFAULT: i32 = -1;
+
+// A failed guest agent call, with whether it failed because the agent is not there to answer.
+#[derive(Debug)]
+pub struct AgentError {
+    pub message: String,
+    pub not_connected: bool,
+}
+
+impl From<virt::error::Error> for AgentError {
+    fn from(e: virt::error::Error) -> Self {
+        AgentError {
+            not_connected: e.code == VIR_ERR_AGENT_UNRESPONSIVE || e.code == VIR_ERR_AGENT_UNSYNCED,
+            message: e.message,
+        }
+    }
+}
+
+impl AgentError {
+    pub fn new(message: String) -> AgentError {
+        AgentError {
+            message,
+            not_connected: false,
+        }
+    }
+
+    // For streaming RPCs, which have no response message to carry agent_not_connected.
+    pub fn to_status(&self) -> Status {
+        match self.not_connected {
+            true => Status::unavailable(self.message.clone()),
+            false => Status::internal(self.message.clone()),
+        }
+    }
+}
+
+fn agent_request(execute: &str, arguments: Value) -> String {
+    let mut command = Map::new();
+    command.insert("execute".to_string(), execute.into());
+    if !arguments.is_null() {
+        command.insert("arguments".to_string(), arguments);
+    }
+
+    Value::Object(command).to_string()
+}
+
+fn agent_reply(execute: &str, reply: &str) -> Result<Value, AgentError> {
+    let mut reply: Value = serde_json::from_str(reply)
+        .map_err(|e| AgentError::new(format!("invalid reply to {}: {}", execute, e)))?;
+
+    Ok(reply["return"].take())
+}
+
+// Runs a guest agent command and returns the "return" member of its reply. Errors reported by the
+// agent come back from libvirt as ordinary errors.
+pub fn agent_command(
+    domain: &Domain,
+    execute: &str,
+    arguments: Value,
+) -> Result<Value, AgentError> {
+    let reply = qemu_agent_command(
+        domain,
+        &agent_request(execute, arguments),
+        VIR_DOMAIN_QEMU_AGENT_COMMAND_DEFAULT,
+    )?;
+
+    agent_reply(execute, &reply)
+}
+
+// agent_command for async tasks: the domain is looked up and the command run on a blocking thread.
+pub async fn spawn_agent_command(
+    conn: Arc<ThreadSafeVirtConn>,
+    uuid: Uuid,
+    execute: &'static str,
+    arguments: Value,
+) -> Result<Value, AgentError> {
+    tokio::task::spawn_blocking(move || {
+        let domain = Domain::lookup_by_uuid_string(&conn.lock(), &uuid.to_string())?;
+        agent_command(&domain, execute, arguments)
+    })
+    .await
+    .map_err(|e| AgentError::new(e.to_string()))?
+}
+
+pub fn guest_interfaces(
+    domain: &Domain,
+    source: u32,
+) -> Result<Vec<libvirt_api::GuestInterface>, AgentError> {
+    Ok(interface_addresses(domain, source)?
+        .into_iter()
+        .map(|x| libvirt_api::GuestInterface {
+            name: x.name,
+            mac: x.hwaddr,
+            addresses: x
+                .addrs
+                .into_iter()
+                .map(|a| libvirt_api::InterfaceAddress {
+                    address: a.addr,
+                    prefix: a.prefix,
+                    ipv6: a.ipv6,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+struct GuestInfo(HashMap<String, TypedParam>);
+
+impl GuestInfo {
+    fn u64(&self, key: &str) -> Option<u64> {
+        self.0.get(key).and_then(|x| x.as_u64())
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        self.0
+            .get(key)
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string())
+    }
+
+    fn count(&self, prefix: &str) -> u64 {
+        self.u64(&format!("{}.count", prefix)).unwrap_or(0)
+    }
+
+    fn has_prefix(&self, prefix: &str) -> bool {
+        self.0.keys().any(|x| x.starts_with(prefix))
+    }
+
+    fn os(&self) -> Option<libvirt_api::GuestOsInfo> {
+        if !self.has_prefix("os.") {
+            return None;
+        }
+
+        let key = |field: &str| self.string(&format!("os.{}", field));
+
+        Some(libvirt_api::GuestOsInfo {
+            id: key("id"),
+            name: key("name"),
+            pretty_name: key("pretty-name"),
+            version: key("version"),
+            version_id: key("version-id"),
+            kernel_release: key("kernel-release"),
+            kernel_version: key("kernel-version"),
+            machine: key("machine"),
+            variant: key("variant"),
+            variant_id: key("variant-id"),
+        })
+    }
+
+    fn timezone(&self) -> Option<libvirt_api::GuestTimezone> {
+        if !self.has_prefix("timezone.") {
+            return None;
+        }
+
+        Some(libvirt_api::GuestTimezone {
+            name: self.string("timezone.name"),
+            offset_s: self
+                .0
+                .get("timezone.offset")
+                .and_then(|x| x.as_i64())
+                .unwrap_or(0) as i32,
+        })
+    }
+
+    fn users(&self) -> Vec<libvirt_api::GuestUser> {
+        (0..self.count("user"))
+            .map(|i| {
+                let key = |field: &str| format!("user.{}.{}", i, field);
+
+                libvirt_api::GuestUser {
+                    name: self.string(&key("name")).unwrap_or_default(),
+                    domain: self.string(&key("domain")),
+                    login_time_ms: self.u64(&key("login-time")).unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    fn filesystems(&self) -> Vec<libvirt_api::GuestFilesystem> {
+        (0..self.count("fs"))
+            .map(|i| {
+                let key = |field: &str| format!("fs.{}.{}", i, field);
+
+                libvirt_api::GuestFilesystem {
+                    mountpoint: self.string(&key("mountpoint")).unwrap_or_default(),
+                    name: self.string(&key("name")).unwrap_or_default(),
+                    fstype: self.string(&key("fstype")).unwrap_or_default(),
+                    total_bytes: self.u64(&key("total-bytes")),
+                    used_bytes: self.u64(&key("used-bytes")),
+                    disks: (0..self.count(&key("disk")))
+                        .map(|j| {
+                            let key = |field: &str| format!("fs.{}.disk.{}.{}", i, j, field);
+
+                            libvirt_api::GuestFilesystemDisk {
+                                alias: self.string(&key("alias")),
+                                serial: self.string(&key("serial")),
+                                device: self.string(&key("device")),
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    fn disks(&self) -> Vec<libvirt_api::GuestDisk> {
+        (0..self.count("disk"))
+            .map(|i| {
+                let key = |field: &str| format!("disk.{}.{}", i, field);
+
+                libvirt_api::GuestDisk {
+                    name: self.string(&key("name")).unwrap_or_default(),
+                    partition: self
+                        .0
+                        .get(&key("partition"))
+                        .and_then(|x| x.as_bool())
+                        .unwrap_or(false),
+                    dependencies: (0..self.count(&key("dependency")))
+                        .filter_map(|j| self.string(&format!("disk.{}.dependency.{}.name", i, j)))
+                        .collect(),
+                    serial: self.string(&key("serial")),
+                    alias: self.string(&key("alias")),
+                    guest_alias: self.string(&key("guest_alias")),
+                }
+            })
+            .collect()
+    }
+}
+
+// `types` is a mask of virDomainGuestInfoTypes; 0 asks for everything the agent supports.
+pub fn guest_info(
+    domain: &Domain,
+    types: u32,
+) -> Result<libvirt_api::GuestInfoResponse, AgentError> {
+    let info = GuestInfo(get_guest_info(domain, types)?);
+
+    Ok(libvirt_api::GuestInfoResponse {
+        success: true,
+        error: None,
+        agent_not_connected: false,
+        os: info.os(),
+        timezone: info.timezone(),
+        hostname: info.string("hostname"),
+        users: info.users(),
+        filesystems: info.filesystems(),
+        disks: info.disks(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn guest_info(params: &[(&str, TypedParam)]) -> GuestInfo {
+        GuestInfo(
+            params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn agent_request_omits_null_arguments() {
+        let request: Value =
+            serde_json::from_str(&agent_request("guest-ping", Value::Null)).unwrap();
+        assert_eq!(request, json!({ "execute": "guest-ping" }));
+
+        let request: Value =
+            serde_json::from_str(&agent_request("guest-exec-status", json!({ "pid": 12 })))
+                .unwrap();
+        assert_eq!(
+            request,
+            json!({ "execute": "guest-exec-status", "arguments": { "pid": 12 } })
+        );
+    }
+
+    #[test]
+    fn agent_reply_takes_the_return_member() {
+        let reply = agent_reply("guest-exec", r#"{"return": {"pid": 12}}"#).unwrap();
+        assert_eq!(reply, json!({ "pid": 12 }));
+
+        assert_eq!(agent_reply("guest-ping", "{}").unwrap(), Value::Null);
+
+        let e = agent_reply("guest-exec", "{\"return\":").unwrap_err();
+        assert!(e.message.starts_with("invalid reply to guest-exec"));
+        assert!(!e.not_connected);
+    }
+
+    #[test]
+    fn agent_errors_report_a_missing_agent() {
+        let status = AgentError {
+            message: "agent is not connected".to_string(),
+            not_connected: true,
+        }
+        .to_status();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+
+        let status = AgentError::new("failed".to_string()).to_status();
+        assert_eq!(status.code(), tonic::Code::Internal);
+    }
+
+    #[test]
+    fn guest_info_reads_os_and_timezone() {
+        let info = guest_info(&[
+            ("os.id", TypedParam::String("fedora".to_string())),
+            ("os.version-id", TypedParam::String("33".to_string())),
+            ("timezone.name", TypedParam::String("CET".to_string())),
+            ("timezone.offset", TypedParam::Int(3600)),
+            ("hostname", TypedParam::String("vm".to_string())),
+        ]);
+
+        let os = info.os().unwrap();
+        assert_eq!(os.id.as_deref(), Some("fedora"));
+        assert_eq!(os.version_id.as_deref(), Some("33"));
+        assert_eq!(os.name, None);
+
+        let timezone = info.timezone().unwrap();
+        assert_eq!(timezone.name.as_deref(), Some("CET"));
+        assert_eq!(timezone.offset_s, 3600);
+
+        let empty = guest_info(&[]);
+        assert!(empty.os().is_none());
+        assert!(empty.timezone().is_none());
+    }
+
+    #[test]
+    fn guest_info_reads_indexed_entries() {
+        let info = guest_info(&[
+            ("user.count", TypedParam::UInt(1)),
+            ("user.0.name", TypedParam::String("root".to_string())),
+            ("user.0.login-time", TypedParam::UInt(1600000000000)),
+            ("fs.count", TypedParam::UInt(1)),
+            ("fs.0.mountpoint", TypedParam::String("/".to_string())),
+            ("fs.0.name", TypedParam::String("vda1".to_string())),
+            ("fs.0.fstype", TypedParam::String("ext4".to_string())),
+            ("fs.0.total-bytes", TypedParam::UInt(1024)),
+            ("fs.0.disk.count", TypedParam::UInt(1)),
+            ("fs.0.disk.0.alias", TypedParam::String("vda".to_string())),
+            ("disk.count", TypedParam::UInt(2)),
+            ("disk.0.name", TypedParam::String("/dev/vda".to_string())),
+            ("disk.0.partition", TypedParam::Bool(false)),
+            ("disk.1.name", TypedParam::String("/dev/vda1".to_string())),
+            ("disk.1.partition", TypedParam::Bool(true)),
+            ("disk.1.dependency.count", TypedParam::UInt(1)),
+            (
+                "disk.1.dependency.0.name",
+                TypedParam::String("/dev/vda".to_string()),
+            ),
+        ]);
+
+        let users = info.users();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].name, "root");
+        assert_eq!(users[0].domain, None);
+        assert_eq!(users[0].login_time_ms, 1600000000000);
+
+        let filesystems = info.filesystems();
+        assert_eq!(filesystems.len(), 1);
+        assert_eq!(filesystems[0].fstype, "ext4");
+        assert_eq!(filesystems[0].total_bytes, Some(1024));
+        assert_eq!(filesystems[0].used_bytes, None);
+        assert_eq!(filesystems[0].disks[0].alias.as_deref(), Some("vda"));
+
+        let disks = info.disks();
+        assert_eq!(disks.len(), 2);
+        assert!(!disks[0].partition && disks[0].dependencies.is_empty());
+        assert!(disks[1].partition);
+        assert_eq!(disks[1].dependencies, vec!["/dev/vda".to_string()]);
+    }
+}
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Status;
+use uuid::Uuid;
+use virt::domain::Domain;
+
+use crate::guest_agent::{agent_command, spawn_agent_command, AgentError};
+use crate::protoc::libvirt_api;
+use crate::protoc::libvirt_api::guest_exec_output::Output;
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+
+// guest-exec-status is polled with a backoff between these bounds, so short commands return
+// quickly without long ones hammering the agent.
+const POLL_INTERVAL_MIN: Duration = Duration::from_millis(50);
+const POLL_INTERVAL_MAX: Duration = Duration::from_secs(1);
+
+const OUTPUT_CHUNK_SIZE: usize = 64 * 1024;
+
+// Stops every process in the tree below "$1" before killing it, so none can start a replacement.
+const KILL_TREE_SCRIPT: &str = r#"t() {
+    kill -STOP "$1"
+    for c in $(pgrep -P "$1"); do t "$c"; done
+    kill -KILL "$1"
+}
+t "$1""#;
+
+fn exec_arguments(request: &libvirt_api::GuestExecRequest) -> Value {
+    let mut arguments = json!({
+        "path": request.path,
+        "arg": request.args,
+        "env": request.env,
+        "capture-output": true,
+    });
+    if !request.stdin.is_empty() {
+        arguments["input-data"] = base64::encode(&request.stdin).into();
+    }
+
+    arguments
+}
+
+// Starts the process in the guest with its output captured and returns its guest PID.
+pub fn start_guest_exec(
+    domain: &Domain,
+    request: &libvirt_api::GuestExecRequest,
+) -> Result<i64, AgentError> {
+    let reply = agent_command(domain, "guest-exec", exec_arguments(request))?;
+
+    reply["pid"]
+        .as_i64()
+        .ok_or_else(|| AgentError::new("guest-exec reply has no pid".to_string()))
+}
+
+// qemu-ga keeps a finished process around until its status has been read once. Returns that final
+// status, which carries the captured output.
+async fn reap(conn: &Arc<ThreadSafeVirtConn>, uuid: Uuid, pid: i64) -> Option<Value> {
+    for _ in 0..10 {
+        let status = spawn_agent_command(
+            conn.clone(),
+            uuid,
+            "guest-exec-status",
+            json!({ "pid": pid }),
+        )
+        .await;
+        match status {
+            Ok(x) if x["exited"] == true => return Some(x),
+            Ok(_) => tokio::time::sleep(POLL_INTERVAL_MIN).await,
+            Err(_) => return None,
+        }
+    }
+
+    None
+}
+
+// The agent has no command to signal a process, so kill it, and everything it started, with
+// another guest-exec. Returns the process's final status, as `reap` does.
+async fn kill(conn: &Arc<ThreadSafeVirtConn>, uuid: Uuid, pid: i64) -> Option<Value> {
+    let windows = spawn_agent_command(conn.clone(), uuid, "guest-get-osinfo", Value::Null)
+        .await
+        .map(|x| x["id"] == "mswindows")
+        .unwrap_or(false);
+
+    let pid_arg = pid.to_string();
+    let (path, args) = match windows {
+        true => ("taskkill.exe", vec!["/F", "/T", "/PID", pid_arg.as_str()]),
+        false => (
+            "sh",
+            vec!["-c", KILL_TREE_SCRIPT, "kill-tree", pid_arg.as_str()],
+        ),
+    };
+
+    let killer = spawn_agent_command(
+        conn.clone(),
+        uuid,
+        "guest-exec",
+        json!({ "path": path, "arg": args }),
+    )
+    .await;
+    if let Err(e) = &killer {
+        eprintln!("guest exec: failed to kill pid {}: {}", pid, e.message);
+    }
+
+    let status = reap(conn, uuid, pid).await;
+    if let Some(killer) = killer.ok().and_then(|x| x["pid"].as_i64()) {
+        reap(conn, uuid, killer).await;
+    }
+
+    status
+}
+
+fn output_chunks(status: &Value, field: &str, output: fn(Vec<u8>) -> Output) -> Vec<Output> {
+    let data = status[field]
+        .as_str()
+        .and_then(|x| base64::decode(x).ok())
+        .unwrap_or_default();
+
+    data.chunks(OUTPUT_CHUNK_SIZE)
+        .map(|x| output(x.to_vec()))
+        .collect()
+}
+
+// Maps a guest-exec-status reply for an exited process. The status is null when a killed process
+// could not be reaped, which leaves only `timed_out` to report.
+fn exec_exit(status: &Value, timed_out: bool) -> libvirt_api::GuestExecExit {
+    libvirt_api::GuestExecExit {
+        exit_code: status["exitcode"].as_i64().map(|x| x as i32),
+        signal: status["signal"].as_i64().map(|x| x as i32),
+        timed_out,
+        stdout_truncated: status["out-truncated"].as_bool().unwrap_or(false),
+        stderr_truncated: status["err-truncated"].as_bool().unwrap_or(false),
+    }
+}
+
+// Waits for the process to exit, then streams its stdout, its stderr and finally its exit status.
+// qemu-ga only hands captured output over once the process has exited. The process is killed if
+// it outlives `timeout`, after which whatever output it had produced is still streamed, or if the
+// client goes away.
+pub fn relay_guest_exec(
+    conn: Arc<ThreadSafeVirtConn>,
+    uuid: Uuid,
+    pid: i64,
+    timeout: Option<Duration>,
+) -> ReceiverStream<Result<libvirt_api::GuestExecOutput, Status>> {
+    let (tx, rx) = mpsc::channel(4);
+
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let mut interval = POLL_INTERVAL_MIN;
+
+        let status = loop {
+            tokio::select! {
+                _ = tx.closed() => {
+                    let _ = kill(&conn, uuid, pid).await;
+                    return;
+                }
+                _ = tokio::time::sleep(interval) => {}
+            }
+            interval = (interval * 2).min(POLL_INTERVAL_MAX);
+
+            let status = spawn_agent_command(
+                conn.clone(),
+                uuid,
+                "guest-exec-status",
+                json!({ "pid": pid }),
+            )
+            .await;
+            match status {
+                Ok(x) if x["exited"] == true => break (x, false),
+                Ok(_) => {}
+                Err(e) => {
+                    let _ = tx.send(Err(e.to_status())).await;
+                    return;
+                }
+            }
+
+            if matches!(timeout, Some(x) if started.elapsed() >= x) {
+                // Whatever the process wrote before it was killed is still worth sending.
+                break (kill(&conn, uuid, pid).await.unwrap_or(Value::Null), true);
+            }
+        };
+        let (status, timed_out) = status;
+
+        let outputs = output_chunks(&status, "out-data", Output::Stdout)
+            .into_iter()
+            .chain(output_chunks(&status, "err-data", Output::Stderr))
+            .chain(std::iter::once(Output::Exit(exec_exit(&status, timed_out))));
+
+        for output in outputs {
+            let message = libvirt_api::GuestExecOutput {
+                output: Some(output),
+            };
+            if tx.send(Ok(message)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_arguments_only_sends_input_when_there_is_some() {
+        let mut request = libvirt_api::GuestExecRequest {
+            path: "/bin/cat".to_string(),
+            args: vec!["-n".to_string()],
+            env: vec!["LANG=C".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            exec_arguments(&request),
+            json!({
+                "path": "/bin/cat",
+                "arg": ["-n"],
+                "env": ["LANG=C"],
+                "capture-output": true,
+            })
+        );
+
+        request.stdin = b"hello".to_vec();
+        assert_eq!(exec_arguments(&request)["input-data"], "aGVsbG8=");
+    }
+
+    #[test]
+    fn output_chunks_splits_decoded_output() {
+        let data = vec![7; OUTPUT_CHUNK_SIZE + 1];
+        let status = json!({ "out-data": base64::encode(&data) });
+
+        let chunks = output_chunks(&status, "out-data", Output::Stdout);
+        assert_eq!(chunks.len(), 2);
+        match (&chunks[0], &chunks[1]) {
+            (Output::Stdout(a), Output::Stdout(b)) => {
+                assert_eq!(a.len(), OUTPUT_CHUNK_SIZE);
+                assert_eq!(b, &vec![7]);
+            }
+            _ => unreachable!(),
+        }
+
+        assert!(output_chunks(&status, "err-data", Output::Stderr).is_empty());
+        assert!(output_chunks(&json!({ "out-data": "%%" }), "out-data", Output::Stdout).is_empty());
+    }
+
+    #[test]
+    fn exec_exit_maps_the_final_status() {
+        let exit = exec_exit(
+            &json!({ "exited": true, "exitcode": 3, "out-truncated": true }),
+            false,
+        );
+        assert_eq!(
+            exit,
+            libvirt_api::GuestExecExit {
+                exit_code: Some(3),
+                signal: None,
+                timed_out: false,
+                stdout_truncated: true,
+                stderr_truncated: false,
+            }
+        );
+
+        let exit = exec_exit(&json!({ "exited": true, "signal": 9 }), true);
+        assert_eq!(
+            (exit.exit_code, exit.signal, exit.timed_out),
+            (None, Some(9), true)
+        );
+
+        let exit = exec_exit(&Value::Null, true);
+        assert_eq!(
+            (exit.exit_code, exit.signal, exit.timed_out),
+            (None, None, true)
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Status;
+use uuid::Uuid;
+
+use crate::guest_agent::{spawn_agent_command, AgentError};
+use crate::protoc::libvirt_api;
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+
+// Bytes moved per guest-file-read/write call. Base64 makes each agent message a third larger,
+// and this keeps it well inside what libvirt and qemu-ga will pass in one reply.
+const FILE_CHUNK_SIZE: usize = 48 * 1024;
+
+// A file opened in the guest through the agent. It must be closed explicitly, since closing is
+// another agent call.
+pub struct GuestFile {
+    conn: Arc<ThreadSafeVirtConn>,
+    uuid: Uuid,
+    handle: i64,
+}
+
+impl GuestFile {
+    // `mode` is an fopen() mode string.
+    pub async fn open(
+        conn: Arc<ThreadSafeVirtConn>,
+        uuid: Uuid,
+        path: &str,
+        mode: &str,
+    ) -> Result<GuestFile, AgentError> {
+        let reply = spawn_agent_command(
+            conn.clone(),
+            uuid,
+            "guest-file-open",
+            json!({ "path": path, "mode": mode }),
+        )
+        .await?;
+
+        let handle = reply
+            .as_i64()
+            .ok_or_else(|| AgentError::new("guest-file-open reply has no handle".to_string()))?;
+
+        Ok(GuestFile { conn, uuid, handle })
+    }
+
+    // The next chunk and whether the end of the file was reached.
+    pub async fn read(&self) -> Result<(Vec<u8>, bool), AgentError> {
+        let reply = spawn_agent_command(
+            self.conn.clone(),
+            self.uuid,
+            "guest-file-read",
+            json!({ "handle": self.handle, "count": FILE_CHUNK_SIZE }),
+        )
+        .await?;
+
+        read_reply(&reply)
+    }
+
+    pub async fn write_all(&self, data: &[u8]) -> Result<(), AgentError> {
+        for chunk in data.chunks(FILE_CHUNK_SIZE) {
+            let mut chunk = chunk;
+
+            // The agent may write less than it was given.
+            while !chunk.is_empty() {
+                let reply = spawn_agent_command(
+                    self.conn.clone(),
+                    self.uuid,
+                    "guest-file-write",
+                    json!({ "handle": self.handle, "buf-b64": base64::encode(chunk) }),
+                )
+                .await?;
+
+                chunk = &chunk[write_reply(&reply)?.min(chunk.len())..];
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn close(self) -> Result<(), AgentError> {
+        spawn_agent_command(
+            self.conn.clone(),
+            self.uuid,
+            "guest-file-close",
+            json!({ "handle": self.handle }),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+fn read_reply(reply: &Value) -> Result<(Vec<u8>, bool), AgentError> {
+    let data = base64::decode(reply["buf-b64"].as_str().unwrap_or_default())
+        .map_err(|e| AgentError::new(format!("invalid guest-file-read data: {}", e)))?;
+
+    let eof = reply["eof"].as_bool().unwrap_or(data.is_empty());
+    Ok((data, eof))
+}
+
+// The number of bytes the agent wrote, which must be more than none for the write to go anywhere.
+fn write_reply(reply: &Value) -> Result<usize, AgentError> {
+    match reply["count"].as_u64().unwrap_or(0) {
+        0 => Err(AgentError::new(
+            "guest-file-write made no progress".to_string(),
+        )),
+        x => Ok(x as usize),
+    }
+}
+
+// Streams the file's contents, then closes it. The file is also closed if the client goes away.
+pub fn relay_guest_file(
+    file: GuestFile,
+) -> ReceiverStream<Result<libvirt_api::GuestFileChunk, Status>> {
+    let (tx, rx) = mpsc::channel(4);
+
+    tokio::spawn(async move {
+        loop {
+            let (data, eof) = match file.read().await {
+                Ok(x) => x,
+                Err(e) => {
+                    let _ = tx.send(Err(e.to_status())).await;
+                    break;
+                }
+            };
+
+            if !data.is_empty()
+                && tx
+                    .send(Ok(libvirt_api::GuestFileChunk { data }))
+                    .await
+                    .is_err()
+            {
+                break;
+            }
+            if eof {
+                break;
+            }
+        }
+
+        if let Err(e) = file.close().await {
+            eprintln!("guest file: failed to close handle: {}", e.message);
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_reply_decodes_data_and_eof() {
+        let (data, eof) =
+            read_reply(&json!({ "count": 5, "buf-b64": "aGVsbG8=", "eof": false })).unwrap();
+        assert_eq!((data.as_slice(), eof), (&b"hello"[..], false));
+
+        let (data, eof) = read_reply(&json!({ "count": 0, "buf-b64": "", "eof": true })).unwrap();
+        assert!(data.is_empty() && eof);
+    }
+
+    #[test]
+    fn read_reply_treats_an_empty_read_without_eof_as_the_end() {
+        let (data, eof) = read_reply(&json!({ "buf-b64": "" })).unwrap();
+        assert!(data.is_empty() && eof);
+
+        let (_, eof) = read_reply(&json!({ "buf-b64": "aGk=" })).unwrap();
+        assert!(!eof);
+    }
+
+    #[test]
+    fn read_reply_rejects_invalid_base64() {
+        assert!(read_reply(&json!({ "buf-b64": "%%", "eof": true })).is_err());
+    }
+
+    #[test]
+    fn write_reply_requires_progress() {
+        assert_eq!(
+            write_reply(&json!({ "count": 3, "eof": false })).unwrap(),
+            3
+        );
+        assert!(write_reply(&json!({ "count": 0 })).is_err());
+        assert!(write_reply(&json!({})).is_err());
+    }
+}
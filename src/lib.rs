@@ -4,11 +4,12 @@ use std::fmt;
 use std::fmt::Formatter;
 use std::num::ParseIntError;
 
-mod schema;
+pub mod schema;
 
 use tonic::Status;
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::os::unix::io::AsRawFd;
 
 pub fn byte_vec_to_uuid(vec: Vec<u8>) -> Result<Uuid, Box<dyn error::Error>> {
     let bytes: [u8; 16] = vec.try_into().unwrap();
@@ -17,54 +18,155 @@ pub fn byte_vec_to_uuid(vec: Vec<u8>) -> Result<Uuid, Box<dyn error::Error>> {
     return Ok(uuid);
 }
 
+// Accepts a USB vendor or product ID as four hex digits, optionally prefixed with "0x", and
+// returns it lowercased without the prefix. Anything else is rejected so IDs can't smuggle
+// markup into the hostdev XML handed to libvirt.
+pub fn normalize_usb_id(id: &str) -> Result<String, Box<dyn error::Error>> {
+    let digits = id
+        .strip_prefix("0x")
+        .or_else(|| id.strip_prefix("0X"))
+        .unwrap_or(id);
+
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' is not a 4-digit hexadecimal USB ID", id).into());
+    }
+
+    Ok(digits.to_ascii_lowercase())
+}
+
+// Builds a USBDevice from a udev device's properties, or None for entries that aren't whole
+// devices (interfaces, hubs without IDs, ...).
+fn usb_device_from_udev(device: &libudev::Device) -> Option<schema::schema::USBDevice> {
+    let mut props: HashMap<String, String> = HashMap::new();
+
+    for x in device.properties() {
+        props.insert(
+            x.name().to_string_lossy().to_string(),
+            x.value().to_string_lossy().to_string(),
+        );
+    }
+
+    // Interfaces carry the parent's IDs but have no device node of their own.
+    if !props.contains_key("ID_MODEL_ID")
+        || !props.contains_key("ID_VENDOR_ID")
+        || !props.contains_key("DEVNAME")
+    {
+        return None;
+    }
+
+    Some(schema::schema::USBDevice {
+        device: props["DEVNAME"].clone(),
+        model: props.get("ID_MODEL").cloned().unwrap_or_default(),
+        vendor_id: props["ID_VENDOR_ID"].clone(),
+        product_id: props["ID_MODEL_ID"].clone(),
+        vendor_name: match props.contains_key("ID_VENDOR_FROM_DATABASE") {
+            true => Some(props["ID_VENDOR_FROM_DATABASE"].clone()),
+            false => None,
+        },
+        model_name: match props.contains_key("ID_MODEL_FROM_DATABASE") {
+            true => Some(props["ID_MODEL_FROM_DATABASE"].clone()),
+            false => None,
+        },
+        bus: props
+            .get("BUSNUM")
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(0),
+        device_number: props
+            .get("DEVNUM")
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(0),
+        port_path: device
+            .sysname()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        serial: props.get("ID_SERIAL_SHORT").cloned(),
+        attached_to: None,
+    })
+}
+
 pub fn enumerate_usb_devices() -> Result<Vec<schema::schema::USBDevice>, Box<dyn error::Error>> {
-    let context = libudev::Context::new().unwrap();
-    let mut enumerator = libudev::Enumerator::new(&context).unwrap();
+    let context = libudev::Context::new()?;
+    let mut enumerator = libudev::Enumerator::new(&context)?;
 
-    enumerator.match_subsystem("usb").unwrap();
+    enumerator.match_subsystem("usb")?;
 
     let mut result: Vec<schema::schema::USBDevice> = vec![];
 
-    for device in enumerator.scan_devices().unwrap() {
-        let mut props: HashMap<String, String> = HashMap::new();
+    for device in enumerator.scan_devices()? {
+        if let Some(x) = usb_device_from_udev(&device) {
+            result.push(x);
+        }
+    }
+
+    Ok(result)
+}
+
+// Reports every USB device currently plugged in as `Present`, then blocks on a udev monitor and
+// reports hotplug events as they arrive. Returns once `on_event` returns false or `is_cancelled`
+// returns true; the latter is checked at least once per second even when nothing happens.
+pub fn watch_usb_devices<F, C>(
+    mut on_event: F,
+    is_cancelled: C,
+) -> Result<(), Box<dyn error::Error>>
+where
+    F: FnMut(schema::schema::USBDeviceAction, schema::schema::USBDevice) -> bool,
+    C: Fn() -> bool,
+{
+    let context = libudev::Context::new()?;
+    let mut monitor = libudev::Monitor::new(&context)?;
+    monitor.match_subsystem("usb")?;
+
+    // Start listening before taking the snapshot so nothing plugged in between the two is missed.
+    let mut socket = monitor.listen()?;
 
-        for x in device.properties() {
-            props.insert(
-                x.name().to_string_lossy().to_string(),
-                x.value().to_string_lossy().to_string(),
-            );
+    for device in enumerate_usb_devices()? {
+        if !on_event(schema::schema::USBDeviceAction::Present, device) {
+            return Ok(());
         }
+    }
+
+    let mut fds = [libc::pollfd {
+        fd: socket.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    }];
 
-        if !props.contains_key("ID_MODEL_ID") || !props.contains_key("ID_VENDOR_ID") {
-            continue;
+    while !is_cancelled() {
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), 1, 1000) };
+        if ready < 0 {
+            let e = std::io::Error::last_os_error();
+            if e.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(e.into());
         }
 
-        let this = schema::schema::USBDevice {
-            device: props["DEVNAME"].clone(),
-            model: props["ID_MODEL"].clone(),
-            vendor_id: props["ID_VENDOR_ID"].clone(),
-            product_id: props["ID_MODEL_ID"].clone(),
-            vendor_name: match props.contains_key("ID_VENDOR_FROM_DATABASE") {
-                true => Some(props["ID_VENDOR_FROM_DATABASE"].clone()),
-                false => None,
-            },
-            model_name: match props.contains_key("ID_MODEL_FROM_DATABASE") {
-                true => Some(props["ID_MODEL_FROM_DATABASE"].clone()),
-                false => None,
-            },
-        };
+        while let Some(event) = socket.receive_event() {
+            let action = match event.event_type() {
+                libudev::EventType::Add => schema::schema::USBDeviceAction::Add,
+                libudev::EventType::Change => schema::schema::USBDeviceAction::Change,
+                libudev::EventType::Remove => schema::schema::USBDeviceAction::Remove,
+                libudev::EventType::Unknown => continue,
+            };
 
-        println!("{}", this);
+            let device = match usb_device_from_udev(event.device()) {
+                Some(x) => x,
+                None => continue,
+            };
 
-        result.push(this);
+            if !on_event(action, device) {
+                return Ok(());
+            }
+        }
     }
 
-    return Ok(result);
+    Ok(())
 }
 
 pub struct GRPCAPIError {
     _msg: String,
     _status: Option<tonic::Status>,
+    _wait_result: Option<schema::schema::WaitResult>,
 }
 
 impl<'a> GRPCAPIError {
@@ -72,6 +174,7 @@ impl<'a> GRPCAPIError {
         GRPCAPIError {
             _msg: msg,
             _status: None,
+            _wait_result: None,
         }
     }
 
@@ -79,9 +182,24 @@ impl<'a> GRPCAPIError {
         GRPCAPIError {
             _msg: msg,
             _status: Some(status),
+            _wait_result: None,
+        }
+    }
+
+    pub fn new_with_wait_result(msg: String, wait: schema::schema::WaitResult) -> GRPCAPIError {
+        GRPCAPIError {
+            _msg: msg,
+            _status: None,
+            _wait_result: Some(wait),
         }
     }
 
+    // Set when a lifecycle call timed out waiting for a state: the state the domain was in when
+    // the server gave up, and how long it waited.
+    pub fn wait_result(&self) -> Option<&schema::schema::WaitResult> {
+        self._wait_result.as_ref()
+    }
+
     fn msg(&self) -> &String {
         &self._msg
     }
@@ -105,3 +223,44 @@ impl fmt::Debug for GRPCAPIError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::domain_xml::{parse_element, HostDev, XMLElement};
+
+    #[test]
+    fn normalize_usb_id_accepts_hex_ids() {
+        assert_eq!(normalize_usb_id("046d").unwrap(), "046d");
+        assert_eq!(normalize_usb_id("0x046D").unwrap(), "046d");
+    }
+
+    #[test]
+    fn normalize_usb_id_rejects_everything_else() {
+        for id in ["46d", "046d'/><foo", "zzzz", "", "0x", "046d0"].iter() {
+            assert!(normalize_usb_id(id).is_err(), "'{}' was accepted", id);
+        }
+    }
+
+    // Validation keeps markup out of IDs, but the XML writer has to escape attribute values on
+    // its own too.
+    #[test]
+    fn hostdev_xml_escapes_ids() {
+        let payload = "0x046d'/><foo bar='";
+        let xml = HostDev::usb(payload.to_string(), "0xc52b".to_string())
+            .to_xml()
+            .unwrap();
+
+        let e = parse_element(&xml).unwrap();
+        let source = e.get_child("source").unwrap();
+        assert_eq!(
+            source.get_child("vendor").unwrap().attributes["id"],
+            payload
+        );
+        assert_eq!(
+            source.get_child("product").unwrap().attributes["id"],
+            "0xc52b"
+        );
+        assert!(source.get_child("foo").is_none());
+    }
+}
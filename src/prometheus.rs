@@ -0,0 +1,353 @@
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, StatusCode};
+
+use crate::domain_stats::ALL_DOMAIN_STATS;
+use crate::rpc_metrics::{RPCMetrics, LATENCY_BUCKETS};
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::usb::{list_host_devices, usb_device_owners};
+use crate::virt_sys::{get_all_domain_stats, DomainStatsRecord};
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Writes the HELP and TYPE lines for a metric family.
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn domain_labels(record: &DomainStatsRecord) -> String {
+    format!("uuid=\"{}\",name=\"{}\"", record.uuid, escape(&record.name))
+}
+
+// One sample per domain for a top-level stats field, scaled by `scale`.
+fn domain_gauge(
+    out: &mut String,
+    records: &[DomainStatsRecord],
+    name: &str,
+    kind: &str,
+    help: &str,
+    field: &str,
+    scale: f64,
+) {
+    family(out, name, kind, help);
+    for record in records {
+        if let Some(x) = record.params.get(field).and_then(|x| x.as_f64()) {
+            let _ = writeln!(out, "{}{{{}}} {}", name, domain_labels(record), x * scale);
+        }
+    }
+}
+
+// One sample per entry of an indexed group ("block.<n>.*", "net.<n>.*"), labelled by its name.
+fn device_counter(
+    out: &mut String,
+    records: &[DomainStatsRecord],
+    name: &str,
+    help: &str,
+    prefix: &str,
+    label: &str,
+    field: &str,
+) {
+    family(out, name, "counter", help);
+    for record in records {
+        for i in 0..record.count(prefix) {
+            let device = record
+                .string(&format!("{}.{}.name", prefix, i))
+                .unwrap_or_default();
+            if let Some(x) = record.u64(&format!("{}.{}.{}", prefix, i, field)) {
+                let _ = writeln!(
+                    out,
+                    "{}{{{},{}=\"{}\"}} {}",
+                    name,
+                    domain_labels(record),
+                    label,
+                    escape(&device),
+                    x
+                );
+            }
+        }
+    }
+}
+
+fn render_domains(out: &mut String, records: &[DomainStatsRecord]) {
+    domain_gauge(
+        out,
+        records,
+        "libvirt_domain_state",
+        "gauge",
+        "Domain state as a virDomainState value (1 = running).",
+        "state.state",
+        1.0,
+    );
+    domain_gauge(
+        out,
+        records,
+        "libvirt_domain_memory_current_bytes",
+        "gauge",
+        "Memory currently assigned to the domain through the balloon.",
+        "balloon.current",
+        1024.0,
+    );
+    domain_gauge(
+        out,
+        records,
+        "libvirt_domain_memory_maximum_bytes",
+        "gauge",
+        "Maximum memory the domain can be ballooned up to.",
+        "balloon.maximum",
+        1024.0,
+    );
+    domain_gauge(
+        out,
+        records,
+        "libvirt_domain_vcpus",
+        "gauge",
+        "Number of online vCPUs.",
+        "vcpu.current",
+        1.0,
+    );
+    domain_gauge(
+        out,
+        records,
+        "libvirt_domain_cpu_time_seconds_total",
+        "counter",
+        "CPU time used by the domain.",
+        "cpu.time",
+        1e-9,
+    );
+
+    family(
+        out,
+        "libvirt_domain_vcpu_time_seconds_total",
+        "counter",
+        "CPU time used by each vCPU.",
+    );
+    for record in records {
+        for i in 0..record.u64_or_zero("vcpu.maximum") {
+            if let Some(x) = record.u64(&format!("vcpu.{}.time", i)) {
+                let _ = writeln!(
+                    out,
+                    "libvirt_domain_vcpu_time_seconds_total{{{},vcpu=\"{}\"}} {}",
+                    domain_labels(record),
+                    i,
+                    x as f64 * 1e-9
+                );
+            }
+        }
+    }
+
+    let block = [
+        ("read_bytes_total", "Bytes read from the disk.", "rd.bytes"),
+        (
+            "write_bytes_total",
+            "Bytes written to the disk.",
+            "wr.bytes",
+        ),
+        (
+            "read_requests_total",
+            "Read requests issued to the disk.",
+            "rd.reqs",
+        ),
+        (
+            "write_requests_total",
+            "Write requests issued to the disk.",
+            "wr.reqs",
+        ),
+    ];
+    for (suffix, help, field) in block.iter() {
+        device_counter(
+            out,
+            records,
+            &format!("libvirt_domain_block_{}", suffix),
+            help,
+            "block",
+            "device",
+            field,
+        );
+    }
+
+    let net = [
+        (
+            "receive_bytes_total",
+            "Bytes received on the interface.",
+            "rx.bytes",
+        ),
+        (
+            "transmit_bytes_total",
+            "Bytes sent on the interface.",
+            "tx.bytes",
+        ),
+        (
+            "receive_packets_total",
+            "Packets received on the interface.",
+            "rx.pkts",
+        ),
+        (
+            "transmit_packets_total",
+            "Packets sent on the interface.",
+            "tx.pkts",
+        ),
+        (
+            "receive_drops_total",
+            "Received packets dropped.",
+            "rx.drop",
+        ),
+        ("transmit_drops_total", "Sent packets dropped.", "tx.drop"),
+    ];
+    for (suffix, help, field) in net.iter() {
+        device_counter(
+            out,
+            records,
+            &format!("libvirt_domain_interface_{}", suffix),
+            help,
+            "net",
+            "interface",
+            field,
+        );
+    }
+}
+
+fn render_usb(out: &mut String, conn: &ThreadSafeVirtConn) {
+    let devices = match list_host_devices() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("metrics: failed to enumerate USB devices: {}", e);
+            return;
+        }
+    };
+    let owners = usb_device_owners(&conn.lock(), &devices);
+    let attached = devices
+        .iter()
+        .filter(|x| owners.contains_key(&(x.bus, x.device_number)))
+        .count();
+
+    family(
+        out,
+        "libvirt_usb_devices",
+        "gauge",
+        "USB devices plugged into the host, by whether a domain claims them.",
+    );
+    let _ = writeln!(out, "libvirt_usb_devices{{attached=\"true\"}} {}", attached);
+    let _ = writeln!(
+        out,
+        "libvirt_usb_devices{{attached=\"false\"}} {}",
+        devices.len() - attached
+    );
+}
+
+fn render_rpcs(out: &mut String, rpc_metrics: &RPCMetrics) {
+    let methods = rpc_metrics.snapshot();
+
+    family(
+        out,
+        "libvirt_grpc_requests_total",
+        "counter",
+        "gRPC requests handled, by method.",
+    );
+    for (method, stats) in methods.iter() {
+        let _ = writeln!(
+            out,
+            "libvirt_grpc_requests_total{{method=\"{}\"}} {}",
+            method, stats.count
+        );
+    }
+
+    family(
+        out,
+        "libvirt_grpc_request_duration_seconds",
+        "histogram",
+        "Time until a gRPC request was answered; for streams, until the stream opened.",
+    );
+    for (method, stats) in methods.iter() {
+        let mut cumulative = 0;
+        for (i, bound) in LATENCY_BUCKETS.iter().enumerate() {
+            cumulative += stats.buckets[i];
+            let _ = writeln!(
+                out,
+                "libvirt_grpc_request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                method, bound, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "libvirt_grpc_request_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+            method, stats.count
+        );
+        let _ = writeln!(
+            out,
+            "libvirt_grpc_request_duration_seconds_sum{{method=\"{}\"}} {}",
+            method, stats.sum_seconds
+        );
+        let _ = writeln!(
+            out,
+            "libvirt_grpc_request_duration_seconds_count{{method=\"{}\"}} {}",
+            method, stats.count
+        );
+    }
+}
+
+pub fn render(conn: &ThreadSafeVirtConn, rpc_metrics: &RPCMetrics) -> String {
+    let mut out = String::new();
+
+    match get_all_domain_stats(&conn.lock(), ALL_DOMAIN_STATS, 0) {
+        Ok(records) => render_domains(&mut out, &records),
+        Err(e) => eprintln!("metrics: failed to get domain stats: {}", e),
+    }
+    render_usb(&mut out, conn);
+    render_rpcs(&mut out, rpc_metrics);
+
+    out
+}
+
+async fn handle(
+    request: Request<Body>,
+    conn: Arc<ThreadSafeVirtConn>,
+    rpc_metrics: Arc<RPCMetrics>,
+) -> Result<Response<Body>, Infallible> {
+    if request.uri().path() != "/metrics" {
+        let mut response = Response::new(Body::from("not found\n"));
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return Ok(response);
+    }
+
+    // Collecting talks to libvirt and udev synchronously, so keep it off the async workers.
+    let body = tokio::task::spawn_blocking(move || render(&conn, &rpc_metrics))
+        .await
+        .unwrap_or_default();
+
+    let mut response = Response::new(Body::from(body));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, "text/plain; version=0.0.4".parse().unwrap());
+    Ok(response)
+}
+
+// Serves Prometheus text-format metrics on http://<addr>/metrics until the process exits.
+pub async fn serve_metrics(
+    addr: SocketAddr,
+    conn: Arc<ThreadSafeVirtConn>,
+    rpc_metrics: Arc<RPCMetrics>,
+) -> Result<(), hyper::Error> {
+    let make_service = make_service_fn(move |_| {
+        let conn = conn.clone();
+        let rpc_metrics = rpc_metrics.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(request, conn.clone(), rpc_metrics.clone())
+            }))
+        }
+    });
+
+    hyper::Server::bind(&addr).serve(make_service).await
+}
@@ -9,6 +9,20 @@ pub struct SuccessResponse {
     pub success: bool,
     #[prost(string, optional, tag = "2")]
     pub error: ::core::option::Option<::prost::alloc::string::String>,
+    /// Only set by lifecycle RPCs that were asked to wait for a state.
+    #[prost(enumeration = "DomainState", optional, tag = "3")]
+    pub state: ::core::option::Option<i32>,
+    #[prost(uint64, optional, tag = "4")]
+    pub elapsed_ms: ::core::option::Option<u64>,
+}
+/// Makes a lifecycle RPC block until the domain reaches `state` or `timeout_ms`
+/// passes (60s if unset, at most 10 minutes).
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaitForState {
+    #[prost(enumeration = "DomainState", tag = "1")]
+    pub state: i32,
+    #[prost(uint32, tag = "2")]
+    pub timeout_ms: u32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Domain {
@@ -32,16 +46,154 @@ pub struct Domain {
     pub virt_cpu_num: u32,
     #[prost(uint64, tag = "10")]
     pub virt_cpu_time: u64,
+    /// A managed-save image exists; the next start resumes from it.
+    #[prost(bool, tag = "11")]
+    pub has_managed_save: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateDomainRequest {
     #[prost(bytes = "vec", tag = "1")]
     pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub wait: ::core::option::Option<WaitForState>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DestroyDomainRequest {
     #[prost(bytes = "vec", tag = "1")]
     pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub wait: ::core::option::Option<WaitForState>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShutdownDomainRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "ShutdownMode", repeated, tag = "2")]
+    pub modes: ::prost::alloc::vec::Vec<i32>,
+    #[prost(message, optional, tag = "3")]
+    pub wait: ::core::option::Option<WaitForState>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RebootDomainRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "ShutdownMode", repeated, tag = "2")]
+    pub modes: ::prost::alloc::vec::Vec<i32>,
+    #[prost(message, optional, tag = "3")]
+    pub wait: ::core::option::Option<WaitForState>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResetDomainRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub wait: ::core::option::Option<WaitForState>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SuspendDomainRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub wait: ::core::option::Option<WaitForState>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResumeDomainRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub wait: ::core::option::Option<WaitForState>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DefineDomainRequest {
+    #[prost(string, tag = "1")]
+    pub xml: ::prost::alloc::string::String,
+    /// Validate the XML against libvirt's domain schema before defining.
+    #[prost(bool, tag = "2")]
+    pub validate: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DefineDomainResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "4")]
+    pub name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UndefineDomainRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Also remove the managed-save image, if any.
+    #[prost(bool, tag = "2")]
+    pub managed_save: bool,
+    /// Also remove snapshot metadata (the snapshot data itself is left alone).
+    #[prost(bool, tag = "3")]
+    pub snapshots_metadata: bool,
+    /// Also remove the domain's NVRAM file.
+    #[prost(bool, tag = "4")]
+    pub nvram: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateTransientDomainRequest {
+    #[prost(string, tag = "1")]
+    pub xml: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub start_paused: bool,
+    /// Destroy the domain when the server's libvirt connection closes.
+    #[prost(bool, tag = "3")]
+    pub autodestroy: bool,
+    #[prost(bool, tag = "4")]
+    pub validate: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetDomainXmlRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Return the persistent config instead of the live definition.
+    #[prost(bool, tag = "2")]
+    pub inactive: bool,
+    /// Include security-sensitive data such as VNC passwords.
+    #[prost(bool, tag = "3")]
+    pub secure: bool,
+    #[prost(bool, tag = "4")]
+    pub migratable: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainXmlResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub xml: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateDomainXmlRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Must be for the domain `uuid`; a missing <uuid> is filled in.
+    #[prost(string, tag = "2")]
+    pub xml: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub validate: bool,
+    /// Include security-sensitive data such as VNC passwords in xml_before and xml_after.
+    #[prost(bool, tag = "4")]
+    pub secure: bool,
+}
+/// Both XML documents are the persistent (inactive) config.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateDomainXmlResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub xml_before: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub xml_after: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListUsbDevicesRequest {}
@@ -66,6 +218,46 @@ pub struct UsbDevice {
     pub vendor_name: ::core::option::Option<::prost::alloc::string::String>,
     #[prost(message, optional, tag = "6")]
     pub model_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, tag = "7")]
+    pub bus: u32,
+    #[prost(uint32, tag = "8")]
+    pub device_number: u32,
+    /// Kernel port path, e.g. "1-1.2" for port 2 of the hub on port 1 of bus 1.
+    #[prost(string, tag = "9")]
+    pub port_path: ::prost::alloc::string::String,
+    /// udev ID_SERIAL_SHORT, if the device reports one.
+    #[prost(message, optional, tag = "10")]
+    pub serial: ::core::option::Option<::prost::alloc::string::String>,
+    /// UUID of the domain whose hostdevs claim this device; empty if none does.
+    #[prost(bytes = "vec", tag = "11")]
+    pub attached_to: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UsbBusAddress {
+    #[prost(uint32, tag = "1")]
+    pub bus: u32,
+    #[prost(uint32, tag = "2")]
+    pub device: u32,
+}
+/// Picks one physical device among those with the same vendor:product. When a
+/// selector is given, vendor_id/product_id on the request are optional and only
+/// narrow the match further.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UsbDeviceSelector {
+    #[prost(oneof = "usb_device_selector::Selector", tags = "1, 2, 3")]
+    pub selector: ::core::option::Option<usb_device_selector::Selector>,
+}
+/// Nested message and enum types in `USBDeviceSelector`.
+pub mod usb_device_selector {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Selector {
+        #[prost(message, tag = "1")]
+        BusAddress(super::UsbBusAddress),
+        #[prost(string, tag = "2")]
+        PortPath(::prost::alloc::string::String),
+        #[prost(string, tag = "3")]
+        Serial(::prost::alloc::string::String),
+    }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AttachDeviceRequest {
@@ -75,6 +267,13 @@ pub struct AttachDeviceRequest {
     pub vendor_id: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub product_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub selector: ::core::option::Option<UsbDeviceSelector>,
+    #[prost(enumeration = "DeviceScope", tag = "5")]
+    pub scope: i32,
+    /// Attach even if another domain already claims the device.
+    #[prost(bool, tag = "6")]
+    pub force: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DetachDeviceRequest {
@@ -84,250 +283,3421 @@ pub struct DetachDeviceRequest {
     pub vendor_id: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub product_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub selector: ::core::option::Option<UsbDeviceSelector>,
+    #[prost(enumeration = "DeviceScope", tag = "5")]
+    pub scope: i32,
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum DomainState {
-    Unspecified = 0,
-    Nostate = 1,
-    Running = 2,
-    Blocked = 3,
-    Paused = 4,
-    Shutdown = 5,
-    Shutoff = 6,
-    Crashed = 7,
-    Pmsuspended = 8,
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListDomainDevicesRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// List the persistent config's devices instead of the running domain's.
+    #[prost(bool, tag = "2")]
+    pub inactive: bool,
 }
-#[doc = r" Generated client implementations."]
-pub mod libvirt_api_client {
-    #![allow(unused_variables, dead_code, missing_docs)]
-    use tonic::codegen::*;
-    pub struct LibvirtApiClient<T> {
-        inner: tonic::client::Grpc<T>,
-    }
-    impl LibvirtApiClient<tonic::transport::Channel> {
-        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
-        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
-        where
-            D: std::convert::TryInto<tonic::transport::Endpoint>,
-            D::Error: Into<StdError>,
-        {
-            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
-            Ok(Self::new(conn))
-        }
-    }
-    impl<T> LibvirtApiClient<T>
-    where
-        T: tonic::client::GrpcService<tonic::body::BoxBody>,
-        T::ResponseBody: Body + HttpBody + Send + 'static,
-        T::Error: Into<StdError>,
-        <T::ResponseBody as HttpBody>::Error: Into<StdError> + Send,
-    {
-        pub fn new(inner: T) -> Self {
-            let inner = tonic::client::Grpc::new(inner);
-            Self { inner }
-        }
-        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
-            let inner = tonic::client::Grpc::with_interceptor(inner, interceptor);
-            Self { inner }
-        }
-        pub async fn list_domains(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ListDomainsRequest>,
-        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Domain>>, tonic::Status>
-        {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ListDomains");
-            self.inner
-                .server_streaming(request.into_request(), path, codec)
-                .await
-        }
-        pub async fn create_domain(
-            &mut self,
-            request: impl tonic::IntoRequest<super::CreateDomainRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/CreateDomain");
-            self.inner.unary(request.into_request(), path, codec).await
-        }
-        pub async fn destroy_domain(
-            &mut self,
-            request: impl tonic::IntoRequest<super::DestroyDomainRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path =
-                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/DestroyDomain");
-            self.inner.unary(request.into_request(), path, codec).await
-        }
-        pub async fn list_usb_devices(
-            &mut self,
-            request: impl tonic::IntoRequest<super::ListUsbDevicesRequest>,
-        ) -> Result<tonic::Response<tonic::codec::Streaming<super::UsbDevice>>, tonic::Status>
-        {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path =
-                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ListUSBDevices");
-            self.inner
-                .server_streaming(request.into_request(), path, codec)
-                .await
-        }
-        pub async fn attach_device(
-            &mut self,
-            request: impl tonic::IntoRequest<super::AttachDeviceRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/AttachDevice");
-            self.inner.unary(request.into_request(), path, codec).await
-        }
-        pub async fn detach_device(
-            &mut self,
-            request: impl tonic::IntoRequest<super::DetachDeviceRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
-            self.inner.ready().await.map_err(|e| {
-                tonic::Status::new(
-                    tonic::Code::Unknown,
-                    format!("Service was not ready: {}", e.into()),
-                )
-            })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/DetachDevice");
-            self.inner.unary(request.into_request(), path, codec).await
-        }
-    }
-    impl<T: Clone> Clone for LibvirtApiClient<T> {
-        fn clone(&self) -> Self {
-            Self {
-                inner: self.inner.clone(),
-            }
-        }
-    }
-    impl<T> std::fmt::Debug for LibvirtApiClient<T> {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            write!(f, "LibvirtApiClient {{ ... }}")
-        }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeviceAddress {
+    #[prost(string, optional, tag = "1")]
+    pub r#type: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(map = "string, string", tag = "2")]
+    pub attributes:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainDisk {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "2")]
+    pub device: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(map = "string, string", tag = "3")]
+    pub source:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub target_dev: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub target_bus: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "6")]
+    pub readonly: bool,
+    #[prost(message, optional, tag = "7")]
+    pub address: ::core::option::Option<DeviceAddress>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainInterface {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "2")]
+    pub mac: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(map = "string, string", tag = "3")]
+    pub source:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub model: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub target_dev: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "6")]
+    pub address: ::core::option::Option<DeviceAddress>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainHostDev {
+    #[prost(string, tag = "1")]
+    pub mode: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "3")]
+    pub vendor_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub product_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "5")]
+    pub source_address: ::core::option::Option<DeviceAddress>,
+    #[prost(message, optional, tag = "6")]
+    pub address: ::core::option::Option<DeviceAddress>,
+    /// The plugged-in USB device this hostdev refers to, if it can be identified.
+    #[prost(message, optional, tag = "7")]
+    pub host_device: ::core::option::Option<UsbDevice>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainController {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(uint32, optional, tag = "2")]
+    pub index: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "3")]
+    pub model: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "4")]
+    pub address: ::core::option::Option<DeviceAddress>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainDevice {
+    #[prost(oneof = "domain_device::Device", tags = "1, 2, 3, 4")]
+    pub device: ::core::option::Option<domain_device::Device>,
+}
+/// Nested message and enum types in `DomainDevice`.
+pub mod domain_device {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Device {
+        #[prost(message, tag = "1")]
+        Disk(super::DomainDisk),
+        #[prost(message, tag = "2")]
+        Interface(super::DomainInterface),
+        #[prost(message, tag = "3")]
+        Hostdev(super::DomainHostDev),
+        #[prost(message, tag = "4")]
+        Controller(super::DomainController),
     }
 }
-#[doc = r" Generated server implementations."]
-pub mod libvirt_api_server {
-    #![allow(unused_variables, dead_code, missing_docs)]
-    use tonic::codegen::*;
-    #[doc = "Generated trait containing gRPC methods that should be implemented for use with LibvirtApiServer."]
-    #[async_trait]
-    pub trait LibvirtApi: Send + Sync + 'static {
-        #[doc = "Server streaming response type for the ListDomains method."]
-        type ListDomainsStream: futures_core::Stream<Item = Result<super::Domain, tonic::Status>>
-            + Send
-            + Sync
-            + 'static;
-        async fn list_domains(
-            &self,
-            request: tonic::Request<super::ListDomainsRequest>,
-        ) -> Result<tonic::Response<Self::ListDomainsStream>, tonic::Status>;
-        async fn create_domain(
-            &self,
-            request: tonic::Request<super::CreateDomainRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
-        async fn destroy_domain(
-            &self,
-            request: tonic::Request<super::DestroyDomainRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
-        #[doc = "Server streaming response type for the ListUSBDevices method."]
-        type ListUSBDevicesStream: futures_core::Stream<Item = Result<super::UsbDevice, tonic::Status>>
-            + Send
-            + Sync
-            + 'static;
-        async fn list_usb_devices(
-            &self,
-            request: tonic::Request<super::ListUsbDevicesRequest>,
-        ) -> Result<tonic::Response<Self::ListUSBDevicesStream>, tonic::Status>;
-        async fn attach_device(
-            &self,
-            request: tonic::Request<super::AttachDeviceRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
-        async fn detach_device(
-            &self,
-            request: tonic::Request<super::DetachDeviceRequest>,
-        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
-    }
-    #[derive(Debug)]
-    pub struct LibvirtApiServer<T: LibvirtApi> {
-        inner: _Inner<T>,
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchUsbDevicesRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UsbDeviceEvent {
+    #[prost(enumeration = "UsbDeviceAction", tag = "1")]
+    pub action: i32,
+    #[prost(message, optional, tag = "2")]
+    pub device: ::core::option::Option<UsbDevice>,
+}
+/// Attaches every plugged-in device matching all of the set criteria to the
+/// domain, live, whenever it is plugged in while the domain runs or the domain
+/// starts while it is plugged in. At least one criterion must be set.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UsbRule {
+    /// Assigned by the server on create; identifies the rule on update.
+    #[prost(bytes = "vec", tag = "1")]
+    pub id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub domain_uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub vendor_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "4")]
+    pub product_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "5")]
+    pub serial: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "6")]
+    pub port_path: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListUsbRulesRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateUsbRuleRequest {
+    #[prost(message, optional, tag = "1")]
+    pub rule: ::core::option::Option<UsbRule>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateUsbRuleRequest {
+    #[prost(message, optional, tag = "1")]
+    pub rule: ::core::option::Option<UsbRule>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteUsbRuleRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub id: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UsbRuleResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "3")]
+    pub rule: ::core::option::Option<UsbRule>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchDomainEventsRequest {
+    /// Only report events for this domain; empty for all domains.
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainLifecycleEvent {
+    #[prost(enumeration = "DomainLifecycleEventType", tag = "1")]
+    pub r#type: i32,
+    /// libvirt's per-type detail code, e.g. virDomainEventStoppedDetailType for
+    /// STOPPED.
+    #[prost(int32, tag = "2")]
+    pub detail: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainRebootEvent {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainDeviceEvent {
+    /// Device alias from the domain XML, e.g. "hostdev0".
+    #[prost(string, tag = "1")]
+    pub alias: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainWatchdogEvent {
+    #[prost(enumeration = "WatchdogAction", tag = "1")]
+    pub action: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainIoErrorEvent {
+    #[prost(string, tag = "1")]
+    pub src_path: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub dev_alias: ::prost::alloc::string::String,
+    #[prost(enumeration = "IoErrorAction", tag = "3")]
+    pub action: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainAgentLifecycleEvent {
+    #[prost(enumeration = "AgentState", tag = "1")]
+    pub state: i32,
+    #[prost(enumeration = "AgentStateReason", tag = "2")]
+    pub reason: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainEvent {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// Milliseconds since the Unix epoch at which the server received the event.
+    #[prost(uint64, tag = "3")]
+    pub timestamp_ms: u64,
+    #[prost(oneof = "domain_event::Event", tags = "4, 5, 6, 7, 8, 9, 10")]
+    pub event: ::core::option::Option<domain_event::Event>,
+}
+/// Nested message and enum types in `DomainEvent`.
+pub mod domain_event {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Event {
+        #[prost(message, tag = "4")]
+        Lifecycle(super::DomainLifecycleEvent),
+        #[prost(message, tag = "5")]
+        Reboot(super::DomainRebootEvent),
+        #[prost(message, tag = "6")]
+        DeviceAdded(super::DomainDeviceEvent),
+        #[prost(message, tag = "7")]
+        DeviceRemoved(super::DomainDeviceEvent),
+        #[prost(message, tag = "8")]
+        Watchdog(super::DomainWatchdogEvent),
+        #[prost(message, tag = "9")]
+        IoError(super::DomainIoErrorEvent),
+        #[prost(message, tag = "10")]
+        AgentLifecycle(super::DomainAgentLifecycleEvent),
     }
-    struct _Inner<T>(Arc<T>, Option<tonic::Interceptor>);
-    impl<T: LibvirtApi> LibvirtApiServer<T> {
-        pub fn new(inner: T) -> Self {
-            let inner = Arc::new(inner);
-            let inner = _Inner(inner, None);
-            Self { inner }
-        }
-        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
-            let inner = Arc::new(inner);
-            let inner = _Inner(inner, Some(interceptor.into()));
-            Self { inner }
-        }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetDomainStatsRequest {
+    /// Bitwise OR of DomainStatsGroup values; 0 for every group above.
+    #[prost(uint32, tag = "1")]
+    pub stats: u32,
+    /// Only return these domains; empty for all.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub uuids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// Only return running domains.
+    #[prost(bool, tag = "3")]
+    pub active_only: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainCpuStats {
+    #[prost(uint64, tag = "1")]
+    pub time_ns: u64,
+    #[prost(uint64, tag = "2")]
+    pub user_ns: u64,
+    #[prost(uint64, tag = "3")]
+    pub system_ns: u64,
+}
+/// Sizes in KiB. Fields other than current/maximum need a balloon driver in the
+/// guest and are missing otherwise.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainBalloonStats {
+    #[prost(uint64, tag = "1")]
+    pub current_kib: u64,
+    #[prost(uint64, tag = "2")]
+    pub maximum_kib: u64,
+    #[prost(uint64, optional, tag = "3")]
+    pub swap_in_kib: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub swap_out_kib: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub major_faults: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub minor_faults: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "7")]
+    pub unused_kib: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "8")]
+    pub available_kib: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "9")]
+    pub usable_kib: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "10")]
+    pub rss_kib: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainVcpuStats {
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    /// virVcpuState: 0 offline, 1 running, 2 blocked.
+    #[prost(int32, tag = "2")]
+    pub state: i32,
+    #[prost(uint64, tag = "3")]
+    pub time_ns: u64,
+    #[prost(uint64, optional, tag = "4")]
+    pub wait_ns: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainInterfaceStats {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub rx_bytes: u64,
+    #[prost(uint64, tag = "3")]
+    pub rx_packets: u64,
+    #[prost(uint64, tag = "4")]
+    pub rx_errors: u64,
+    #[prost(uint64, tag = "5")]
+    pub rx_drops: u64,
+    #[prost(uint64, tag = "6")]
+    pub tx_bytes: u64,
+    #[prost(uint64, tag = "7")]
+    pub tx_packets: u64,
+    #[prost(uint64, tag = "8")]
+    pub tx_errors: u64,
+    #[prost(uint64, tag = "9")]
+    pub tx_drops: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainBlockStats {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub path: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, tag = "3")]
+    pub read_requests: u64,
+    #[prost(uint64, tag = "4")]
+    pub read_bytes: u64,
+    #[prost(uint64, tag = "5")]
+    pub read_time_ns: u64,
+    #[prost(uint64, tag = "6")]
+    pub write_requests: u64,
+    #[prost(uint64, tag = "7")]
+    pub write_bytes: u64,
+    #[prost(uint64, tag = "8")]
+    pub write_time_ns: u64,
+    #[prost(uint64, tag = "9")]
+    pub flush_requests: u64,
+    #[prost(uint64, tag = "10")]
+    pub flush_time_ns: u64,
+    #[prost(uint64, optional, tag = "11")]
+    pub allocation: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "12")]
+    pub capacity: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "13")]
+    pub physical: ::core::option::Option<u64>,
+}
+/// Groups that weren't requested, or that libvirt couldn't collect for this
+/// domain, are left unset or empty.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainStats {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(enumeration = "DomainState", optional, tag = "3")]
+    pub state: ::core::option::Option<i32>,
+    #[prost(message, optional, tag = "4")]
+    pub cpu: ::core::option::Option<DomainCpuStats>,
+    #[prost(message, optional, tag = "5")]
+    pub balloon: ::core::option::Option<DomainBalloonStats>,
+    #[prost(uint32, tag = "6")]
+    pub vcpu_current: u32,
+    #[prost(uint32, tag = "7")]
+    pub vcpu_maximum: u32,
+    #[prost(message, repeated, tag = "8")]
+    pub vcpus: ::prost::alloc::vec::Vec<DomainVcpuStats>,
+    #[prost(message, repeated, tag = "9")]
+    pub interfaces: ::prost::alloc::vec::Vec<DomainInterfaceStats>,
+    #[prost(message, repeated, tag = "10")]
+    pub blocks: ::prost::alloc::vec::Vec<DomainBlockStats>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamDomainMetricsRequest {
+    /// How often to emit metrics; at least 1000, and 0 means 1000. Rates are
+    /// averaged over the actual time between the two samples they compare.
+    #[prost(uint32, tag = "1")]
+    pub interval_ms: u32,
+    /// Only report these domains; empty for all running domains.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub uuids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DiskMetrics {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(double, tag = "2")]
+    pub read_iops: f64,
+    #[prost(double, tag = "3")]
+    pub write_iops: f64,
+    #[prost(double, tag = "4")]
+    pub read_bytes_per_sec: f64,
+    #[prost(double, tag = "5")]
+    pub write_bytes_per_sec: f64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InterfaceMetrics {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(double, tag = "2")]
+    pub rx_bytes_per_sec: f64,
+    #[prost(double, tag = "3")]
+    pub tx_bytes_per_sec: f64,
+    #[prost(double, tag = "4")]
+    pub rx_packets_per_sec: f64,
+    #[prost(double, tag = "5")]
+    pub tx_packets_per_sec: f64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainMetrics {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// When the newer of the two compared samples was taken, in milliseconds
+    /// since the Unix epoch.
+    #[prost(uint64, tag = "3")]
+    pub timestamp_ms: u64,
+    #[prost(uint64, tag = "4")]
+    pub interval_ms: u64,
+    /// 100 per fully busy host CPU, so up to 100 * vcpus.
+    #[prost(double, tag = "5")]
+    pub cpu_percent: f64,
+    #[prost(uint32, tag = "6")]
+    pub vcpus: u32,
+    #[prost(uint64, tag = "7")]
+    pub memory_kib: u64,
+    #[prost(message, repeated, tag = "8")]
+    pub disks: ::prost::alloc::vec::Vec<DiskMetrics>,
+    #[prost(message, repeated, tag = "9")]
+    pub interfaces: ::prost::alloc::vec::Vec<InterfaceMetrics>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateSnapshotRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// A full <domainsnapshot> document. When empty, one is built from name and
+    /// description; an empty name lets libvirt pick one.
+    #[prost(string, tag = "2")]
+    pub xml: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub description: ::prost::alloc::string::String,
+    /// Snapshot the disks only, leaving out memory state.
+    #[prost(bool, tag = "5")]
+    pub disk_only: bool,
+    /// Freeze guest filesystems through the guest agent while the snapshot is
+    /// taken; requires disk_only.
+    #[prost(bool, tag = "6")]
+    pub quiesce: bool,
+    /// Either snapshot every disk or none.
+    #[prost(bool, tag = "7")]
+    pub atomic: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSnapshotsRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Snapshot {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+    /// Name of the snapshot this one was taken on top of, if any.
+    #[prost(string, optional, tag = "3")]
+    pub parent: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "4")]
+    pub children: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Domain state captured by the snapshot, e.g. "running", "shutoff" or
+    /// "disk-snapshot".
+    #[prost(string, tag = "5")]
+    pub state: ::prost::alloc::string::String,
+    /// Seconds since the Unix epoch.
+    #[prost(int64, tag = "6")]
+    pub creation_time: i64,
+    #[prost(bool, tag = "7")]
+    pub current: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevertToSnapshotRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// Start the domain after reverting, regardless of the snapshot's state.
+    #[prost(bool, tag = "3")]
+    pub running: bool,
+    /// Leave the domain paused after reverting.
+    #[prost(bool, tag = "4")]
+    pub paused: bool,
+    /// Revert even when libvirt considers it risky, e.g. across config changes.
+    #[prost(bool, tag = "5")]
+    pub force: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteSnapshotRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// Also delete all descendants.
+    #[prost(bool, tag = "3")]
+    pub children: bool,
+    /// Delete only the descendants, keeping this snapshot.
+    #[prost(bool, tag = "4")]
+    pub children_only: bool,
+    /// Only drop libvirt's metadata, leaving the snapshot data in place.
+    #[prost(bool, tag = "5")]
+    pub metadata_only: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSnapshotXmlRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub secure: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotXmlResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub xml: ::prost::alloc::string::String,
+}
+/// How a saved domain comes back. At most one of running and paused may be set;
+/// with neither, it returns in the state it was saved in.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SaveOptions {
+    /// Skip the host page cache when writing or reading the image.
+    #[prost(bool, tag = "1")]
+    pub bypass_cache: bool,
+    #[prost(bool, tag = "2")]
+    pub running: bool,
+    #[prost(bool, tag = "3")]
+    pub paused: bool,
+}
+/// Saves the domain's memory to a libvirt-managed image and stops it; the next
+/// CreateDomain resumes from the image.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ManagedSaveRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub options: ::core::option::Option<SaveOptions>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HasManagedSaveRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HasManagedSaveResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "3")]
+    pub has_managed_save: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RemoveManagedSaveRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SaveDomainRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Absolute path on the host to write the image to.
+    #[prost(string, tag = "2")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub options: ::core::option::Option<SaveOptions>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestoreDomainRequest {
+    /// Absolute path on the host of an image written by SaveDomain.
+    #[prost(string, tag = "1")]
+    pub path: ::prost::alloc::string::String,
+    /// Replacement domain XML; only host-side details such as disk paths may
+    /// differ from the saved definition. Empty to use the saved one.
+    #[prost(string, tag = "2")]
+    pub xml: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub options: ::core::option::Option<SaveOptions>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConsoleOpen {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Console or serial device alias; empty for the domain's first console.
+    #[prost(string, tag = "2")]
+    pub dev_name: ::prost::alloc::string::String,
+    /// Take the console over from a session that already has it open.
+    #[prost(bool, tag = "3")]
+    pub force: bool,
+}
+/// The first message must be `open`; every later one carries keyboard input. Closing the request
+/// stream closes the console.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConsoleInput {
+    #[prost(oneof = "console_input::Input", tags = "1, 2")]
+    pub input: ::core::option::Option<console_input::Input>,
+}
+/// Nested message and enum types in `ConsoleInput`.
+pub mod console_input {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Input {
+        #[prost(message, tag = "1")]
+        Open(super::ConsoleOpen),
+        #[prost(bytes, tag = "2")]
+        Data(::prost::alloc::vec::Vec<u8>),
     }
-    impl<T, B> Service<http::Request<B>> for LibvirtApiServer<T>
-    where
-        T: LibvirtApi,
-        B: HttpBody + Send + Sync + 'static,
-        B::Error: Into<StdError> + Send + 'static,
-    {
-        type Response = http::Response<tonic::body::BoxBody>;
-        type Error = Never;
-        type Future = BoxFuture<Self::Response, Self::Error>;
-        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-            Poll::Ready(Ok(()))
-        }
-        fn call(&mut self, req: http::Request<B>) -> Self::Future {
-            let inner = self.inner.clone();
-            match req.uri().path() {
-                "/libvirt_api.LibvirtAPI/ListDomains" => {
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConsoleOutput {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetGraphicsInfoRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GraphicsDevice {
+    /// Position among the domain's graphics devices, as taken by CreateGraphicsToken.
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    /// vnc, spice, rdp, sdl, egl-headless, ...
+    #[prost(string, tag = "2")]
+    pub r#type: ::prost::alloc::string::String,
+    /// address, network, socket or none.
+    #[prost(string, optional, tag = "3")]
+    pub listen_type: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub listen_address: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub listen_socket: ::core::option::Option<::prost::alloc::string::String>,
+    /// Unset while an autoport device has not been given a port by a running domain.
+    #[prost(uint32, optional, tag = "6")]
+    pub port: ::core::option::Option<u32>,
+    #[prost(bool, tag = "7")]
+    pub autoport: bool,
+    #[prost(uint32, optional, tag = "8")]
+    pub tls_port: ::core::option::Option<u32>,
+    /// Whether the device accepts TLS connections. Only visible for SPICE; VNC TLS is configured
+    /// host-wide in qemu.conf.
+    #[prost(bool, tag = "9")]
+    pub tls: bool,
+    #[prost(bool, tag = "10")]
+    pub password_set: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GraphicsInfoResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, repeated, tag = "3")]
+    pub devices: ::prost::alloc::vec::Vec<GraphicsDevice>,
+}
+/// Issues a single-use token for the websocket graphics proxy, which must be enabled on the server.
+/// Only VNC devices of running domains can be proxied.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateGraphicsTokenRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, tag = "2")]
+    pub index: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GraphicsTokenResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    /// Connect to ws://<proxy address>/?token=<token>.
+    #[prost(string, tag = "3")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "4")]
+    pub expires_in_s: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetScreenshotRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Head of the video device to capture; 0 is the primary screen.
+    #[prost(uint32, tag = "2")]
+    pub screen: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScreenshotResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub png: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, tag = "4")]
+    pub width: u32,
+    #[prost(uint32, tag = "5")]
+    pub height: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetInterfaceAddressesRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "InterfaceAddressSource", tag = "2")]
+    pub source: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InterfaceAddress {
+    #[prost(string, tag = "1")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub prefix: u32,
+    #[prost(bool, tag = "3")]
+    pub ipv6: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestInterface {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "2")]
+    pub mac: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, repeated, tag = "3")]
+    pub addresses: ::prost::alloc::vec::Vec<InterfaceAddress>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InterfaceAddressesResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    /// The request needed the guest agent and it is not connected or not responding, as opposed
+    /// to the agent reporting an error. A domain without an agent channel is an ordinary error.
+    #[prost(bool, tag = "3")]
+    pub agent_not_connected: bool,
+    #[prost(message, repeated, tag = "4")]
+    pub interfaces: ::prost::alloc::vec::Vec<GuestInterface>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetGuestInfoRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Empty for everything the agent supports.
+    #[prost(enumeration = "GuestInfoType", repeated, tag = "2")]
+    pub types: ::prost::alloc::vec::Vec<i32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestOsInfo {
+    #[prost(string, optional, tag = "1")]
+    pub id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "2")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "3")]
+    pub pretty_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub version: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub version_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "6")]
+    pub kernel_release: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "7")]
+    pub kernel_version: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "8")]
+    pub machine: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "9")]
+    pub variant: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "10")]
+    pub variant_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestTimezone {
+    #[prost(string, optional, tag = "1")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    /// Offset from UTC.
+    #[prost(int32, tag = "2")]
+    pub offset_s: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestUser {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "2")]
+    pub domain: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, tag = "3")]
+    pub login_time_ms: u64,
+}
+/// A host disk backing a guest filesystem.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestFilesystemDisk {
+    /// Alias of the domain's disk device, e.g. "virtio-disk0".
+    #[prost(string, optional, tag = "1")]
+    pub alias: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "2")]
+    pub serial: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "3")]
+    pub device: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestFilesystem {
+    #[prost(string, tag = "1")]
+    pub mountpoint: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub fstype: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "4")]
+    pub total_bytes: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub used_bytes: ::core::option::Option<u64>,
+    #[prost(message, repeated, tag = "6")]
+    pub disks: ::prost::alloc::vec::Vec<GuestFilesystemDisk>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestDisk {
+    /// Guest device name, e.g. "/dev/sda".
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub partition: bool,
+    /// Devices this one is built on, e.g. the disks under a partition or RAID volume.
+    #[prost(string, repeated, tag = "3")]
+    pub dependencies: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub serial: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub alias: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "6")]
+    pub guest_alias: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestInfoResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    /// See InterfaceAddressesResponse.agent_not_connected.
+    #[prost(bool, tag = "3")]
+    pub agent_not_connected: bool,
+    /// Each of the following is only set if it was asked for and the agent supports it.
+    #[prost(message, optional, tag = "4")]
+    pub os: ::core::option::Option<GuestOsInfo>,
+    #[prost(message, optional, tag = "5")]
+    pub timezone: ::core::option::Option<GuestTimezone>,
+    #[prost(string, optional, tag = "6")]
+    pub hostname: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, repeated, tag = "7")]
+    pub users: ::prost::alloc::vec::Vec<GuestUser>,
+    #[prost(message, repeated, tag = "8")]
+    pub filesystems: ::prost::alloc::vec::Vec<GuestFilesystem>,
+    #[prost(message, repeated, tag = "9")]
+    pub disks: ::prost::alloc::vec::Vec<GuestDisk>,
+}
+/// Runs a process in the guest through the guest agent. The agent only returns captured output
+/// once the process has exited, so stdout and stderr arrive after it finishes, followed by `exit`.
+/// Cancelling the call kills the process and everything it started. So does a timeout, after
+/// which the output produced so far is still sent.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestExecRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Looked up in the guest's PATH if not absolute.
+    #[prost(string, tag = "2")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "3")]
+    pub args: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// "NAME=value" pairs.
+    #[prost(string, repeated, tag = "4")]
+    pub env: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub stdin: ::prost::alloc::vec::Vec<u8>,
+    /// Kill the process if it runs longer than this; 0 for no limit.
+    #[prost(uint32, tag = "6")]
+    pub timeout_s: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestExecExit {
+    /// Unset if the process was killed by a signal, or timed out and its status was lost.
+    #[prost(int32, optional, tag = "1")]
+    pub exit_code: ::core::option::Option<i32>,
+    #[prost(int32, optional, tag = "2")]
+    pub signal: ::core::option::Option<i32>,
+    #[prost(bool, tag = "3")]
+    pub timed_out: bool,
+    /// The agent caps captured output and drops the rest.
+    #[prost(bool, tag = "4")]
+    pub stdout_truncated: bool,
+    #[prost(bool, tag = "5")]
+    pub stderr_truncated: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestExecOutput {
+    #[prost(oneof = "guest_exec_output::Output", tags = "1, 2, 3")]
+    pub output: ::core::option::Option<guest_exec_output::Output>,
+}
+/// Nested message and enum types in `GuestExecOutput`.
+pub mod guest_exec_output {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Output {
+        #[prost(bytes, tag = "1")]
+        Stdout(::prost::alloc::vec::Vec<u8>),
+        #[prost(bytes, tag = "2")]
+        Stderr(::prost::alloc::vec::Vec<u8>),
+        #[prost(message, tag = "3")]
+        Exit(super::GuestExecExit),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestReadFileRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Absolute path in the guest.
+    #[prost(string, tag = "2")]
+    pub path: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestFileChunk {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestWriteFileOpen {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Absolute path in the guest. The file is created if needed and, unless appending, truncated
+    /// as soon as it is opened: a failed or cancelled upload leaves a partial file behind, not the
+    /// old contents. To replace a file atomically, upload to a temporary path and rename it in the
+    /// guest with GuestExec.
+    #[prost(string, tag = "2")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub append: bool,
+}
+/// The first message must be `open`; every later one carries the next piece of the file.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestWriteFileRequest {
+    #[prost(oneof = "guest_write_file_request::Input", tags = "1, 2")]
+    pub input: ::core::option::Option<guest_write_file_request::Input>,
+}
+/// Nested message and enum types in `GuestWriteFileRequest`.
+pub mod guest_write_file_request {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Input {
+        #[prost(message, tag = "1")]
+        Open(super::GuestWriteFileOpen),
+        #[prost(bytes, tag = "2")]
+        Data(::prost::alloc::vec::Vec<u8>),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GuestWriteFileResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    /// See InterfaceAddressesResponse.agent_not_connected.
+    #[prost(bool, tag = "3")]
+    pub agent_not_connected: bool,
+    /// Written before any failure; a failed write leaves this much of the file in place.
+    #[prost(uint64, tag = "4")]
+    pub bytes_written: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FsFreezeRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Guest mountpoints to freeze; every guest filesystem when empty.
+    #[prost(string, repeated, tag = "2")]
+    pub mountpoints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FsThawRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// Must be empty: QEMU only thaws every frozen guest filesystem at once and rejects a list.
+    #[prost(string, repeated, tag = "2")]
+    pub mountpoints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FsFreezeResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    /// See InterfaceAddressesResponse.agent_not_connected.
+    #[prost(bool, tag = "3")]
+    pub agent_not_connected: bool,
+    /// How many filesystems were frozen or thawed.
+    #[prost(uint32, tag = "4")]
+    pub filesystems: u32,
+}
+/// Freezes the guest's filesystems, takes a disk-only snapshot and thaws them again. The thaw
+/// happens whether or not the snapshot succeeds, and also when the client goes away mid-call.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateQuiescedSnapshotRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub uuid: ::prost::alloc::vec::Vec<u8>,
+    /// As in CreateSnapshotRequest.
+    #[prost(string, tag = "2")]
+    pub xml: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(bool, tag = "5")]
+    pub atomic: bool,
+    /// As in FSFreezeRequest.
+    #[prost(string, repeated, tag = "6")]
+    pub mountpoints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuiescedSnapshotResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+    /// See InterfaceAddressesResponse.agent_not_connected.
+    #[prost(bool, tag = "3")]
+    pub agent_not_connected: bool,
+    /// Set when the snapshot was created, even if thawing afterwards failed.
+    #[prost(string, tag = "4")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "5")]
+    pub frozen: u32,
+    #[prost(uint32, tag = "6")]
+    pub thawed: u32,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DomainState {
+    Unspecified = 0,
+    Nostate = 1,
+    Running = 2,
+    Blocked = 3,
+    Paused = 4,
+    Shutdown = 5,
+    Shutoff = 6,
+    Crashed = 7,
+    Pmsuspended = 8,
+}
+/// Values match virDomainShutdownFlagValues / virDomainRebootFlagValues so they
+/// can be OR'd together and passed straight to libvirt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ShutdownMode {
+    Default = 0,
+    AcpiPowerBtn = 1,
+    GuestAgent = 2,
+    Initctl = 4,
+    Signal = 8,
+    Paravirt = 16,
+}
+/// Which definition of the domain a device change applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DeviceScope {
+    /// The running domain only, same as DEVICE_SCOPE_LIVE.
+    Unspecified = 0,
+    /// The running domain if it is active, otherwise its persistent config.
+    Current = 1,
+    Live = 2,
+    Config = 3,
+    Both = 4,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum UsbDeviceAction {
+    Unspecified = 0,
+    /// Plugged in before the watch started; sent once per device up front.
+    Present = 1,
+    Add = 2,
+    Change = 3,
+    Remove = 4,
+}
+/// virDomainEventType, shifted by one to keep 0 free.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DomainLifecycleEventType {
+    Unspecified = 0,
+    Defined = 1,
+    Undefined = 2,
+    Started = 3,
+    Suspended = 4,
+    Resumed = 5,
+    Stopped = 6,
+    Shutdown = 7,
+    Pmsuspended = 8,
+    Crashed = 9,
+}
+/// virDomainEventWatchdogAction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WatchdogAction {
+    None = 0,
+    Pause = 1,
+    Reset = 2,
+    Poweroff = 3,
+    Shutdown = 4,
+    Debug = 5,
+    Injectnmi = 6,
+}
+/// virDomainEventIOErrorAction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum IoErrorAction {
+    None = 0,
+    Pause = 1,
+    Report = 2,
+}
+/// virConnectDomainEventAgentLifecycleState
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AgentState {
+    Unspecified = 0,
+    Connected = 1,
+    Disconnected = 2,
+}
+/// virConnectDomainEventAgentLifecycleReason
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AgentStateReason {
+    Unknown = 0,
+    DomainStarted = 1,
+    Channel = 2,
+}
+/// virDomainStatsTypes, for the GetDomainStats bitmask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DomainStatsGroup {
+    Unspecified = 0,
+    State = 1,
+    Cpu = 2,
+    Balloon = 4,
+    Vcpu = 8,
+    Interface = 16,
+    Block = 32,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum InterfaceAddressSource {
+    /// Same as INTERFACE_ADDRESS_SOURCE_LEASE.
+    Unspecified = 0,
+    /// DHCP leases handed out by libvirt-managed networks.
+    Lease = 1,
+    /// The guest agent, which also sees interfaces and addresses libvirt does not manage.
+    Agent = 2,
+    /// The host's ARP table.
+    Arp = 3,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum GuestInfoType {
+    Unspecified = 0,
+    Users = 1,
+    Os = 2,
+    Timezone = 4,
+    Hostname = 8,
+    Filesystem = 16,
+    Disks = 32,
+}
+#[doc = r" Generated client implementations."]
+pub mod libvirt_api_client {
+    #![allow(unused_variables, dead_code, missing_docs)]
+    use tonic::codegen::*;
+    pub struct LibvirtApiClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl LibvirtApiClient<tonic::transport::Channel> {
+        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> LibvirtApiClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::ResponseBody: Body + HttpBody + Send + 'static,
+        T::Error: Into<StdError>,
+        <T::ResponseBody as HttpBody>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
+            let inner = tonic::client::Grpc::with_interceptor(inner, interceptor);
+            Self { inner }
+        }
+        pub async fn list_domains(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListDomainsRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Domain>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ListDomains");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn create_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/CreateDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn destroy_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DestroyDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/DestroyDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn shutdown_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ShutdownDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ShutdownDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn reboot_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RebootDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/RebootDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn reset_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResetDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ResetDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn suspend_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SuspendDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/SuspendDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn resume_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResumeDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ResumeDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn define_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DefineDomainRequest>,
+        ) -> Result<tonic::Response<super::DefineDomainResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/DefineDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn undefine_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UndefineDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/UndefineDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn create_transient_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateTransientDomainRequest>,
+        ) -> Result<tonic::Response<super::DefineDomainResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/libvirt_api.LibvirtAPI/CreateTransientDomain",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_domain_xml(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetDomainXmlRequest>,
+        ) -> Result<tonic::Response<super::DomainXmlResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GetDomainXML");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn update_domain_xml(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateDomainXmlRequest>,
+        ) -> Result<tonic::Response<super::UpdateDomainXmlResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/UpdateDomainXML");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn list_usb_devices(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListUsbDevicesRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::UsbDevice>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ListUSBDevices");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn attach_device(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AttachDeviceRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/AttachDevice");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn detach_device(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DetachDeviceRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/DetachDevice");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn list_domain_devices(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListDomainDevicesRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::DomainDevice>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ListDomainDevices");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn watch_usb_devices(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchUsbDevicesRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::UsbDeviceEvent>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/WatchUSBDevices");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn list_usb_rules(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListUsbRulesRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::UsbRule>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ListUSBRules");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn create_usb_rule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateUsbRuleRequest>,
+        ) -> Result<tonic::Response<super::UsbRuleResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/CreateUSBRule");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn update_usb_rule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateUsbRuleRequest>,
+        ) -> Result<tonic::Response<super::UsbRuleResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/UpdateUSBRule");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn delete_usb_rule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteUsbRuleRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/DeleteUSBRule");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn watch_domain_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchDomainEventsRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::DomainEvent>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/WatchDomainEvents");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn get_domain_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetDomainStatsRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::DomainStats>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GetDomainStats");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn stream_domain_metrics(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamDomainMetricsRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::DomainMetrics>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/StreamDomainMetrics");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn create_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateSnapshotRequest>,
+        ) -> Result<tonic::Response<super::SnapshotResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/CreateSnapshot");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn list_snapshots(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListSnapshotsRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Snapshot>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ListSnapshots");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn revert_to_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevertToSnapshotRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/RevertToSnapshot");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn delete_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteSnapshotRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/DeleteSnapshot");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_snapshot_xml(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSnapshotXmlRequest>,
+        ) -> Result<tonic::Response<super::SnapshotXmlResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GetSnapshotXML");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn managed_save(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ManagedSaveRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/ManagedSave");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn has_managed_save(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HasManagedSaveRequest>,
+        ) -> Result<tonic::Response<super::HasManagedSaveResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/HasManagedSave");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn remove_managed_save(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RemoveManagedSaveRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/RemoveManagedSave");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn save_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SaveDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/SaveDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn restore_domain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RestoreDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/RestoreDomain");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn open_console(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::ConsoleInput>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::ConsoleOutput>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/OpenConsole");
+            self.inner
+                .streaming(request.into_streaming_request(), path, codec)
+                .await
+        }
+        pub async fn get_graphics_info(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetGraphicsInfoRequest>,
+        ) -> Result<tonic::Response<super::GraphicsInfoResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GetGraphicsInfo");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn create_graphics_token(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateGraphicsTokenRequest>,
+        ) -> Result<tonic::Response<super::GraphicsTokenResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/CreateGraphicsToken");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_screenshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetScreenshotRequest>,
+        ) -> Result<tonic::Response<super::ScreenshotResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GetScreenshot");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_interface_addresses(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetInterfaceAddressesRequest>,
+        ) -> Result<tonic::Response<super::InterfaceAddressesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/libvirt_api.LibvirtAPI/GetInterfaceAddresses",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_guest_info(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetGuestInfoRequest>,
+        ) -> Result<tonic::Response<super::GuestInfoResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GetGuestInfo");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn guest_exec(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GuestExecRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::GuestExecOutput>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GuestExec");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn guest_read_file(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GuestReadFileRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::GuestFileChunk>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GuestReadFile");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn guest_write_file(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::GuestWriteFileRequest>,
+        ) -> Result<tonic::Response<super::GuestWriteFileResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/GuestWriteFile");
+            self.inner
+                .client_streaming(request.into_streaming_request(), path, codec)
+                .await
+        }
+        pub async fn fs_freeze(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FsFreezeRequest>,
+        ) -> Result<tonic::Response<super::FsFreezeResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/FSFreeze");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn fs_thaw(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FsThawRequest>,
+        ) -> Result<tonic::Response<super::FsFreezeResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/libvirt_api.LibvirtAPI/FSThaw");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn create_quiesced_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateQuiescedSnapshotRequest>,
+        ) -> Result<tonic::Response<super::QuiescedSnapshotResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/libvirt_api.LibvirtAPI/CreateQuiescedSnapshot",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+    impl<T: Clone> Clone for LibvirtApiClient<T> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+    impl<T> std::fmt::Debug for LibvirtApiClient<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "LibvirtApiClient {{ ... }}")
+        }
+    }
+}
+#[doc = r" Generated server implementations."]
+pub mod libvirt_api_server {
+    #![allow(unused_variables, dead_code, missing_docs)]
+    use tonic::codegen::*;
+    #[doc = "Generated trait containing gRPC methods that should be implemented for use with LibvirtApiServer."]
+    #[async_trait]
+    pub trait LibvirtApi: Send + Sync + 'static {
+        #[doc = "Server streaming response type for the ListDomains method."]
+        type ListDomainsStream: futures_core::Stream<Item = Result<super::Domain, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn list_domains(
+            &self,
+            request: tonic::Request<super::ListDomainsRequest>,
+        ) -> Result<tonic::Response<Self::ListDomainsStream>, tonic::Status>;
+        async fn create_domain(
+            &self,
+            request: tonic::Request<super::CreateDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn destroy_domain(
+            &self,
+            request: tonic::Request<super::DestroyDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn shutdown_domain(
+            &self,
+            request: tonic::Request<super::ShutdownDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn reboot_domain(
+            &self,
+            request: tonic::Request<super::RebootDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn reset_domain(
+            &self,
+            request: tonic::Request<super::ResetDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn suspend_domain(
+            &self,
+            request: tonic::Request<super::SuspendDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn resume_domain(
+            &self,
+            request: tonic::Request<super::ResumeDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn define_domain(
+            &self,
+            request: tonic::Request<super::DefineDomainRequest>,
+        ) -> Result<tonic::Response<super::DefineDomainResponse>, tonic::Status>;
+        async fn undefine_domain(
+            &self,
+            request: tonic::Request<super::UndefineDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn create_transient_domain(
+            &self,
+            request: tonic::Request<super::CreateTransientDomainRequest>,
+        ) -> Result<tonic::Response<super::DefineDomainResponse>, tonic::Status>;
+        async fn get_domain_xml(
+            &self,
+            request: tonic::Request<super::GetDomainXmlRequest>,
+        ) -> Result<tonic::Response<super::DomainXmlResponse>, tonic::Status>;
+        async fn update_domain_xml(
+            &self,
+            request: tonic::Request<super::UpdateDomainXmlRequest>,
+        ) -> Result<tonic::Response<super::UpdateDomainXmlResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the ListUSBDevices method."]
+        type ListUSBDevicesStream: futures_core::Stream<Item = Result<super::UsbDevice, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn list_usb_devices(
+            &self,
+            request: tonic::Request<super::ListUsbDevicesRequest>,
+        ) -> Result<tonic::Response<Self::ListUSBDevicesStream>, tonic::Status>;
+        async fn attach_device(
+            &self,
+            request: tonic::Request<super::AttachDeviceRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn detach_device(
+            &self,
+            request: tonic::Request<super::DetachDeviceRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the ListDomainDevices method."]
+        type ListDomainDevicesStream: futures_core::Stream<Item = Result<super::DomainDevice, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn list_domain_devices(
+            &self,
+            request: tonic::Request<super::ListDomainDevicesRequest>,
+        ) -> Result<tonic::Response<Self::ListDomainDevicesStream>, tonic::Status>;
+        #[doc = "Server streaming response type for the WatchUSBDevices method."]
+        type WatchUSBDevicesStream: futures_core::Stream<Item = Result<super::UsbDeviceEvent, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn watch_usb_devices(
+            &self,
+            request: tonic::Request<super::WatchUsbDevicesRequest>,
+        ) -> Result<tonic::Response<Self::WatchUSBDevicesStream>, tonic::Status>;
+        #[doc = "Server streaming response type for the ListUSBRules method."]
+        type ListUSBRulesStream: futures_core::Stream<Item = Result<super::UsbRule, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn list_usb_rules(
+            &self,
+            request: tonic::Request<super::ListUsbRulesRequest>,
+        ) -> Result<tonic::Response<Self::ListUSBRulesStream>, tonic::Status>;
+        async fn create_usb_rule(
+            &self,
+            request: tonic::Request<super::CreateUsbRuleRequest>,
+        ) -> Result<tonic::Response<super::UsbRuleResponse>, tonic::Status>;
+        async fn update_usb_rule(
+            &self,
+            request: tonic::Request<super::UpdateUsbRuleRequest>,
+        ) -> Result<tonic::Response<super::UsbRuleResponse>, tonic::Status>;
+        async fn delete_usb_rule(
+            &self,
+            request: tonic::Request<super::DeleteUsbRuleRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the WatchDomainEvents method."]
+        type WatchDomainEventsStream: futures_core::Stream<Item = Result<super::DomainEvent, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn watch_domain_events(
+            &self,
+            request: tonic::Request<super::WatchDomainEventsRequest>,
+        ) -> Result<tonic::Response<Self::WatchDomainEventsStream>, tonic::Status>;
+        #[doc = "Server streaming response type for the GetDomainStats method."]
+        type GetDomainStatsStream: futures_core::Stream<Item = Result<super::DomainStats, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn get_domain_stats(
+            &self,
+            request: tonic::Request<super::GetDomainStatsRequest>,
+        ) -> Result<tonic::Response<Self::GetDomainStatsStream>, tonic::Status>;
+        #[doc = "Server streaming response type for the StreamDomainMetrics method."]
+        type StreamDomainMetricsStream: futures_core::Stream<Item = Result<super::DomainMetrics, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn stream_domain_metrics(
+            &self,
+            request: tonic::Request<super::StreamDomainMetricsRequest>,
+        ) -> Result<tonic::Response<Self::StreamDomainMetricsStream>, tonic::Status>;
+        async fn create_snapshot(
+            &self,
+            request: tonic::Request<super::CreateSnapshotRequest>,
+        ) -> Result<tonic::Response<super::SnapshotResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the ListSnapshots method."]
+        type ListSnapshotsStream: futures_core::Stream<Item = Result<super::Snapshot, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn list_snapshots(
+            &self,
+            request: tonic::Request<super::ListSnapshotsRequest>,
+        ) -> Result<tonic::Response<Self::ListSnapshotsStream>, tonic::Status>;
+        async fn revert_to_snapshot(
+            &self,
+            request: tonic::Request<super::RevertToSnapshotRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn delete_snapshot(
+            &self,
+            request: tonic::Request<super::DeleteSnapshotRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn get_snapshot_xml(
+            &self,
+            request: tonic::Request<super::GetSnapshotXmlRequest>,
+        ) -> Result<tonic::Response<super::SnapshotXmlResponse>, tonic::Status>;
+        async fn managed_save(
+            &self,
+            request: tonic::Request<super::ManagedSaveRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn has_managed_save(
+            &self,
+            request: tonic::Request<super::HasManagedSaveRequest>,
+        ) -> Result<tonic::Response<super::HasManagedSaveResponse>, tonic::Status>;
+        async fn remove_managed_save(
+            &self,
+            request: tonic::Request<super::RemoveManagedSaveRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn save_domain(
+            &self,
+            request: tonic::Request<super::SaveDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        async fn restore_domain(
+            &self,
+            request: tonic::Request<super::RestoreDomainRequest>,
+        ) -> Result<tonic::Response<super::SuccessResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the OpenConsole method."]
+        type OpenConsoleStream: futures_core::Stream<Item = Result<super::ConsoleOutput, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn open_console(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::ConsoleInput>>,
+        ) -> Result<tonic::Response<Self::OpenConsoleStream>, tonic::Status>;
+        async fn get_graphics_info(
+            &self,
+            request: tonic::Request<super::GetGraphicsInfoRequest>,
+        ) -> Result<tonic::Response<super::GraphicsInfoResponse>, tonic::Status>;
+        async fn create_graphics_token(
+            &self,
+            request: tonic::Request<super::CreateGraphicsTokenRequest>,
+        ) -> Result<tonic::Response<super::GraphicsTokenResponse>, tonic::Status>;
+        async fn get_screenshot(
+            &self,
+            request: tonic::Request<super::GetScreenshotRequest>,
+        ) -> Result<tonic::Response<super::ScreenshotResponse>, tonic::Status>;
+        async fn get_interface_addresses(
+            &self,
+            request: tonic::Request<super::GetInterfaceAddressesRequest>,
+        ) -> Result<tonic::Response<super::InterfaceAddressesResponse>, tonic::Status>;
+        async fn get_guest_info(
+            &self,
+            request: tonic::Request<super::GetGuestInfoRequest>,
+        ) -> Result<tonic::Response<super::GuestInfoResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the GuestExec method."]
+        type GuestExecStream: futures_core::Stream<Item = Result<super::GuestExecOutput, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn guest_exec(
+            &self,
+            request: tonic::Request<super::GuestExecRequest>,
+        ) -> Result<tonic::Response<Self::GuestExecStream>, tonic::Status>;
+        #[doc = "Server streaming response type for the GuestReadFile method."]
+        type GuestReadFileStream: futures_core::Stream<Item = Result<super::GuestFileChunk, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        async fn guest_read_file(
+            &self,
+            request: tonic::Request<super::GuestReadFileRequest>,
+        ) -> Result<tonic::Response<Self::GuestReadFileStream>, tonic::Status>;
+        async fn guest_write_file(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::GuestWriteFileRequest>>,
+        ) -> Result<tonic::Response<super::GuestWriteFileResponse>, tonic::Status>;
+        async fn fs_freeze(
+            &self,
+            request: tonic::Request<super::FsFreezeRequest>,
+        ) -> Result<tonic::Response<super::FsFreezeResponse>, tonic::Status>;
+        async fn fs_thaw(
+            &self,
+            request: tonic::Request<super::FsThawRequest>,
+        ) -> Result<tonic::Response<super::FsFreezeResponse>, tonic::Status>;
+        async fn create_quiesced_snapshot(
+            &self,
+            request: tonic::Request<super::CreateQuiescedSnapshotRequest>,
+        ) -> Result<tonic::Response<super::QuiescedSnapshotResponse>, tonic::Status>;
+    }
+    #[derive(Debug)]
+    pub struct LibvirtApiServer<T: LibvirtApi> {
+        inner: _Inner<T>,
+    }
+    struct _Inner<T>(Arc<T>, Option<tonic::Interceptor>);
+    impl<T: LibvirtApi> LibvirtApiServer<T> {
+        pub fn new(inner: T) -> Self {
+            let inner = Arc::new(inner);
+            let inner = _Inner(inner, None);
+            Self { inner }
+        }
+        pub fn with_interceptor(inner: T, interceptor: impl Into<tonic::Interceptor>) -> Self {
+            let inner = Arc::new(inner);
+            let inner = _Inner(inner, Some(interceptor.into()));
+            Self { inner }
+        }
+    }
+    impl<T, B> Service<http::Request<B>> for LibvirtApiServer<T>
+    where
+        T: LibvirtApi,
+        B: HttpBody + Send + Sync + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = Never;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/libvirt_api.LibvirtAPI/ListDomains" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListDomainsSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::ListDomainsRequest>
+                        for ListDomainsSvc<T>
+                    {
+                        type Response = super::Domain;
+                        type ResponseStream = T::ListDomainsStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListDomainsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_domains(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = ListDomainsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/CreateDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::CreateDomainRequest> for CreateDomainSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).create_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = CreateDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/DestroyDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct DestroyDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::DestroyDomainRequest>
+                        for DestroyDomainSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DestroyDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).destroy_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = DestroyDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ShutdownDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct ShutdownDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::ShutdownDomainRequest>
+                        for ShutdownDomainSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ShutdownDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).shutdown_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ShutdownDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/RebootDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct RebootDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::RebootDomainRequest> for RebootDomainSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RebootDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reboot_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = RebootDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ResetDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResetDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::ResetDomainRequest> for ResetDomainSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResetDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reset_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ResetDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/SuspendDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct SuspendDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::SuspendDomainRequest>
+                        for SuspendDomainSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SuspendDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).suspend_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = SuspendDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ResumeDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResumeDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::ResumeDomainRequest> for ResumeDomainSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResumeDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).resume_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ResumeDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/DefineDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct DefineDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::DefineDomainRequest> for DefineDomainSvc<T> {
+                        type Response = super::DefineDomainResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DefineDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).define_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = DefineDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/UndefineDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct UndefineDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::UndefineDomainRequest>
+                        for UndefineDomainSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UndefineDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).undefine_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = UndefineDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/CreateTransientDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateTransientDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::UnaryService<super::CreateTransientDomainRequest>
+                        for CreateTransientDomainSvc<T>
+                    {
+                        type Response = super::DefineDomainResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateTransientDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut =
+                                async move { (*inner).create_transient_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = CreateTransientDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/GetDomainXML" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetDomainXMLSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::GetDomainXmlRequest> for GetDomainXMLSvc<T> {
+                        type Response = super::DomainXmlResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetDomainXmlRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_domain_xml(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetDomainXMLSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/UpdateDomainXML" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateDomainXMLSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::UpdateDomainXmlRequest>
+                        for UpdateDomainXMLSvc<T>
+                    {
+                        type Response = super::UpdateDomainXmlResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateDomainXmlRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update_domain_xml(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = UpdateDomainXMLSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ListUSBDevices" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListUSBDevicesSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::ListUsbDevicesRequest>
+                        for ListUSBDevicesSvc<T>
+                    {
+                        type Response = super::UsbDevice;
+                        type ResponseStream = T::ListUSBDevicesStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListUsbDevicesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_usb_devices(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = ListUSBDevicesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/AttachDevice" => {
+                    #[allow(non_camel_case_types)]
+                    struct AttachDeviceSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::AttachDeviceRequest> for AttachDeviceSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AttachDeviceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).attach_device(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = AttachDeviceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/DetachDevice" => {
+                    #[allow(non_camel_case_types)]
+                    struct DetachDeviceSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::DetachDeviceRequest> for DetachDeviceSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DetachDeviceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).detach_device(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = DetachDeviceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ListDomainDevices" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListDomainDevicesSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::ListDomainDevicesRequest>
+                        for ListDomainDevicesSvc<T>
+                    {
+                        type Response = super::DomainDevice;
+                        type ResponseStream = T::ListDomainDevicesStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListDomainDevicesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_domain_devices(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = ListDomainDevicesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/WatchUSBDevices" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchUSBDevicesSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::WatchUsbDevicesRequest>
+                        for WatchUSBDevicesSvc<T>
+                    {
+                        type Response = super::UsbDeviceEvent;
+                        type ResponseStream = T::WatchUSBDevicesStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchUsbDevicesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).watch_usb_devices(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = WatchUSBDevicesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ListUSBRules" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListUSBRulesSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::ListUsbRulesRequest>
+                        for ListUSBRulesSvc<T>
+                    {
+                        type Response = super::UsbRule;
+                        type ResponseStream = T::ListUSBRulesStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListUsbRulesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_usb_rules(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = ListUSBRulesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/CreateUSBRule" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateUSBRuleSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::CreateUsbRuleRequest>
+                        for CreateUSBRuleSvc<T>
+                    {
+                        type Response = super::UsbRuleResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateUsbRuleRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).create_usb_rule(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = CreateUSBRuleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/UpdateUSBRule" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateUSBRuleSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::UpdateUsbRuleRequest>
+                        for UpdateUSBRuleSvc<T>
+                    {
+                        type Response = super::UsbRuleResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateUsbRuleRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update_usb_rule(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = UpdateUSBRuleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/DeleteUSBRule" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteUSBRuleSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::DeleteUsbRuleRequest>
+                        for DeleteUSBRuleSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteUsbRuleRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).delete_usb_rule(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = DeleteUSBRuleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/WatchDomainEvents" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchDomainEventsSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::WatchDomainEventsRequest>
+                        for WatchDomainEventsSvc<T>
+                    {
+                        type Response = super::DomainEvent;
+                        type ResponseStream = T::WatchDomainEventsStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchDomainEventsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).watch_domain_events(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = WatchDomainEventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/GetDomainStats" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetDomainStatsSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::GetDomainStatsRequest>
+                        for GetDomainStatsSvc<T>
+                    {
+                        type Response = super::DomainStats;
+                        type ResponseStream = T::GetDomainStatsStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetDomainStatsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_domain_stats(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = GetDomainStatsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/StreamDomainMetrics" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamDomainMetricsSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::StreamDomainMetricsRequest>
+                        for StreamDomainMetricsSvc<T>
+                    {
+                        type Response = super::DomainMetrics;
+                        type ResponseStream = T::StreamDomainMetricsStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamDomainMetricsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).stream_domain_metrics(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = StreamDomainMetricsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/CreateSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateSnapshotSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::CreateSnapshotRequest>
+                        for CreateSnapshotSvc<T>
+                    {
+                        type Response = super::SnapshotResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateSnapshotRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).create_snapshot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = CreateSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ListSnapshots" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListSnapshotsSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::ListSnapshotsRequest>
+                        for ListSnapshotsSvc<T>
+                    {
+                        type Response = super::Snapshot;
+                        type ResponseStream = T::ListSnapshotsStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListSnapshotsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_snapshots(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = ListSnapshotsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/RevertToSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevertToSnapshotSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::RevertToSnapshotRequest>
+                        for RevertToSnapshotSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevertToSnapshotRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).revert_to_snapshot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = RevertToSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/DeleteSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteSnapshotSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::DeleteSnapshotRequest>
+                        for DeleteSnapshotSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteSnapshotRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).delete_snapshot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = DeleteSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/GetSnapshotXML" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSnapshotXMLSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::GetSnapshotXmlRequest>
+                        for GetSnapshotXMLSvc<T>
+                    {
+                        type Response = super::SnapshotXmlResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSnapshotXmlRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_snapshot_xml(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetSnapshotXMLSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/ManagedSave" => {
+                    #[allow(non_camel_case_types)]
+                    struct ManagedSaveSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::ManagedSaveRequest> for ManagedSaveSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ManagedSaveRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).managed_save(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = ManagedSaveSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/HasManagedSave" => {
+                    #[allow(non_camel_case_types)]
+                    struct HasManagedSaveSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::HasManagedSaveRequest>
+                        for HasManagedSaveSvc<T>
+                    {
+                        type Response = super::HasManagedSaveResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HasManagedSaveRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).has_managed_save(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = HasManagedSaveSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/RemoveManagedSave" => {
+                    #[allow(non_camel_case_types)]
+                    struct RemoveManagedSaveSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::RemoveManagedSaveRequest>
+                        for RemoveManagedSaveSvc<T>
+                    {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RemoveManagedSaveRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).remove_managed_save(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = RemoveManagedSaveSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/SaveDomain" => {
                     #[allow(non_camel_case_types)]
-                    struct ListDomainsSvc<T: LibvirtApi>(pub Arc<T>);
-                    impl<T: LibvirtApi>
-                        tonic::server::ServerStreamingService<super::ListDomainsRequest>
-                        for ListDomainsSvc<T>
+                    struct SaveDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::SaveDomainRequest> for SaveDomainSvc<T> {
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SaveDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).save_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = SaveDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/RestoreDomain" => {
+                    #[allow(non_camel_case_types)]
+                    struct RestoreDomainSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::RestoreDomainRequest>
+                        for RestoreDomainSvc<T>
                     {
-                        type Response = super::Domain;
-                        type ResponseStream = T::ListDomainsStream;
+                        type Response = super::SuccessResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RestoreDomainRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).restore_domain(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = RestoreDomainSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/OpenConsole" => {
+                    #[allow(non_camel_case_types)]
+                    struct OpenConsoleSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::StreamingService<super::ConsoleInput> for OpenConsoleSvc<T> {
+                        type Response = super::ConsoleOutput;
+                        type ResponseStream = T::OpenConsoleStream;
                         type Future =
                             BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListDomainsRequest>,
+                            request: tonic::Request<tonic::Streaming<super::ConsoleInput>>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).list_domains(request).await };
+                            let fut = async move { (*inner).open_console(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -335,30 +3705,32 @@ pub mod libvirt_api_server {
                     let fut = async move {
                         let interceptor = inner.1;
                         let inner = inner.0;
-                        let method = ListDomainsSvc(inner);
+                        let method = OpenConsoleSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = if let Some(interceptor) = interceptor {
                             tonic::server::Grpc::with_interceptor(codec, interceptor)
                         } else {
                             tonic::server::Grpc::new(codec)
                         };
-                        let res = grpc.server_streaming(method, req).await;
+                        let res = grpc.streaming(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
                 }
-                "/libvirt_api.LibvirtAPI/CreateDomain" => {
+                "/libvirt_api.LibvirtAPI/GetGraphicsInfo" => {
                     #[allow(non_camel_case_types)]
-                    struct CreateDomainSvc<T: LibvirtApi>(pub Arc<T>);
-                    impl<T: LibvirtApi> tonic::server::UnaryService<super::CreateDomainRequest> for CreateDomainSvc<T> {
-                        type Response = super::SuccessResponse;
+                    struct GetGraphicsInfoSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::GetGraphicsInfoRequest>
+                        for GetGraphicsInfoSvc<T>
+                    {
+                        type Response = super::GraphicsInfoResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CreateDomainRequest>,
+                            request: tonic::Request<super::GetGraphicsInfoRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).create_domain(request).await };
+                            let fut = async move { (*inner).get_graphics_info(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -366,7 +3738,7 @@ pub mod libvirt_api_server {
                     let fut = async move {
                         let interceptor = inner.1.clone();
                         let inner = inner.0;
-                        let method = CreateDomainSvc(inner);
+                        let method = GetGraphicsInfoSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = if let Some(interceptor) = interceptor {
                             tonic::server::Grpc::with_interceptor(codec, interceptor)
@@ -378,20 +3750,21 @@ pub mod libvirt_api_server {
                     };
                     Box::pin(fut)
                 }
-                "/libvirt_api.LibvirtAPI/DestroyDomain" => {
+                "/libvirt_api.LibvirtAPI/CreateGraphicsToken" => {
                     #[allow(non_camel_case_types)]
-                    struct DestroyDomainSvc<T: LibvirtApi>(pub Arc<T>);
-                    impl<T: LibvirtApi> tonic::server::UnaryService<super::DestroyDomainRequest>
-                        for DestroyDomainSvc<T>
+                    struct CreateGraphicsTokenSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::UnaryService<super::CreateGraphicsTokenRequest>
+                        for CreateGraphicsTokenSvc<T>
                     {
-                        type Response = super::SuccessResponse;
+                        type Response = super::GraphicsTokenResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DestroyDomainRequest>,
+                            request: tonic::Request<super::CreateGraphicsTokenRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).destroy_domain(request).await };
+                            let fut = async move { (*inner).create_graphics_token(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -399,7 +3772,7 @@ pub mod libvirt_api_server {
                     let fut = async move {
                         let interceptor = inner.1.clone();
                         let inner = inner.0;
-                        let method = DestroyDomainSvc(inner);
+                        let method = CreateGraphicsTokenSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = if let Some(interceptor) = interceptor {
                             tonic::server::Grpc::with_interceptor(codec, interceptor)
@@ -411,23 +3784,122 @@ pub mod libvirt_api_server {
                     };
                     Box::pin(fut)
                 }
-                "/libvirt_api.LibvirtAPI/ListUSBDevices" => {
+                "/libvirt_api.LibvirtAPI/GetScreenshot" => {
                     #[allow(non_camel_case_types)]
-                    struct ListUSBDevicesSvc<T: LibvirtApi>(pub Arc<T>);
+                    struct GetScreenshotSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::GetScreenshotRequest>
+                        for GetScreenshotSvc<T>
+                    {
+                        type Response = super::ScreenshotResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetScreenshotRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_screenshot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetScreenshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/GetInterfaceAddresses" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetInterfaceAddressesSvc<T: LibvirtApi>(pub Arc<T>);
                     impl<T: LibvirtApi>
-                        tonic::server::ServerStreamingService<super::ListUsbDevicesRequest>
-                        for ListUSBDevicesSvc<T>
+                        tonic::server::UnaryService<super::GetInterfaceAddressesRequest>
+                        for GetInterfaceAddressesSvc<T>
                     {
-                        type Response = super::UsbDevice;
-                        type ResponseStream = T::ListUSBDevicesStream;
+                        type Response = super::InterfaceAddressesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetInterfaceAddressesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut =
+                                async move { (*inner).get_interface_addresses(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetInterfaceAddressesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/GetGuestInfo" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetGuestInfoSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::GetGuestInfoRequest> for GetGuestInfoSvc<T> {
+                        type Response = super::GuestInfoResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetGuestInfoRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_guest_info(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = GetGuestInfoSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/GuestExec" => {
+                    #[allow(non_camel_case_types)]
+                    struct GuestExecSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::GuestExecRequest>
+                        for GuestExecSvc<T>
+                    {
+                        type Response = super::GuestExecOutput;
+                        type ResponseStream = T::GuestExecStream;
                         type Future =
                             BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListUsbDevicesRequest>,
+                            request: tonic::Request<super::GuestExecRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).list_usb_devices(request).await };
+                            let fut = async move { (*inner).guest_exec(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -435,7 +3907,7 @@ pub mod libvirt_api_server {
                     let fut = async move {
                         let interceptor = inner.1;
                         let inner = inner.0;
-                        let method = ListUSBDevicesSvc(inner);
+                        let method = GuestExecSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = if let Some(interceptor) = interceptor {
                             tonic::server::Grpc::with_interceptor(codec, interceptor)
@@ -447,18 +3919,88 @@ pub mod libvirt_api_server {
                     };
                     Box::pin(fut)
                 }
-                "/libvirt_api.LibvirtAPI/AttachDevice" => {
+                "/libvirt_api.LibvirtAPI/GuestReadFile" => {
                     #[allow(non_camel_case_types)]
-                    struct AttachDeviceSvc<T: LibvirtApi>(pub Arc<T>);
-                    impl<T: LibvirtApi> tonic::server::UnaryService<super::AttachDeviceRequest> for AttachDeviceSvc<T> {
-                        type Response = super::SuccessResponse;
+                    struct GuestReadFileSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ServerStreamingService<super::GuestReadFileRequest>
+                        for GuestReadFileSvc<T>
+                    {
+                        type Response = super::GuestFileChunk;
+                        type ResponseStream = T::GuestReadFileStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GuestReadFileRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).guest_read_file(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = GuestReadFileSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/GuestWriteFile" => {
+                    #[allow(non_camel_case_types)]
+                    struct GuestWriteFileSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::ClientStreamingService<super::GuestWriteFileRequest>
+                        for GuestWriteFileSvc<T>
+                    {
+                        type Response = super::GuestWriteFileResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::AttachDeviceRequest>,
+                            request: tonic::Request<tonic::Streaming<super::GuestWriteFileRequest>>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).attach_device(request).await };
+                            let fut = async move { (*inner).guest_write_file(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1;
+                        let inner = inner.0;
+                        let method = GuestWriteFileSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/FSFreeze" => {
+                    #[allow(non_camel_case_types)]
+                    struct FSFreezeSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::FsFreezeRequest> for FSFreezeSvc<T> {
+                        type Response = super::FsFreezeResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FsFreezeRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).fs_freeze(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -466,7 +4008,7 @@ pub mod libvirt_api_server {
                     let fut = async move {
                         let interceptor = inner.1.clone();
                         let inner = inner.0;
-                        let method = AttachDeviceSvc(inner);
+                        let method = FSFreezeSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = if let Some(interceptor) = interceptor {
                             tonic::server::Grpc::with_interceptor(codec, interceptor)
@@ -478,18 +4020,18 @@ pub mod libvirt_api_server {
                     };
                     Box::pin(fut)
                 }
-                "/libvirt_api.LibvirtAPI/DetachDevice" => {
+                "/libvirt_api.LibvirtAPI/FSThaw" => {
                     #[allow(non_camel_case_types)]
-                    struct DetachDeviceSvc<T: LibvirtApi>(pub Arc<T>);
-                    impl<T: LibvirtApi> tonic::server::UnaryService<super::DetachDeviceRequest> for DetachDeviceSvc<T> {
-                        type Response = super::SuccessResponse;
+                    struct FSThawSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi> tonic::server::UnaryService<super::FsThawRequest> for FSThawSvc<T> {
+                        type Response = super::FsFreezeResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DetachDeviceRequest>,
+                            request: tonic::Request<super::FsThawRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).detach_device(request).await };
+                            let fut = async move { (*inner).fs_thaw(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -497,7 +4039,42 @@ pub mod libvirt_api_server {
                     let fut = async move {
                         let interceptor = inner.1.clone();
                         let inner = inner.0;
-                        let method = DetachDeviceSvc(inner);
+                        let method = FSThawSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/libvirt_api.LibvirtAPI/CreateQuiescedSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateQuiescedSnapshotSvc<T: LibvirtApi>(pub Arc<T>);
+                    impl<T: LibvirtApi>
+                        tonic::server::UnaryService<super::CreateQuiescedSnapshotRequest>
+                        for CreateQuiescedSnapshotSvc<T>
+                    {
+                        type Response = super::QuiescedSnapshotResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateQuiescedSnapshotRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut =
+                                async move { (*inner).create_quiesced_snapshot(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = CreateQuiescedSnapshotSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = if let Some(interceptor) = interceptor {
                             tonic::server::Grpc::with_interceptor(codec, interceptor)
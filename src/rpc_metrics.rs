@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tonic::transport::NamedService;
+use tower_service::Service;
+
+// Upper bounds, in seconds, of the latency histogram buckets.
+pub const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0];
+
+#[derive(Clone, Default)]
+pub struct MethodStats {
+    pub count: u64,
+    pub sum_seconds: f64,
+    // Non-cumulative per-bucket counts; the last entry is the +Inf bucket.
+    pub buckets: [u64; LATENCY_BUCKETS.len() + 1],
+}
+
+// Request counts and latencies per gRPC method.
+#[derive(Default)]
+pub struct RPCMetrics {
+    methods: Mutex<BTreeMap<String, MethodStats>>,
+}
+
+impl RPCMetrics {
+    pub fn record(&self, method: &str, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|x| seconds <= *x)
+            .unwrap_or(LATENCY_BUCKETS.len());
+
+        let mut methods = self.methods.lock().unwrap();
+        let stats = methods.entry(method.to_string()).or_default();
+        stats.count += 1;
+        stats.sum_seconds += seconds;
+        stats.buckets[bucket] += 1;
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, MethodStats> {
+        self.methods.lock().unwrap().clone()
+    }
+}
+
+// Wraps the generated gRPC service and records every call in RPCMetrics. For streaming RPCs the
+// latency is the time until the stream is opened, not until it ends.
+#[derive(Clone)]
+pub struct MeteredService<S> {
+    inner: S,
+    metrics: Arc<RPCMetrics>,
+}
+
+impl<S> MeteredService<S> {
+    pub fn new(inner: S, metrics: Arc<RPCMetrics>) -> MeteredService<S> {
+        MeteredService { inner, metrics }
+    }
+}
+
+impl<S: NamedService> NamedService for MeteredService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S, B> Service<http::Request<B>> for MeteredService<S>
+where
+    S: Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        // gRPC paths look like "/libvirt_api.LibvirtAPI/ListDomains".
+        let method = request
+            .uri()
+            .path()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let metrics = self.metrics.clone();
+        let started = Instant::now();
+        let response = self.inner.call(request);
+
+        Box::pin(async move {
+            let response = response.await;
+            metrics.record(&method, started.elapsed());
+            response
+        })
+    }
+}
@@ -1 +1,2 @@
+pub mod domain_xml;
 pub mod schema;
\ No newline at end of file
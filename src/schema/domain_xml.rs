@@ -0,0 +1,1230 @@
+// Typed view of libvirt domain XML (https://libvirt.org/formatdomain.html).
+//
+// Only the parts of the document this crate cares about are modelled. Every typed struct keeps
+// the element it was parsed from, and `to_element` writes the typed fields back into a copy of
+// it, so attributes and children that aren't modelled survive a parse/serialize round trip.
+// Through serde only the domain's own element travels, as an XML string next to the typed
+// fields; nested structs get theirs back from it when written, so the same holds for a serde
+// round trip.
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use xmltree::{Element, EmitterConfig, XMLNode};
+
+#[derive(Debug)]
+pub enum DomainXMLError {
+    Parse(String),
+    Write(String),
+    Invalid(String),
+}
+
+impl fmt::Display for DomainXMLError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DomainXMLError::Parse(e) => write!(f, "failed to parse domain XML: {}", e),
+            DomainXMLError::Write(e) => write!(f, "failed to write domain XML: {}", e),
+            DomainXMLError::Invalid(e) => write!(f, "invalid domain XML: {}", e),
+        }
+    }
+}
+
+impl error::Error for DomainXMLError {}
+
+pub trait XMLElement: Sized {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError>;
+    fn to_element(&self) -> Element;
+
+    fn from_xml(xml: &str) -> Result<Self, DomainXMLError> {
+        Self::from_element(&parse_element(xml)?)
+    }
+
+    fn to_xml(&self) -> Result<String, DomainXMLError> {
+        write_element(&self.to_element())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DomainXML {
+    pub domain_type: String,
+    pub name: String,
+    pub uuid: Option<String>,
+    pub memory: Option<Memory>,
+    pub current_memory: Option<Memory>,
+    pub vcpu: Option<VCPU>,
+    pub os: Option<OS>,
+    pub features: Option<Vec<Feature>>,
+    pub devices: Option<Devices>,
+    #[serde(default, with = "raw_element", skip_serializing_if = "Option::is_none")]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Memory {
+    pub unit: Option<String>,
+    pub value: u64,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VCPU {
+    pub placement: Option<String>,
+    pub current: Option<u32>,
+    pub count: u32,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OS {
+    pub os_type: String,
+    pub arch: Option<String>,
+    pub machine: Option<String>,
+    pub loader: Option<String>,
+    pub boot: Vec<String>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Feature {
+    pub name: String,
+    pub state: Option<String>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Devices {
+    pub emulator: Option<String>,
+    pub devices: Vec<Device>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+// Devices keep their document order; anything not modelled is carried through as raw XML.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Device {
+    Disk(Disk),
+    Interface(Interface),
+    HostDev(HostDev),
+    Controller(Controller),
+    Graphics(Graphics),
+    Other { xml: RawElement },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Address {
+    pub address_type: Option<String>,
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Disk {
+    pub disk_type: String,
+    pub device: Option<String>,
+    pub driver_name: Option<String>,
+    pub driver_type: Option<String>,
+    // Source attributes depend on the disk type (file, dev, pool/volume, protocol/name, ...).
+    pub source: Option<BTreeMap<String, String>>,
+    pub target_dev: Option<String>,
+    pub target_bus: Option<String>,
+    pub readonly: bool,
+    pub address: Option<Address>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Interface {
+    pub interface_type: String,
+    pub mac: Option<String>,
+    // Source attributes depend on the interface type (network, bridge, dev, ...).
+    pub source: Option<BTreeMap<String, String>>,
+    pub model: Option<String>,
+    pub target_dev: Option<String>,
+    pub address: Option<Address>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostDev {
+    pub mode: String,
+    pub hostdev_type: String,
+    pub managed: Option<String>,
+    pub source: HostDevSource,
+    pub address: Option<Address>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostDevSource {
+    pub vendor_id: Option<String>,
+    pub product_id: Option<String>,
+    // Host-side address: bus/device for USB, domain/bus/slot/function for PCI.
+    pub address: Option<Address>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Controller {
+    pub controller_type: String,
+    pub index: Option<u32>,
+    pub model: Option<String>,
+    pub address: Option<Address>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Graphics {
+    pub graphics_type: String,
+    // -1 until a running domain with autoport has been assigned one.
+    pub port: Option<i32>,
+    pub autoport: Option<String>,
+    pub tls_port: Option<i32>,
+    pub passwd: Option<String>,
+    // Attributes of the first <listen> (type, address, network, socket). Older XML that only has
+    // a `listen` attribute is read as an address listen.
+    pub listen: Option<BTreeMap<String, String>>,
+    #[serde(skip)]
+    element: Option<Element>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RawElement(pub Element);
+
+impl Serialize for RawElement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let xml = write_element(&self.0).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&xml)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawElement {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let xml = String::deserialize(deserializer)?;
+        let element = parse_element(&xml).map_err(serde::de::Error::custom)?;
+        Ok(RawElement(element))
+    }
+}
+
+// The serde form of a typed struct's source element.
+mod raw_element {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use xmltree::Element;
+
+    use super::{parse_element, write_element};
+
+    pub fn serialize<S: Serializer>(e: &Option<Element>, serializer: S) -> Result<S::Ok, S::Error> {
+        match e {
+            Some(e) => {
+                let xml = write_element(e).map_err(serde::ser::Error::custom)?;
+                serializer.serialize_some(&xml)
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Element>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(xml) => parse_element(&xml)
+                .map(Some)
+                .map_err(serde::de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+impl HostDev {
+    pub fn usb(vendor_id: String, product_id: String) -> HostDev {
+        HostDev {
+            mode: "subsystem".to_string(),
+            hostdev_type: "usb".to_string(),
+            managed: Some("no".to_string()),
+            source: HostDevSource {
+                vendor_id: Some(vendor_id),
+                product_id: Some(product_id),
+                address: None,
+            },
+            address: None,
+            element: None,
+        }
+    }
+}
+
+impl Address {
+    pub fn usb_host(bus: u32, device: u32) -> Address {
+        let mut attributes = BTreeMap::new();
+        attributes.insert("bus".to_string(), bus.to_string());
+        attributes.insert("device".to_string(), device.to_string());
+
+        Address {
+            address_type: None,
+            attributes,
+        }
+    }
+}
+
+impl DomainXML {
+    // Nested structs that came through serde have no source element. This gives them back the
+    // matching child of the domain's: by name for single children and features, and by position
+    // among same-named siblings for devices, as long as none of that kind was added or removed.
+    fn with_nested_elements(&self) -> DomainXML {
+        let mut domain = self.clone();
+        let root = match &self.element {
+            Some(x) => x,
+            None => return domain,
+        };
+
+        if let Some(x) = domain.memory.as_mut() {
+            adopt(&mut x.element, root.get_child("memory"));
+        }
+        if let Some(x) = domain.current_memory.as_mut() {
+            adopt(&mut x.element, root.get_child("currentMemory"));
+        }
+        if let Some(x) = domain.vcpu.as_mut() {
+            adopt(&mut x.element, root.get_child("vcpu"));
+        }
+        if let Some(x) = domain.os.as_mut() {
+            adopt(&mut x.element, root.get_child("os"));
+        }
+        if let (Some(features), Some(source)) =
+            (domain.features.as_mut(), root.get_child("features"))
+        {
+            for feature in features {
+                adopt(
+                    &mut feature.element,
+                    source.get_child(feature.name.as_str()),
+                );
+            }
+        }
+        if let Some(devices) = domain.devices.as_mut() {
+            let source = root.get_child("devices");
+            adopt(&mut devices.element, source);
+
+            let mut sources: BTreeMap<&str, Vec<&Element>> = BTreeMap::new();
+            for e in source.into_iter().flat_map(child_elements) {
+                sources.entry(e.name.as_str()).or_default().push(e);
+            }
+            let mut targets: BTreeMap<&str, Vec<&mut Option<Element>>> = BTreeMap::new();
+            for device in devices.devices.iter_mut() {
+                if let Some((name, element)) = device.element_mut() {
+                    targets.entry(name).or_default().push(element);
+                }
+            }
+
+            for (name, elements) in targets {
+                let sources = sources.get(name).map_or(&[][..], |x| x.as_slice());
+                if sources.len() == elements.len() {
+                    for (element, source) in elements.into_iter().zip(sources) {
+                        adopt(element, Some(source));
+                    }
+                }
+            }
+        }
+
+        domain
+    }
+}
+
+impl XMLElement for DomainXML {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "domain")?;
+
+        Ok(DomainXML {
+            domain_type: require_attr(e, "type")?,
+            name: child_text(e, "name")
+                .ok_or_else(|| DomainXMLError::Invalid("domain has no <name>".to_string()))?,
+            uuid: child_text(e, "uuid"),
+            memory: match e.get_child("memory") {
+                Some(x) => Some(Memory::from_element(x)?),
+                None => None,
+            },
+            current_memory: match e.get_child("currentMemory") {
+                Some(x) => Some(Memory::from_element(x)?),
+                None => None,
+            },
+            vcpu: match e.get_child("vcpu") {
+                Some(x) => Some(VCPU::from_element(x)?),
+                None => None,
+            },
+            os: match e.get_child("os") {
+                Some(x) => Some(OS::from_element(x)?),
+                None => None,
+            },
+            features: match e.get_child("features") {
+                Some(x) => Some(
+                    child_elements(x)
+                        .map(Feature::from_element)
+                        .collect::<Result<Vec<_>, _>>()?,
+                ),
+                None => None,
+            },
+            devices: match e.get_child("devices") {
+                Some(x) => Some(Devices::from_element(x)?),
+                None => None,
+            },
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let domain = self.with_nested_elements();
+        let mut e = base_element(&domain.element, "domain");
+
+        e.attributes
+            .insert("type".to_string(), domain.domain_type.clone());
+        set_child(&mut e, "name", Some(text_element("name", &domain.name)));
+        set_child(
+            &mut e,
+            "uuid",
+            domain.uuid.as_ref().map(|x| text_element("uuid", x)),
+        );
+        set_child(
+            &mut e,
+            "memory",
+            domain.memory.as_ref().map(|x| x.to_named_element("memory")),
+        );
+        set_child(
+            &mut e,
+            "currentMemory",
+            domain
+                .current_memory
+                .as_ref()
+                .map(|x| x.to_named_element("currentMemory")),
+        );
+        set_child(&mut e, "vcpu", domain.vcpu.as_ref().map(|x| x.to_element()));
+        set_child(&mut e, "os", domain.os.as_ref().map(|x| x.to_element()));
+        set_child(
+            &mut e,
+            "features",
+            domain.features.as_ref().map(|x| {
+                let mut features = Element::new("features");
+                for feature in x {
+                    features
+                        .children
+                        .push(XMLNode::Element(feature.to_element()));
+                }
+                features
+            }),
+        );
+        set_child(
+            &mut e,
+            "devices",
+            domain.devices.as_ref().map(|x| x.to_element()),
+        );
+
+        e
+    }
+}
+
+impl Memory {
+    pub fn to_named_element(&self, name: &str) -> Element {
+        let mut e = base_element(&self.element, name);
+        e.name = name.to_string();
+
+        set_attr(&mut e, "unit", &self.unit);
+        set_text(&mut e, self.value.to_string());
+
+        e
+    }
+}
+
+impl XMLElement for Memory {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        Ok(Memory {
+            unit: attr(e, "unit"),
+            value: parse_text(e)?,
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        self.to_named_element("memory")
+    }
+}
+
+impl XMLElement for VCPU {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "vcpu")?;
+
+        Ok(VCPU {
+            placement: attr(e, "placement"),
+            current: match attr(e, "current") {
+                Some(x) => Some(parse_value(&x, "vcpu current")?),
+                None => None,
+            },
+            count: parse_text(e)?,
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "vcpu");
+
+        set_attr(&mut e, "placement", &self.placement);
+        set_attr(&mut e, "current", &self.current.map(|x| x.to_string()));
+        set_text(&mut e, self.count.to_string());
+
+        e
+    }
+}
+
+impl XMLElement for OS {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "os")?;
+
+        let os_type = e
+            .get_child("type")
+            .ok_or_else(|| DomainXMLError::Invalid("os has no <type>".to_string()))?;
+
+        Ok(OS {
+            os_type: text(os_type).unwrap_or_default(),
+            arch: attr(os_type, "arch"),
+            machine: attr(os_type, "machine"),
+            loader: child_text(e, "loader"),
+            boot: child_elements(e)
+                .filter(|x| x.name == "boot")
+                .filter_map(|x| attr(x, "dev"))
+                .collect(),
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "os");
+
+        update_child(&mut e, "type", |x| {
+            set_attr(x, "arch", &self.arch);
+            set_attr(x, "machine", &self.machine);
+            set_text(x, self.os_type.clone());
+        });
+        match &self.loader {
+            Some(loader) => update_child(&mut e, "loader", |x| set_text(x, loader.clone())),
+            None => set_child(&mut e, "loader", None),
+        }
+        set_children(
+            &mut e,
+            "boot",
+            self.boot
+                .iter()
+                .map(|dev| {
+                    let mut boot = Element::new("boot");
+                    boot.attributes.insert("dev".to_string(), dev.clone());
+                    boot
+                })
+                .collect(),
+        );
+
+        e
+    }
+}
+
+impl XMLElement for Feature {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        Ok(Feature {
+            name: e.name.clone(),
+            state: attr(e, "state"),
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, &self.name);
+        e.name = self.name.clone();
+
+        set_attr(&mut e, "state", &self.state);
+
+        e
+    }
+}
+
+impl XMLElement for Devices {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "devices")?;
+
+        Ok(Devices {
+            emulator: child_text(e, "emulator"),
+            devices: child_elements(e)
+                .filter(|x| x.name != "emulator")
+                .map(Device::from_element)
+                .collect::<Result<Vec<_>, _>>()?,
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "devices");
+
+        let emulator = self.emulator.as_ref().map(|x| text_element("emulator", x));
+        e.children = emulator
+            .into_iter()
+            .chain(self.devices.iter().map(|x| x.to_element()))
+            .map(XMLNode::Element)
+            .collect();
+
+        e
+    }
+}
+
+impl Device {
+    // The source element of a modelled device, with the element name it is parsed from.
+    fn element_mut(&mut self) -> Option<(&'static str, &mut Option<Element>)> {
+        match self {
+            Device::Disk(x) => Some(("disk", &mut x.element)),
+            Device::Interface(x) => Some(("interface", &mut x.element)),
+            Device::HostDev(x) => Some(("hostdev", &mut x.element)),
+            Device::Controller(x) => Some(("controller", &mut x.element)),
+            Device::Graphics(x) => Some(("graphics", &mut x.element)),
+            Device::Other { .. } => None,
+        }
+    }
+}
+
+impl XMLElement for Device {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        Ok(match e.name.as_str() {
+            "disk" => Device::Disk(Disk::from_element(e)?),
+            "interface" => Device::Interface(Interface::from_element(e)?),
+            "hostdev" => Device::HostDev(HostDev::from_element(e)?),
+            "controller" => Device::Controller(Controller::from_element(e)?),
+            "graphics" => Device::Graphics(Graphics::from_element(e)?),
+            _ => Device::Other {
+                xml: RawElement(e.clone()),
+            },
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        match self {
+            Device::Disk(x) => x.to_element(),
+            Device::Interface(x) => x.to_element(),
+            Device::HostDev(x) => x.to_element(),
+            Device::Controller(x) => x.to_element(),
+            Device::Graphics(x) => x.to_element(),
+            Device::Other { xml } => xml.0.clone(),
+        }
+    }
+}
+
+impl XMLElement for Address {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "address")?;
+
+        Ok(Address {
+            address_type: attr(e, "type"),
+            attributes: e
+                .attributes
+                .iter()
+                .filter(|(k, _)| k.as_str() != "type")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = Element::new("address");
+
+        set_attr(&mut e, "type", &self.address_type);
+        for (k, v) in &self.attributes {
+            e.attributes.insert(k.clone(), v.clone());
+        }
+
+        e
+    }
+}
+
+impl XMLElement for Disk {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "disk")?;
+
+        let driver = e.get_child("driver");
+        let target = e.get_child("target");
+
+        Ok(Disk {
+            disk_type: require_attr(e, "type")?,
+            device: attr(e, "device"),
+            driver_name: driver.and_then(|x| attr(x, "name")),
+            driver_type: driver.and_then(|x| attr(x, "type")),
+            source: e.get_child("source").map(attr_map),
+            target_dev: target.and_then(|x| attr(x, "dev")),
+            target_bus: target.and_then(|x| attr(x, "bus")),
+            readonly: e.get_child("readonly").is_some(),
+            address: match e.get_child("address") {
+                Some(x) => Some(Address::from_element(x)?),
+                None => None,
+            },
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "disk");
+
+        e.attributes
+            .insert("type".to_string(), self.disk_type.clone());
+        set_attr(&mut e, "device", &self.device);
+
+        if self.driver_name.is_some() || self.driver_type.is_some() {
+            update_child(&mut e, "driver", |x| {
+                set_attr(x, "name", &self.driver_name);
+                set_attr(x, "type", &self.driver_type);
+            });
+        } else {
+            set_child(&mut e, "driver", None);
+        }
+        match &self.source {
+            Some(source) => update_child(&mut e, "source", |x| replace_attrs(x, source)),
+            None => set_child(&mut e, "source", None),
+        }
+        if self.target_dev.is_some() || self.target_bus.is_some() {
+            update_child(&mut e, "target", |x| {
+                set_attr(x, "dev", &self.target_dev);
+                set_attr(x, "bus", &self.target_bus);
+            });
+        } else {
+            set_child(&mut e, "target", None);
+        }
+        match (self.readonly, e.get_child("readonly").is_some()) {
+            (true, false) => e.children.push(XMLNode::Element(Element::new("readonly"))),
+            (false, true) => set_child(&mut e, "readonly", None),
+            _ => {}
+        }
+        set_child(
+            &mut e,
+            "address",
+            self.address.as_ref().map(|x| x.to_element()),
+        );
+
+        e
+    }
+}
+
+impl XMLElement for Interface {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "interface")?;
+
+        Ok(Interface {
+            interface_type: require_attr(e, "type")?,
+            mac: e.get_child("mac").and_then(|x| attr(x, "address")),
+            source: e.get_child("source").map(attr_map),
+            model: e.get_child("model").and_then(|x| attr(x, "type")),
+            target_dev: e.get_child("target").and_then(|x| attr(x, "dev")),
+            address: match e.get_child("address") {
+                Some(x) => Some(Address::from_element(x)?),
+                None => None,
+            },
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "interface");
+
+        e.attributes
+            .insert("type".to_string(), self.interface_type.clone());
+        match &self.mac {
+            Some(mac) => update_child(&mut e, "mac", |x| {
+                x.attributes.insert("address".to_string(), mac.clone());
+            }),
+            None => set_child(&mut e, "mac", None),
+        }
+        match &self.source {
+            Some(source) => update_child(&mut e, "source", |x| replace_attrs(x, source)),
+            None => set_child(&mut e, "source", None),
+        }
+        match &self.model {
+            Some(model) => update_child(&mut e, "model", |x| {
+                x.attributes.insert("type".to_string(), model.clone());
+            }),
+            None => set_child(&mut e, "model", None),
+        }
+        match &self.target_dev {
+            Some(dev) => update_child(&mut e, "target", |x| {
+                x.attributes.insert("dev".to_string(), dev.clone());
+            }),
+            None => set_child(&mut e, "target", None),
+        }
+        set_child(
+            &mut e,
+            "address",
+            self.address.as_ref().map(|x| x.to_element()),
+        );
+
+        e
+    }
+}
+
+impl XMLElement for HostDev {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "hostdev")?;
+
+        let source = e.get_child("source");
+
+        Ok(HostDev {
+            mode: attr(e, "mode").unwrap_or_else(|| "subsystem".to_string()),
+            hostdev_type: require_attr(e, "type")?,
+            managed: attr(e, "managed"),
+            source: HostDevSource {
+                vendor_id: source
+                    .and_then(|x| x.get_child("vendor"))
+                    .and_then(|x| attr(x, "id")),
+                product_id: source
+                    .and_then(|x| x.get_child("product"))
+                    .and_then(|x| attr(x, "id")),
+                address: match source.and_then(|x| x.get_child("address")) {
+                    Some(x) => Some(Address::from_element(x)?),
+                    None => None,
+                },
+            },
+            address: match e.get_child("address") {
+                Some(x) => Some(Address::from_element(x)?),
+                None => None,
+            },
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "hostdev");
+
+        e.attributes.insert("mode".to_string(), self.mode.clone());
+        e.attributes
+            .insert("type".to_string(), self.hostdev_type.clone());
+        set_attr(&mut e, "managed", &self.managed);
+
+        update_child(&mut e, "source", |source| {
+            match &self.source.vendor_id {
+                Some(id) => update_child(source, "vendor", |x| {
+                    x.attributes.insert("id".to_string(), id.clone());
+                }),
+                None => set_child(source, "vendor", None),
+            }
+            match &self.source.product_id {
+                Some(id) => update_child(source, "product", |x| {
+                    x.attributes.insert("id".to_string(), id.clone());
+                }),
+                None => set_child(source, "product", None),
+            }
+            set_child(
+                source,
+                "address",
+                self.source.address.as_ref().map(|x| x.to_element()),
+            );
+        });
+        set_child(
+            &mut e,
+            "address",
+            self.address.as_ref().map(|x| x.to_element()),
+        );
+
+        e
+    }
+}
+
+impl XMLElement for Controller {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "controller")?;
+
+        Ok(Controller {
+            controller_type: require_attr(e, "type")?,
+            index: match attr(e, "index") {
+                Some(x) => Some(parse_value(&x, "controller index")?),
+                None => None,
+            },
+            model: attr(e, "model"),
+            address: match e.get_child("address") {
+                Some(x) => Some(Address::from_element(x)?),
+                None => None,
+            },
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "controller");
+
+        e.attributes
+            .insert("type".to_string(), self.controller_type.clone());
+        set_attr(&mut e, "index", &self.index.map(|x| x.to_string()));
+        set_attr(&mut e, "model", &self.model);
+        set_child(
+            &mut e,
+            "address",
+            self.address.as_ref().map(|x| x.to_element()),
+        );
+
+        e
+    }
+}
+
+impl XMLElement for Graphics {
+    fn from_element(e: &Element) -> Result<Self, DomainXMLError> {
+        expect_name(e, "graphics")?;
+
+        let listen = match (e.get_child("listen"), attr(e, "listen")) {
+            (Some(x), _) => Some(attr_map(x)),
+            (None, Some(address)) => {
+                let mut attrs = BTreeMap::new();
+                attrs.insert("type".to_string(), "address".to_string());
+                attrs.insert("address".to_string(), address);
+                Some(attrs)
+            }
+            (None, None) => None,
+        };
+
+        Ok(Graphics {
+            graphics_type: require_attr(e, "type")?,
+            port: match attr(e, "port") {
+                Some(x) => Some(parse_value(&x, "graphics port")?),
+                None => None,
+            },
+            autoport: attr(e, "autoport"),
+            tls_port: match attr(e, "tlsPort") {
+                Some(x) => Some(parse_value(&x, "graphics TLS port")?),
+                None => None,
+            },
+            passwd: attr(e, "passwd"),
+            listen,
+            element: Some(e.clone()),
+        })
+    }
+
+    fn to_element(&self) -> Element {
+        let mut e = base_element(&self.element, "graphics");
+
+        e.attributes
+            .insert("type".to_string(), self.graphics_type.clone());
+        set_attr(&mut e, "port", &self.port.map(|x| x.to_string()));
+        set_attr(&mut e, "autoport", &self.autoport);
+        set_attr(&mut e, "tlsPort", &self.tls_port.map(|x| x.to_string()));
+        set_attr(&mut e, "passwd", &self.passwd);
+
+        // libvirt rejects a `listen` attribute that disagrees with the first <listen>.
+        let address = self
+            .listen
+            .as_ref()
+            .filter(|x| x.get("type").map(|x| x.as_str()) == Some("address"))
+            .and_then(|x| x.get("address").cloned());
+        set_attr(&mut e, "listen", &address);
+        match &self.listen {
+            Some(listen) => update_child(&mut e, "listen", |x| replace_attrs(x, listen)),
+            None => set_child(&mut e, "listen", None),
+        }
+
+        e
+    }
+}
+
+pub fn parse_element(xml: &str) -> Result<Element, DomainXMLError> {
+    Element::parse(xml.as_bytes()).map_err(|e| DomainXMLError::Parse(e.to_string()))
+}
+
+pub fn write_element(e: &Element) -> Result<String, DomainXMLError> {
+    let config = EmitterConfig::new()
+        .perform_indent(true)
+        .write_document_declaration(false);
+
+    let mut buf: Vec<u8> = Vec::new();
+    e.write_with_config(&mut buf, config)
+        .map_err(|e| DomainXMLError::Write(e.to_string()))?;
+
+    String::from_utf8(buf).map_err(|e| DomainXMLError::Write(e.to_string()))
+}
+
+fn expect_name(e: &Element, name: &str) -> Result<(), DomainXMLError> {
+    match e.name == name {
+        true => Ok(()),
+        false => Err(DomainXMLError::Invalid(format!(
+            "expected <{}>, found <{}>",
+            name, e.name
+        ))),
+    }
+}
+
+fn base_element(element: &Option<Element>, name: &str) -> Element {
+    element.clone().unwrap_or_else(|| Element::new(name))
+}
+
+// Sets a typed struct's source element unless it already has one.
+fn adopt(element: &mut Option<Element>, source: Option<&Element>) {
+    if element.is_none() {
+        *element = source.cloned();
+    }
+}
+
+fn child_elements(e: &Element) -> impl Iterator<Item = &Element> {
+    e.children.iter().filter_map(|x| x.as_element())
+}
+
+fn attr(e: &Element, name: &str) -> Option<String> {
+    e.attributes.get(name).cloned()
+}
+
+fn attr_map(e: &Element) -> BTreeMap<String, String> {
+    e.attributes
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+fn require_attr(e: &Element, name: &str) -> Result<String, DomainXMLError> {
+    attr(e, name)
+        .ok_or_else(|| DomainXMLError::Invalid(format!("<{}> has no '{}' attribute", e.name, name)))
+}
+
+fn set_attr(e: &mut Element, name: &str, value: &Option<String>) {
+    match value {
+        Some(x) => {
+            e.attributes.insert(name.to_string(), x.clone());
+        }
+        None => {
+            e.attributes.remove(name);
+        }
+    }
+}
+
+fn replace_attrs(e: &mut Element, attrs: &BTreeMap<String, String>) {
+    e.attributes.clear();
+    for (k, v) in attrs {
+        e.attributes.insert(k.clone(), v.clone());
+    }
+}
+
+fn text(e: &Element) -> Option<String> {
+    e.get_text().map(|x| x.trim().to_string())
+}
+
+fn child_text(e: &Element, name: &str) -> Option<String> {
+    e.get_child(name).and_then(text)
+}
+
+fn parse_value<T: FromStr>(value: &str, what: &str) -> Result<T, DomainXMLError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| DomainXMLError::Invalid(format!("invalid {} '{}'", what, value)))
+}
+
+fn parse_text<T: FromStr>(e: &Element) -> Result<T, DomainXMLError> {
+    parse_value(&text(e).unwrap_or_default(), &format!("<{}> value", e.name))
+}
+
+fn set_text(e: &mut Element, value: String) {
+    e.children.retain(|x| !matches!(x, XMLNode::Text(_)));
+    e.children.insert(0, XMLNode::Text(value));
+}
+
+fn text_element(name: &str, value: &str) -> Element {
+    let mut e = Element::new(name);
+    e.children.push(XMLNode::Text(value.to_string()));
+    e
+}
+
+fn child_position(e: &Element, name: &str) -> Option<usize> {
+    e.children
+        .iter()
+        .position(|x| matches!(x, XMLNode::Element(c) if c.name == name))
+}
+
+// Replaces the first child called `name` in place, appends it if there is none, or removes it
+// when `child` is None.
+fn set_child(e: &mut Element, name: &str, child: Option<Element>) {
+    match (child_position(e, name), child) {
+        (Some(i), Some(c)) => e.children[i] = XMLNode::Element(c),
+        (None, Some(c)) => e.children.push(XMLNode::Element(c)),
+        (Some(i), None) => {
+            e.children.remove(i);
+        }
+        (None, None) => {}
+    }
+}
+
+// Replaces every child called `name` with `children`, keeping the position of the first one.
+fn set_children(e: &mut Element, name: &str, children: Vec<Element>) {
+    let i = child_position(e, name).unwrap_or(e.children.len());
+    e.children
+        .retain(|x| !matches!(x, XMLNode::Element(c) if c.name == name));
+
+    let i = i.min(e.children.len());
+    for (offset, child) in children.into_iter().enumerate() {
+        e.children.insert(i + offset, XMLNode::Element(child));
+    }
+}
+
+// Edits the first child called `name`, creating it first if needed.
+fn update_child<F: FnOnce(&mut Element)>(e: &mut Element, name: &str, f: F) {
+    if e.get_child(name).is_none() {
+        e.children.push(XMLNode::Element(Element::new(name)));
+    }
+    f(e.get_mut_child(name).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `virsh dumpxml` of a running domain, with plenty that the model doesn't cover.
+    const DOMAIN_XML: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/domain.xml"
+    ));
+
+    fn device_kinds(domain: &DomainXML) -> Vec<&'static str> {
+        domain
+            .devices
+            .as_ref()
+            .unwrap()
+            .devices
+            .iter()
+            .map(|x| match x {
+                Device::Disk(_) => "disk",
+                Device::Interface(_) => "interface",
+                Device::HostDev(_) => "hostdev",
+                Device::Controller(_) => "controller",
+                Device::Graphics(_) => "graphics",
+                Device::Other { .. } => "other",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_modelled_fields() {
+        let domain = DomainXML::from_xml(DOMAIN_XML).unwrap();
+
+        assert_eq!(domain.domain_type, "kvm");
+        assert_eq!(domain.name, "vm-i686");
+        assert_eq!(
+            domain.uuid.as_deref(),
+            Some("6c8fb1b6-3a8e-4d5a-9d4f-2e7c0b3f9a41")
+        );
+        assert_eq!(domain.memory.as_ref().unwrap().value, 2097152);
+        assert_eq!(domain.vcpu.as_ref().unwrap().current, Some(1));
+        assert_eq!(domain.os.as_ref().unwrap().boot, vec!["hd", "cdrom"]);
+        assert_eq!(
+            device_kinds(&domain),
+            vec![
+                "disk",
+                "disk",
+                "controller",
+                "controller",
+                "interface",
+                "other",
+                "other",
+                "other",
+                "graphics",
+                "other",
+                "hostdev",
+                "other",
+            ]
+        );
+    }
+
+    #[test]
+    fn xml_round_trip_is_lossless() {
+        let domain = DomainXML::from_xml(DOMAIN_XML).unwrap();
+
+        assert_eq!(
+            parse_element(&domain.to_xml().unwrap()).unwrap(),
+            parse_element(DOMAIN_XML).unwrap()
+        );
+    }
+
+    #[test]
+    fn serde_round_trip_is_lossless() {
+        let domain = DomainXML::from_xml(DOMAIN_XML).unwrap();
+
+        let json = serde_json::to_string(&domain).unwrap();
+        let domain: DomainXML = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            parse_element(&domain.to_xml().unwrap()).unwrap(),
+            parse_element(DOMAIN_XML).unwrap()
+        );
+    }
+
+    #[test]
+    fn serde_round_trip_keeps_edits_and_unknown_elements() {
+        let domain = DomainXML::from_xml(DOMAIN_XML).unwrap();
+
+        let mut json: serde_json::Value = serde_json::to_value(&domain).unwrap();
+        json["name"] = "renamed".into();
+        json["vcpu"]["count"] = 4.into();
+        let domain: DomainXML = serde_json::from_value(json).unwrap();
+
+        let e = parse_element(&domain.to_xml().unwrap()).unwrap();
+        assert_eq!(e.get_child("name").unwrap().get_text().unwrap(), "renamed");
+        assert_eq!(e.get_child("vcpu").unwrap().get_text().unwrap(), "4");
+        assert_eq!(e.get_child("vcpu").unwrap().attributes["current"], "1");
+        assert!(e.get_child("clock").is_some());
+        assert!(e.get_child("seclabel").is_some());
+        assert_eq!(
+            e.get_child("devices")
+                .unwrap()
+                .get_child("disk")
+                .unwrap()
+                .get_child("alias")
+                .unwrap()
+                .attributes["name"],
+            "virtio-disk0"
+        );
+    }
+
+    #[test]
+    fn serde_carries_only_the_domain_element() {
+        let domain = DomainXML::from_xml(DOMAIN_XML).unwrap();
+
+        let json = serde_json::to_value(&domain).unwrap();
+        assert!(json.get("element").is_some());
+        assert!(json["vcpu"].get("element").is_none());
+        assert!(json["devices"].get("element").is_none());
+        assert!(json["devices"]["devices"][0].get("element").is_none());
+    }
+
+    // With a disk gone, the other one can't be told apart by position, so it only keeps its
+    // typed fields instead of picking up the removed disk's alias.
+    #[test]
+    fn serde_round_trip_does_not_mix_up_devices() {
+        let domain = DomainXML::from_xml(DOMAIN_XML).unwrap();
+
+        let mut json: serde_json::Value = serde_json::to_value(&domain).unwrap();
+        json["devices"]["devices"].as_array_mut().unwrap().remove(0);
+        let domain: DomainXML = serde_json::from_value(json).unwrap();
+
+        let e = parse_element(&domain.to_xml().unwrap()).unwrap();
+        let devices = e.get_child("devices").unwrap();
+        let disk = devices.get_child("disk").unwrap();
+        assert_eq!(disk.attributes["device"], "cdrom");
+        assert!(disk.get_child("alias").is_none());
+        assert_eq!(
+            devices
+                .get_child("interface")
+                .unwrap()
+                .get_child("alias")
+                .unwrap()
+                .attributes["name"],
+            "net0"
+        );
+    }
+
+    #[test]
+    fn typed_structs_without_source_element_serialize_without_it() {
+        let hostdev = HostDev::usb("046d".to_string(), "c52b".to_string());
+
+        let json = serde_json::to_value(&hostdev).unwrap();
+        assert!(json.get("element").is_none());
+
+        let hostdev: HostDev = serde_json::from_value(json).unwrap();
+        assert!(hostdev.element.is_none());
+    }
+}
@@ -15,9 +15,11 @@ pub struct Domain {
     pub memory_max: u64,
     pub virt_cpu_num: u32,
     pub virt_cpu_time: u64,
+    pub has_managed_save: bool,
 }
 
 #[repr(i32)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum DomainState {
     Unspecified = 0,
     NoState = 1,
@@ -36,6 +38,228 @@ impl fmt::Debug for DomainState {
     }
 }
 
+#[repr(i32)]
+#[derive(Debug, Clone, Copy)]
+pub enum ShutdownMode {
+    Default = 0,
+    ACPIPowerButton = 1,
+    GuestAgent = 2,
+    Initctl = 4,
+    Signal = 8,
+    Paravirt = 16,
+}
+
+#[derive(Debug)]
+pub struct WaitForState {
+    pub state: DomainState,
+    pub timeout: std::time::Duration,
+}
+
+#[derive(Debug)]
+pub struct WaitResult {
+    pub state: DomainState,
+    pub elapsed: std::time::Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct UndefineOptions {
+    pub managed_save: bool,
+    pub snapshots_metadata: bool,
+    pub nvram: bool,
+}
+
+#[derive(Debug)]
+pub struct DomainXMLUpdate {
+    pub xml_before: String,
+    pub xml_after: String,
+}
+
+#[derive(Debug, Default)]
+pub struct SnapshotOptions {
+    // A full <domainsnapshot> document; name and description are ignored when it is set.
+    pub xml: Option<String>,
+    pub name: String,
+    pub description: String,
+    pub disk_only: bool,
+    pub quiesce: bool,
+    pub atomic: bool,
+}
+
+// A disk-only snapshot taken with the guest's filesystems frozen through the guest agent.
+#[derive(Debug, Default)]
+pub struct QuiescedSnapshotOptions {
+    // As in SnapshotOptions.
+    pub xml: Option<String>,
+    pub name: String,
+    pub description: String,
+    pub atomic: bool,
+    // Guest mountpoints to freeze; all of them when empty.
+    pub mountpoints: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct SnapshotRevertOptions {
+    pub running: bool,
+    pub paused: bool,
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InterfaceAddressSource {
+    Lease,
+    Agent,
+    ARP,
+}
+
+#[derive(Debug)]
+pub struct InterfaceAddress {
+    pub address: String,
+    pub prefix: u32,
+    pub ipv6: bool,
+}
+
+#[derive(Debug)]
+pub struct GuestInterface {
+    pub name: String,
+    pub mac: Option<String>,
+    pub addresses: Vec<InterfaceAddress>,
+}
+
+#[derive(Debug, Default)]
+pub struct GuestCommand {
+    pub path: String,
+    pub args: Vec<String>,
+    // "NAME=value" pairs.
+    pub env: Vec<String>,
+    pub stdin: Vec<u8>,
+    pub timeout: Option<std::time::Duration>,
+}
+
+#[derive(Debug)]
+pub enum GuestExecOutput {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+    Exit {
+        exit_code: Option<i32>,
+        signal: Option<i32>,
+        timed_out: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct GraphicsDevice {
+    pub index: u32,
+    pub graphics_type: String,
+    pub listen_type: Option<String>,
+    pub listen_address: Option<String>,
+    pub listen_socket: Option<String>,
+    pub port: Option<u32>,
+    pub autoport: bool,
+    pub tls_port: Option<u32>,
+    pub tls: bool,
+    pub password_set: bool,
+}
+
+#[derive(Debug)]
+pub struct GraphicsToken {
+    pub token: String,
+    pub expires_in: std::time::Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct SaveOptions {
+    pub bypass_cache: bool,
+    pub running: bool,
+    pub paused: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct SnapshotDeleteOptions {
+    pub children: bool,
+    pub children_only: bool,
+    pub metadata_only: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub name: String,
+    pub description: String,
+    pub parent: Option<String>,
+    pub children: Vec<String>,
+    pub state: String,
+    pub creation_time: std::time::SystemTime,
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DomainLifecycleEventType {
+    Unknown,
+    Defined,
+    Undefined,
+    Started,
+    Suspended,
+    Resumed,
+    Stopped,
+    Shutdown,
+    PMSuspended,
+    Crashed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WatchdogAction {
+    None,
+    Pause,
+    Reset,
+    PowerOff,
+    Shutdown,
+    Debug,
+    InjectNMI,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IOErrorAction {
+    None,
+    Pause,
+    Report,
+}
+
+#[derive(Debug, Clone)]
+pub enum DomainEventKind {
+    // `detail` is libvirt's per-type detail code, e.g. virDomainEventStoppedDetailType.
+    Lifecycle {
+        event: DomainLifecycleEventType,
+        detail: i32,
+    },
+    Reboot,
+    DeviceAdded {
+        alias: String,
+    },
+    DeviceRemoved {
+        alias: String,
+    },
+    Watchdog {
+        action: WatchdogAction,
+    },
+    IOError {
+        src_path: String,
+        dev_alias: String,
+        action: IOErrorAction,
+    },
+    AgentLifecycle {
+        connected: bool,
+    },
+    // Sent by a newer server than this client knows about.
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct DomainEvent {
+    pub uuid: Uuid,
+    pub name: String,
+    pub timestamp: std::time::SystemTime,
+    pub kind: DomainEventKind,
+}
+
 pub struct USBDevice {
     pub device: String,
     pub vendor_id: String,
@@ -43,6 +267,56 @@ pub struct USBDevice {
     pub model: String,
     pub vendor_name: Option<String>,
     pub model_name: Option<String>,
+    pub bus: u32,
+    pub device_number: u32,
+    pub port_path: String,
+    pub serial: Option<String>,
+    pub attached_to: Option<Uuid>,
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy)]
+pub enum DeviceScope {
+    Unspecified = 0,
+    Current = 1,
+    Live = 2,
+    Config = 3,
+    Both = 4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum USBDeviceAction {
+    // Part of the initial inventory sent before any hotplug events.
+    Present,
+    Add,
+    Change,
+    Remove,
+}
+
+#[derive(Debug, Clone)]
+pub enum USBDeviceSelector {
+    VendorProduct {
+        vendor_id: String,
+        product_id: String,
+    },
+    BusAddress {
+        bus: u32,
+        device: u32,
+    },
+    PortPath(String),
+    Serial(String),
+}
+
+// Devices matching every set criterion are attached to `domain_uuid` while it runs. `id` is
+// ignored on create.
+#[derive(Debug, Clone)]
+pub struct USBRule {
+    pub id: Uuid,
+    pub domain_uuid: Uuid,
+    pub vendor_id: Option<String>,
+    pub product_id: Option<String>,
+    pub serial: Option<String>,
+    pub port_path: Option<String>,
 }
 
 impl fmt::Display for USBDevice {
@@ -0,0 +1,180 @@
+use png::{BitDepth, ColorType, Decoder, Encoder};
+use virt::domain::Domain;
+
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::virt_sys::VirStream;
+
+pub struct Screenshot {
+    pub png: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Splits the next whitespace-separated token off a PPM header, skipping "#" comments.
+fn header_token<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    loop {
+        let start = data
+            .iter()
+            .position(|x| !x.is_ascii_whitespace())
+            .ok_or("truncated PPM header")?;
+        *data = &data[start..];
+
+        if data[0] != b'#' {
+            break;
+        }
+        let end = data.iter().position(|x| *x == b'\n').unwrap_or(data.len());
+        *data = &data[end..];
+    }
+
+    let end = data
+        .iter()
+        .position(|x| x.is_ascii_whitespace())
+        .ok_or("truncated PPM header")?;
+    let token = &data[..end];
+    // Exactly one whitespace byte separates the header from the pixels.
+    *data = &data[end + 1..];
+
+    Ok(token)
+}
+
+fn header_number(data: &mut &[u8]) -> Result<u32, String> {
+    std::str::from_utf8(header_token(data)?)
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .ok_or_else(|| "invalid number in PPM header".to_string())
+}
+
+// Decodes a binary (P6) PPM, which is what QEMU produces, into 8-bit RGB.
+fn decode_ppm(mut data: &[u8]) -> Result<(Vec<u8>, u32, u32), String> {
+    if header_token(&mut data)? != b"P6" {
+        return Err("not a binary PPM image".to_string());
+    }
+
+    let width = header_number(&mut data)?;
+    let height = header_number(&mut data)?;
+    let max = header_number(&mut data)?;
+    if max == 0 || max > 65535 {
+        return Err(format!("invalid PPM maximum value {}", max));
+    }
+
+    let samples = width as usize * height as usize * 3;
+    let bytes_per_sample = if max < 256 { 1 } else { 2 };
+    if data.len() < samples * bytes_per_sample {
+        return Err("truncated PPM image".to_string());
+    }
+
+    let rgb = match (bytes_per_sample, max) {
+        (1, 255) => data[..samples].to_vec(),
+        (1, _) => data[..samples]
+            .iter()
+            .map(|x| (*x as u32 * 255 / max) as u8)
+            .collect(),
+        _ => data[..samples * 2]
+            .chunks(2)
+            .map(|x| ((x[0] as u32) << 8 | x[1] as u32) * 255 / max)
+            .map(|x| x as u8)
+            .collect(),
+    };
+
+    Ok((rgb, width, height))
+}
+
+fn encode_png(rgb: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut png = vec![];
+
+    {
+        let mut encoder = Encoder::new(&mut png, width, height);
+        encoder.set_color(ColorType::RGB);
+        encoder.set_depth(BitDepth::Eight);
+
+        encoder
+            .write_header()
+            .and_then(|mut x| x.write_image_data(rgb))
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(png)
+}
+
+// Grabs a screen of a running domain as PNG. Hypervisors that already return PNG are passed
+// through; PPM is converted. The connection is only locked while the capture is started, not
+// while the image is transferred.
+pub fn take_screenshot(
+    conn: &ThreadSafeVirtConn,
+    domain: &Domain,
+    screen: u32,
+) -> Result<Screenshot, String> {
+    let (stream, mime_type) = VirStream::screenshot(&conn.lock(), domain, screen)?;
+    let data = stream.recv_all()?;
+
+    match mime_type.as_str() {
+        "image/png" => {
+            let (info, _) = Decoder::new(data.as_slice())
+                .read_info()
+                .map_err(|e| e.to_string())?;
+
+            Ok(Screenshot {
+                width: info.width,
+                height: info.height,
+                png: data,
+            })
+        }
+        "image/x-portable-pixmap" => {
+            let (rgb, width, height) = decode_ppm(&data)?;
+
+            Ok(Screenshot {
+                png: encode_png(&rgb, width, height)?,
+                width,
+                height,
+            })
+        }
+        x => Err(format!("unsupported screenshot format '{}'", x)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_ppm_reads_8_bit_images() {
+        let mut data = b"P6\n# written by QEMU\n2 1\n255\n".to_vec();
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(decode_ppm(&data).unwrap(), (vec![1, 2, 3, 4, 5, 6], 2, 1));
+    }
+
+    #[test]
+    fn decode_ppm_scales_to_8_bits() {
+        let mut data = b"P6 1 1 15 ".to_vec();
+        data.extend_from_slice(&[0, 15, 5]);
+        assert_eq!(decode_ppm(&data).unwrap(), (vec![0, 255, 85], 1, 1));
+
+        let mut data = b"P6 1 1 65535 ".to_vec();
+        data.extend_from_slice(&[0, 0, 0xff, 0xff, 0x80, 0x80]);
+        assert_eq!(decode_ppm(&data).unwrap(), (vec![0, 255, 128], 1, 1));
+    }
+
+    #[test]
+    fn decode_ppm_rejects_bad_images() {
+        assert!(decode_ppm(b"P3 1 1 255 1 2 3").is_err());
+        assert!(decode_ppm(b"P6 1 1 0 \x01\x02\x03").is_err());
+        assert!(decode_ppm(b"P6 2 1 255 \x01\x02\x03").is_err());
+        assert!(decode_ppm(b"P6 1 1").is_err());
+        assert!(decode_ppm(b"").is_err());
+    }
+
+    #[test]
+    fn encode_png_round_trips() {
+        let rgb = (0..2 * 3 * 3).collect::<Vec<u8>>();
+        let png = encode_png(&rgb, 2, 3).unwrap();
+
+        let (info, mut reader) = Decoder::new(png.as_slice()).read_info().unwrap();
+        assert_eq!((info.width, info.height), (2, 3));
+        assert_eq!(info.color_type, ColorType::RGB);
+
+        let mut decoded = vec![0; info.buffer_size()];
+        reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(decoded, rgb);
+    }
+}
@@ -1,66 +1,233 @@
-use tokio::sync::mpsc;
+// Every handler fails with tonic::Status, and so do the helpers they lean on; boxing it would only
+// add conversions at each call.
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{broadcast, mpsc};
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{transport::Server, Request, Response, Status};
+use tonic::{transport::Server, Request, Response, Status, Streaming};
 use uuid::Uuid;
 use virt::domain::Domain;
+use virt::domain_snapshot::DomainSnapshot;
+use xmltree::{Element, XMLNode};
 
-use libvirt_grpc_api::{byte_vec_to_uuid, enumerate_usb_devices};
+use libvirt_grpc_api::schema::schema::{USBDevice, USBDeviceAction};
+use libvirt_grpc_api::{byte_vec_to_uuid, schema, watch_usb_devices};
+use schema::domain_xml::{parse_element, write_element};
 use schema::schema::DomainState;
 
+use crate::console::relay_console;
+use crate::domain_devices::{list_domain_devices, list_graphics_devices};
+use crate::domain_events::{start_event_loop, DomainEvents};
+use crate::domain_metrics::{domain_metrics, MetricsSampler, SAMPLE_INTERVAL};
+use crate::domain_stats::{
+    to_proto_domain_stats, ALL_DOMAIN_STATS, VIR_CONNECT_GET_ALL_DOMAINS_STATS_ACTIVE,
+};
+use crate::fs_freeze::{freeze_filesystems, quiesced_snapshot, thaw_filesystems};
+use crate::graphics_proxy::{serve_graphics_proxy, GraphicsTokens, GRAPHICS_TOKEN_TTL};
+use crate::guest_agent::{guest_info, guest_interfaces, AgentError};
+use crate::guest_exec::{relay_guest_exec, start_guest_exec};
+use crate::guest_files::{relay_guest_file, GuestFile};
+use crate::prometheus::serve_metrics;
 use crate::protoc::libvirt_api;
 use crate::protoc::libvirt_api::libvirt_api_server::*;
+use crate::rpc_metrics::{MeteredService, RPCMetrics};
+use crate::screenshot::take_screenshot;
+use crate::snapshots::{list_snapshots, snapshot_xml};
 use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::usb::{
+    check_usb_owner, list_host_devices, resolve_usb_target, to_proto_usb_action,
+    to_proto_usb_device, usb_device_owners, VIR_DOMAIN_AFFECT_CONFIG, VIR_DOMAIN_AFFECT_CURRENT,
+    VIR_DOMAIN_AFFECT_LIVE,
+};
+use crate::usb_rules::{spawn_rule_engine, USBRule, USBRuleStore};
+use crate::virt_sys::{
+    get_all_domain_stats, reboot_domain_flags, restore_domain, revert_to_snapshot, save_domain,
+    shutdown_domain_flags, undefine_domain_flags, VirStream,
+};
 
+mod console;
+mod domain_devices;
+mod domain_events;
+mod domain_metrics;
+mod domain_stats;
+mod fs_freeze;
+mod graphics_proxy;
+mod guest_agent;
+mod guest_exec;
+mod guest_files;
+mod prometheus;
 mod protoc;
-mod schema;
+mod rpc_metrics;
+mod screenshot;
+mod snapshots;
 mod thread_safe_virt_conn;
+mod usb;
+mod usb_rules;
+mod virt_sys;
+
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+// Longer waits are cut short so a single request can't hold a handler indefinitely.
+const MAX_WAIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// virDomainDefineFlags
+const VIR_DOMAIN_DEFINE_VALIDATE: u32 = 1 << 0;
+
+// virDomainUndefineFlagsValues
+const VIR_DOMAIN_UNDEFINE_MANAGED_SAVE: u32 = 1 << 0;
+const VIR_DOMAIN_UNDEFINE_SNAPSHOTS_METADATA: u32 = 1 << 1;
+const VIR_DOMAIN_UNDEFINE_NVRAM: u32 = 1 << 2;
+
+// virDomainCreateFlags
+const VIR_DOMAIN_START_PAUSED: u32 = 1 << 0;
+const VIR_DOMAIN_START_AUTODESTROY: u32 = 1 << 1;
+const VIR_DOMAIN_START_VALIDATE: u32 = 1 << 4;
+
+// virDomainXMLFlags
+const VIR_DOMAIN_XML_SECURE: u32 = 1 << 0;
+const VIR_DOMAIN_XML_INACTIVE: u32 = 1 << 1;
+const VIR_DOMAIN_XML_MIGRATABLE: u32 = 1 << 3;
+
+// virDomainSnapshotCreateFlags
+const VIR_DOMAIN_SNAPSHOT_CREATE_DISK_ONLY: u32 = 1 << 4;
+const VIR_DOMAIN_SNAPSHOT_CREATE_QUIESCE: u32 = 1 << 6;
+const VIR_DOMAIN_SNAPSHOT_CREATE_ATOMIC: u32 = 1 << 7;
+
+// virDomainSnapshotRevertFlags
+const VIR_DOMAIN_SNAPSHOT_REVERT_RUNNING: u32 = 1 << 0;
+const VIR_DOMAIN_SNAPSHOT_REVERT_PAUSED: u32 = 1 << 1;
+const VIR_DOMAIN_SNAPSHOT_REVERT_FORCE: u32 = 1 << 2;
+
+// virDomainSnapshotDeleteFlags
+const VIR_DOMAIN_SNAPSHOT_DELETE_CHILDREN: u32 = 1 << 0;
+const VIR_DOMAIN_SNAPSHOT_DELETE_METADATA_ONLY: u32 = 1 << 1;
+const VIR_DOMAIN_SNAPSHOT_DELETE_CHILDREN_ONLY: u32 = 1 << 2;
+
+// virDomainSaveRestoreFlags
+const VIR_DOMAIN_SAVE_BYPASS_CACHE: u32 = 1 << 0;
+const VIR_DOMAIN_SAVE_RUNNING: u32 = 1 << 1;
+const VIR_DOMAIN_SAVE_PAUSED: u32 = 1 << 2;
+
+// virDomainConsoleFlags
+const VIR_DOMAIN_CONSOLE_FORCE: u32 = 1 << 0;
+const VIR_DOMAIN_CONSOLE_SAFE: u32 = 1 << 1;
+
+// Address for the Prometheus /metrics listener, e.g. "[::1]:9177". Unset to disable it.
+const METRICS_ADDR_ENV: &str = "LIBVIRT_GRPC_METRICS_ADDR";
+
+// Address for the websocket graphics proxy, e.g. "[::]:6080". Unset to disable it.
+const GRAPHICS_PROXY_ADDR_ENV: &str = "LIBVIRT_GRPC_GRAPHICS_PROXY_ADDR";
+
+// Where the USB auto-attach rules are kept. Defaults to DEFAULT_USB_RULES_PATH.
+const USB_RULES_PATH_ENV: &str = "LIBVIRT_GRPC_USB_RULES_PATH";
+const DEFAULT_USB_RULES_PATH: &str = "/var/lib/libvirt-grpc-api/usb-rules.json";
 
 pub struct LibvirtAPIService {
-    conn: ThreadSafeVirtConn,
+    conn: Arc<ThreadSafeVirtConn>,
+    usb_rules: Arc<USBRuleStore>,
+    events: DomainEvents,
+    metrics: MetricsSampler,
+    // Set when the graphics proxy is running.
+    graphics_tokens: Option<Arc<GraphicsTokens>>,
 }
 
-struct TryGetDomainResult {
+struct TryGetDomainResult<T> {
     domain: Option<Domain>,
-    success_response: Option<Result<Response<libvirt_api::SuccessResponse>, Status>>,
+    success_response: Option<Result<Response<T>, Status>>,
+}
+
+// Every unary response carries `success` and `error`, so a failed domain lookup can be reported
+// through whichever response type the handler returns.
+trait FailureResponse {
+    fn failure(error: String) -> Self;
+}
+
+macro_rules! impl_failure_response {
+    ($($t:ty),*) => {
+        $(impl FailureResponse for $t {
+            fn failure(error: String) -> Self {
+                Self {
+                    success: false,
+                    error: Some(error),
+                    ..Default::default()
+                }
+            }
+        })*
+    };
 }
 
+impl_failure_response!(
+    libvirt_api::SuccessResponse,
+    libvirt_api::DefineDomainResponse,
+    libvirt_api::DomainXmlResponse,
+    libvirt_api::UpdateDomainXmlResponse,
+    libvirt_api::UsbRuleResponse,
+    libvirt_api::SnapshotResponse,
+    libvirt_api::SnapshotXmlResponse,
+    libvirt_api::HasManagedSaveResponse,
+    libvirt_api::GraphicsInfoResponse,
+    libvirt_api::GraphicsTokenResponse,
+    libvirt_api::ScreenshotResponse,
+    libvirt_api::InterfaceAddressesResponse,
+    libvirt_api::GuestInfoResponse,
+    libvirt_api::GuestWriteFileResponse,
+    libvirt_api::FsFreezeResponse
+);
+
 impl LibvirtAPIService {
-    fn new(uri: &str) -> LibvirtAPIService {
-        LibvirtAPIService {
-            conn: ThreadSafeVirtConn::new(uri),
-        }
+    fn new(
+        uri: &str,
+        usb_rules_path: &str,
+    ) -> Result<LibvirtAPIService, Box<dyn std::error::Error>> {
+        let usb_rules = USBRuleStore::open(usb_rules_path.into())
+            .map_err(|e| format!("failed to load USB rules from '{}': {}", usb_rules_path, e))?;
+
+        let conn = Arc::new(ThreadSafeVirtConn::new(uri));
+        let events = DomainEvents::register(&conn.lock())?;
+        let metrics = MetricsSampler::start(conn.clone());
+
+        Ok(LibvirtAPIService {
+            conn,
+            usb_rules: Arc::new(usb_rules),
+            events,
+            metrics,
+            graphics_tokens: None,
+        })
     }
 
-    fn try_get_domain(&self, uuid: Uuid) -> TryGetDomainResult {
+    fn try_get_domain<T: FailureResponse>(&self, uuid: Uuid) -> TryGetDomainResult<T> {
         let domain =
-            virt::domain::Domain::lookup_by_uuid_string(&self.conn.lock(), &*uuid.to_string());
+            virt::domain::Domain::lookup_by_uuid_string(&self.conn.lock(), &uuid.to_string());
 
-        return match domain {
+        match domain {
             Ok(x) => TryGetDomainResult {
                 domain: Some(x),
                 success_response: None,
             },
             Err(e) => TryGetDomainResult {
                 domain: None,
-                success_response: Some(Ok(Response::new(libvirt_api::SuccessResponse {
-                    success: false,
-                    error: Some(
-                        format!(
-                            "failed to look up domain with UUID '{}': {}",
-                            uuid, e.message
-                        )
-                        .to_string(),
-                    ),
-                }))),
+                success_response: Some(Ok(Response::new(T::failure(
+                    format!(
+                        "failed to look up domain with UUID '{}': {}",
+                        uuid, e.message
+                    )
+                    .to_string(),
+                )))),
             },
-        };
+        }
     }
 
     fn return_success(&self) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
-        return Ok(Response::new(libvirt_api::SuccessResponse {
+        Ok(Response::new(libvirt_api::SuccessResponse {
             success: true,
             error: None,
-        }));
+            state: None,
+            elapsed_ms: None,
+        }))
     }
 
     fn return_failure(
@@ -70,8 +237,333 @@ impl LibvirtAPIService {
         Ok(Response::new(libvirt_api::SuccessResponse {
             success: false,
             error: Some(message),
+            state: None,
+            elapsed_ms: None,
         }))
     }
+
+    fn return_defined(
+        &self,
+        domain: Result<Domain, virt::error::Error>,
+    ) -> Result<Response<libvirt_api::DefineDomainResponse>, Status> {
+        Ok(Response::new(match domain {
+            Ok(x) => libvirt_api::DefineDomainResponse {
+                success: true,
+                error: None,
+                uuid: Uuid::parse_str(x.get_uuid_string().unwrap().as_str())
+                    .unwrap()
+                    .as_bytes()
+                    .to_vec(),
+                name: x.get_name().unwrap(),
+            },
+            Err(e) => libvirt_api::DefineDomainResponse::failure(e.message),
+        }))
+    }
+
+    fn return_usb_rule(
+        &self,
+        result: Result<USBRule, String>,
+    ) -> Result<Response<libvirt_api::UsbRuleResponse>, Status> {
+        Ok(Response::new(match result {
+            Ok(rule) => libvirt_api::UsbRuleResponse {
+                success: true,
+                error: None,
+                rule: Some(to_proto_usb_rule(rule)),
+            },
+            Err(e) => libvirt_api::UsbRuleResponse::failure(e),
+        }))
+    }
+
+    // Looks up the domain and runs `op` against it. The connection is only locked for the lookup,
+    // and the Domain handle is dropped before returning so callers can await afterwards.
+    fn with_domain<F>(&self, uuid: Uuid, op: F) -> Result<(), String>
+    where
+        F: FnOnce(&Domain) -> Result<(), String>,
+    {
+        let domain =
+            virt::domain::Domain::lookup_by_uuid_string(&self.conn.lock(), &uuid.to_string());
+
+        match domain {
+            Ok(x) => op(&x),
+            Err(e) => Err(format!(
+                "failed to look up domain with UUID '{}': {}",
+                uuid, e.message
+            )),
+        }
+    }
+
+    fn current_state(&self, uuid: Uuid) -> Result<DomainState, String> {
+        let mut state = DomainState::Unspecified;
+        self.with_domain(uuid, |domain| {
+            state = domain_state(domain);
+            Ok(())
+        })?;
+        Ok(state)
+    }
+
+    async fn return_lifecycle(
+        &self,
+        uuid: Uuid,
+        result: Result<(), String>,
+        wait: Option<libvirt_api::WaitForState>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        if let Err(e) = result {
+            return self.return_failure(e);
+        }
+
+        let wait = match wait {
+            Some(x) => x,
+            None => return self.return_success(),
+        };
+
+        let timeout = match wait.timeout_ms {
+            0 => DEFAULT_WAIT_TIMEOUT,
+            x => Duration::from_millis(x as u64).min(MAX_WAIT_TIMEOUT),
+        };
+        let start = Instant::now();
+
+        loop {
+            let state = match self.current_state(uuid) {
+                Ok(x) => proto_domain_state(x),
+                Err(e) => return self.return_failure(e),
+            };
+            let elapsed = start.elapsed();
+
+            if state == wait.state || elapsed >= timeout {
+                return Ok(Response::new(libvirt_api::SuccessResponse {
+                    success: state == wait.state,
+                    error: match state == wait.state {
+                        true => None,
+                        false => Some(format!(
+                            "timed out after {}ms waiting for domain '{}' to reach state {:?}; \
+                             it is {:?}",
+                            elapsed.as_millis(),
+                            uuid,
+                            libvirt_api::DomainState::from_i32(wait.state)
+                                .unwrap_or(libvirt_api::DomainState::Unspecified),
+                            libvirt_api::DomainState::from_i32(state)
+                                .unwrap_or(libvirt_api::DomainState::Unspecified)
+                        )),
+                    },
+                    state: Some(state),
+                    elapsed_ms: Some(elapsed.as_millis() as u64),
+                }));
+            }
+
+            tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+        }
+    }
+}
+
+// Makes `xml` redefine the domain `uuid` instead of defining a new one: a <uuid> has to match,
+// and a missing one is filled in.
+fn pin_domain_uuid(xml: &str, uuid: Uuid) -> Result<String, String> {
+    let mut root = parse_element(xml).map_err(|e| e.to_string())?;
+
+    let text = match root.get_child("uuid") {
+        Some(x) => x.get_text().unwrap_or_default().trim().to_string(),
+        None => {
+            let mut e = Element::new("uuid");
+            e.children.push(XMLNode::Text(uuid.to_string()));
+            root.children.push(XMLNode::Element(e));
+
+            return write_element(&root).map_err(|e| e.to_string());
+        }
+    };
+
+    match Uuid::parse_str(&text) {
+        Ok(x) if x == uuid => Ok(xml.to_string()),
+        Ok(x) => Err(format!("XML is for domain '{}', not '{}'", x, uuid)),
+        Err(e) => Err(format!("invalid <uuid> '{}': {}", text, e)),
+    }
+}
+
+fn domain_state(domain: &Domain) -> DomainState {
+    match domain.get_state() {
+        Ok(s) => match s.0 {
+            virt::domain::VIR_DOMAIN_NOSTATE => DomainState::NoState,
+            virt::domain::VIR_DOMAIN_RUNNING => DomainState::Running,
+            virt::domain::VIR_DOMAIN_BLOCKED => DomainState::Blocked,
+            virt::domain::VIR_DOMAIN_PAUSED => DomainState::Paused,
+            virt::domain::VIR_DOMAIN_SHUTDOWN => DomainState::ShutDown,
+            virt::domain::VIR_DOMAIN_SHUTOFF => DomainState::ShutOff,
+            virt::domain::VIR_DOMAIN_CRASHED => DomainState::Crashed,
+            virt::domain::VIR_DOMAIN_PMSUSPENDED => DomainState::PMSuspended,
+            _ => panic!("Out of bounds"),
+        },
+        Err(_) => DomainState::Unspecified,
+    }
+}
+
+fn proto_domain_state(state: DomainState) -> i32 {
+    match state {
+        DomainState::Unspecified => libvirt_api::DomainState::Unspecified as i32,
+        DomainState::NoState => libvirt_api::DomainState::Nostate as i32,
+        DomainState::Running => libvirt_api::DomainState::Running as i32,
+        DomainState::Blocked => libvirt_api::DomainState::Blocked as i32,
+        DomainState::Paused => libvirt_api::DomainState::Paused as i32,
+        DomainState::ShutDown => libvirt_api::DomainState::Shutdown as i32,
+        DomainState::ShutOff => libvirt_api::DomainState::Shutoff as i32,
+        DomainState::Crashed => libvirt_api::DomainState::Crashed as i32,
+        DomainState::PMSuspended => libvirt_api::DomainState::Pmsuspended as i32,
+    }
+}
+
+// An empty field means "not set"; anything else has to be a 16-byte UUID.
+fn optional_uuid(bytes: Vec<u8>, field: &str) -> Result<Option<Uuid>, Status> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+
+    match Uuid::from_slice(&bytes) {
+        Ok(x) => Ok(Some(x)),
+        Err(_) => Err(Status::invalid_argument(format!(
+            "{} is not a 16-byte UUID",
+            field
+        ))),
+    }
+}
+
+// Every entry has to be a 16-byte UUID.
+fn uuid_list(list: &[Vec<u8>], field: &str) -> Result<Vec<Uuid>, Status> {
+    list.iter()
+        .map(|x| {
+            Uuid::from_slice(x).map_err(|_| {
+                Status::invalid_argument(format!("{} must only hold 16-byte UUIDs", field))
+            })
+        })
+        .collect()
+}
+
+fn from_proto_usb_rule(rule: libvirt_api::UsbRule) -> Result<USBRule, Status> {
+    let domain_uuid = match optional_uuid(rule.domain_uuid, "domain_uuid")? {
+        Some(x) => x,
+        None => return Err(Status::invalid_argument("domain_uuid is required")),
+    };
+
+    let rule = USBRule {
+        id: optional_uuid(rule.id, "id")?.unwrap_or_else(Uuid::nil),
+        domain_uuid,
+        vendor_id: rule.vendor_id,
+        product_id: rule.product_id,
+        serial: rule.serial,
+        port_path: rule.port_path,
+    };
+
+    rule.normalized().map_err(Status::invalid_argument)
+}
+
+fn to_proto_usb_rule(rule: USBRule) -> libvirt_api::UsbRule {
+    libvirt_api::UsbRule {
+        id: rule.id.as_bytes().to_vec(),
+        domain_uuid: rule.domain_uuid.as_bytes().to_vec(),
+        vendor_id: rule.vendor_id,
+        product_id: rule.product_id,
+        serial: rule.serial,
+        port_path: rule.port_path,
+    }
+}
+
+fn scope_flags(scope: i32) -> Result<u32, Status> {
+    match libvirt_api::DeviceScope::from_i32(scope) {
+        Some(libvirt_api::DeviceScope::Current) => Ok(VIR_DOMAIN_AFFECT_CURRENT),
+        Some(libvirt_api::DeviceScope::Config) => Ok(VIR_DOMAIN_AFFECT_CONFIG),
+        Some(libvirt_api::DeviceScope::Both) => {
+            Ok(VIR_DOMAIN_AFFECT_LIVE | VIR_DOMAIN_AFFECT_CONFIG)
+        }
+        // virDomainAttachDevice/virDomainDetachDevice only ever touched the live domain.
+        Some(libvirt_api::DeviceScope::Live) | Some(libvirt_api::DeviceScope::Unspecified) => {
+            Ok(VIR_DOMAIN_AFFECT_LIVE)
+        }
+        None => Err(Status::invalid_argument(format!(
+            "unknown device scope {}",
+            scope
+        ))),
+    }
+}
+
+// ShutdownMode values are libvirt's own flag bits, so the requested modes are
+// simply OR'd together.
+fn mode_flags(modes: &[i32]) -> Result<u32, Status> {
+    let mut flags = 0;
+    for &mode in modes {
+        if libvirt_api::ShutdownMode::from_i32(mode).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "unknown shutdown mode {}",
+                mode
+            )));
+        }
+        flags |= mode as u32;
+    }
+
+    Ok(flags)
+}
+
+// GuestInfoType values are libvirt's virDomainGuestInfoTypes bits; none at all asks for
+// everything the agent supports.
+fn guest_info_flags(types: &[i32]) -> Result<u32, Status> {
+    let mut flags = 0;
+    for &t in types {
+        if libvirt_api::GuestInfoType::from_i32(t).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "unknown guest info type {}",
+                t
+            )));
+        }
+        flags |= t as u32;
+    }
+
+    Ok(flags)
+}
+
+fn save_flags(options: Option<libvirt_api::SaveOptions>) -> Result<u32, Status> {
+    let options = options.unwrap_or_default();
+
+    if options.running && options.paused {
+        return Err(Status::invalid_argument(
+            "running and paused are mutually exclusive",
+        ));
+    }
+
+    let mut flags = 0;
+    if options.bypass_cache {
+        flags |= VIR_DOMAIN_SAVE_BYPASS_CACHE;
+    }
+    if options.running {
+        flags |= VIR_DOMAIN_SAVE_RUNNING;
+    }
+    if options.paused {
+        flags |= VIR_DOMAIN_SAVE_PAUSED;
+    }
+
+    Ok(flags)
+}
+
+// Save images are written and read by libvirtd, so a relative path would resolve against its
+// working directory rather than anything the caller knows about.
+fn check_save_path(path: &str) -> Result<(), Status> {
+    if !std::path::Path::new(path).is_absolute() {
+        return Err(Status::invalid_argument(format!(
+            "path '{}' must be absolute",
+            path
+        )));
+    }
+
+    Ok(())
+}
+
+fn fs_freeze_response(result: Result<u32, AgentError>) -> libvirt_api::FsFreezeResponse {
+    match result {
+        Ok(filesystems) => libvirt_api::FsFreezeResponse {
+            success: true,
+            filesystems,
+            ..Default::default()
+        },
+        Err(e) => libvirt_api::FsFreezeResponse {
+            agent_not_connected: e.not_connected,
+            ..libvirt_api::FsFreezeResponse::failure(e.message)
+        },
+    }
 }
 
 #[tonic::async_trait]
@@ -101,28 +593,15 @@ impl LibvirtApi for LibvirtAPIService {
                     hostname: x.get_hostname(0).ok(),
                     os_type: x.get_os_type().ok(),
 
-                    state: match x.get_state() {
-                        Ok(s) => match s.0 {
-                            virt::domain::VIR_DOMAIN_NOSTATE => DomainState::NoState,
-                            virt::domain::VIR_DOMAIN_RUNNING => DomainState::Running,
-                            virt::domain::VIR_DOMAIN_BLOCKED => DomainState::Blocked,
-                            virt::domain::VIR_DOMAIN_PAUSED => DomainState::Paused,
-                            virt::domain::VIR_DOMAIN_SHUTDOWN => DomainState::ShutDown,
-                            virt::domain::VIR_DOMAIN_SHUTOFF => DomainState::ShutOff,
-                            virt::domain::VIR_DOMAIN_CRASHED => DomainState::Crashed,
-                            virt::domain::VIR_DOMAIN_PMSUSPENDED => DomainState::PMSuspended,
-                            _ => panic!("Out of bounds"),
-                        },
-                        Err(_) => DomainState::Unspecified,
-                    },
-                    memory: x.get_max_memory().unwrap(),
-                    memory_max: x.get_max_memory().unwrap(),
+                    state: domain_state(x),
+                    memory: info.memory,
+                    memory_max: info.max_mem,
                     virt_cpu_num: info.nr_virt_cpu,
                     virt_cpu_time: info.cpu_time,
+                    has_managed_save: x.has_managed_save(0).unwrap_or(false),
                 }
             })
-            .collect::<Vec<_>>()
-            .into();
+            .collect::<Vec<_>>();
 
         let (tx, rx) = mpsc::channel(4);
 
@@ -134,21 +613,12 @@ impl LibvirtApi for LibvirtAPIService {
                     name: v.name,
                     hostname: v.hostname,
                     os_type: v.os_type,
-                    state: match v.state {
-                        DomainState::Unspecified => libvirt_api::DomainState::Unspecified as i32,
-                        DomainState::NoState => libvirt_api::DomainState::Nostate as i32,
-                        DomainState::Running => libvirt_api::DomainState::Running as i32,
-                        DomainState::Blocked => libvirt_api::DomainState::Blocked as i32,
-                        DomainState::Paused => libvirt_api::DomainState::Paused as i32,
-                        DomainState::ShutDown => libvirt_api::DomainState::Shutdown as i32,
-                        DomainState::ShutOff => libvirt_api::DomainState::Shutoff as i32,
-                        DomainState::Crashed => libvirt_api::DomainState::Crashed as i32,
-                        DomainState::PMSuspended => libvirt_api::DomainState::Pmsuspended as i32,
-                    },
+                    state: proto_domain_state(v.state),
                     memory: v.memory,
                     memory_max: v.memory_max,
                     virt_cpu_num: v.virt_cpu_num,
                     virt_cpu_time: v.virt_cpu_time,
+                    has_managed_save: v.has_managed_save,
                 }))
                 .await
                 .unwrap();
@@ -163,21 +633,16 @@ impl LibvirtApi for LibvirtAPIService {
         request: Request<libvirt_api::CreateDomainRequest>,
     ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
         eprintln!("create_domain");
-        let uuid = byte_vec_to_uuid(request.into_inner().uuid).unwrap();
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
 
-        let domain_r = self.try_get_domain(uuid);
-        if domain_r.success_response.is_some() {
-            return domain_r.success_response.unwrap();
-        }
-        let domain = domain_r.domain.unwrap();
+        let result = self.with_domain(uuid, |domain| match domain.create() {
+            Ok(0) => Ok(()),
+            Ok(x) => Err(format!("virDomainCreate returned a non-0 response: {}", x)),
+            Err(e) => Err(e.message),
+        });
 
-        return match domain.create() {
-            Ok(0) => self.return_success(),
-            Ok(x) => self.return_failure(
-                format!("virDomainCreate returned a non-0 response: {}", x).to_string(),
-            ),
-            Err(e) => self.return_failure(e.message),
-        };
+        return self.return_lifecycle(uuid, result, r.wait).await;
     }
 
     async fn destroy_domain(
@@ -185,19 +650,265 @@ impl LibvirtApi for LibvirtAPIService {
         request: Request<libvirt_api::DestroyDomainRequest>,
     ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
         eprintln!("destroy_domain");
-        let uuid = byte_vec_to_uuid(request.into_inner().uuid).unwrap();
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let result = self.with_domain(uuid, |domain| match domain.destroy() {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.message),
+        });
+
+        return self.return_lifecycle(uuid, result, r.wait).await;
+    }
+
+    async fn shutdown_domain(
+        &self,
+        request: Request<libvirt_api::ShutdownDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("shutdown_domain");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+        let flags = mode_flags(&r.modes)?;
+
+        let result = self.with_domain(uuid, |domain| shutdown_domain_flags(domain, flags));
+
+        return self.return_lifecycle(uuid, result, r.wait).await;
+    }
+
+    async fn reboot_domain(
+        &self,
+        request: Request<libvirt_api::RebootDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("reboot_domain");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+        let flags = mode_flags(&r.modes)?;
+
+        let result = self.with_domain(uuid, |domain| reboot_domain_flags(domain, flags));
+
+        return self.return_lifecycle(uuid, result, r.wait).await;
+    }
+
+    async fn reset_domain(
+        &self,
+        request: Request<libvirt_api::ResetDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("reset_domain");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let result = self.with_domain(uuid, |domain| match domain.reset() {
+            Ok(0) => Ok(()),
+            Ok(x) => Err(format!("virDomainReset returned a non-0 response: {}", x)),
+            Err(e) => Err(e.message),
+        });
+
+        return self.return_lifecycle(uuid, result, r.wait).await;
+    }
+
+    async fn suspend_domain(
+        &self,
+        request: Request<libvirt_api::SuspendDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("suspend_domain");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let result = self.with_domain(uuid, |domain| match domain.suspend() {
+            Ok(0) => Ok(()),
+            Ok(x) => Err(format!("virDomainSuspend returned a non-0 response: {}", x)),
+            Err(e) => Err(e.message),
+        });
+
+        return self.return_lifecycle(uuid, result, r.wait).await;
+    }
+
+    async fn resume_domain(
+        &self,
+        request: Request<libvirt_api::ResumeDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("resume_domain");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let result = self.with_domain(uuid, |domain| match domain.resume() {
+            Ok(0) => Ok(()),
+            Ok(x) => Err(format!("virDomainResume returned a non-0 response: {}", x)),
+            Err(e) => Err(e.message),
+        });
+
+        return self.return_lifecycle(uuid, result, r.wait).await;
+    }
+
+    async fn define_domain(
+        &self,
+        request: Request<libvirt_api::DefineDomainRequest>,
+    ) -> Result<Response<libvirt_api::DefineDomainResponse>, Status> {
+        eprintln!("define_domain");
+        let r = request.into_inner();
+
+        let mut flags = 0;
+        if r.validate {
+            flags |= VIR_DOMAIN_DEFINE_VALIDATE;
+        }
+
+        let domain = virt::domain::Domain::define_xml_flags(&self.conn.lock(), &r.xml, flags);
+
+        return self.return_defined(domain);
+    }
+
+    async fn undefine_domain(
+        &self,
+        request: Request<libvirt_api::UndefineDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("undefine_domain");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let mut flags = 0;
+        if r.managed_save {
+            flags |= VIR_DOMAIN_UNDEFINE_MANAGED_SAVE;
+        }
+        if r.snapshots_metadata {
+            flags |= VIR_DOMAIN_UNDEFINE_SNAPSHOTS_METADATA;
+        }
+        if r.nvram {
+            flags |= VIR_DOMAIN_UNDEFINE_NVRAM;
+        }
 
         let domain_r = self.try_get_domain(uuid);
-        if domain_r.success_response.is_some() {
-            return domain_r.success_response.unwrap();
+        if let Some(response) = domain_r.success_response {
+            return response;
         }
         let domain = domain_r.domain.unwrap();
-        let r = domain.destroy();
 
-        return match r {
+        return match undefine_domain_flags(&domain, flags) {
             Ok(_) => self.return_success(),
-            Err(e) => self.return_failure(e.message),
+            Err(e) => self.return_failure(e),
+        };
+    }
+
+    async fn create_transient_domain(
+        &self,
+        request: Request<libvirt_api::CreateTransientDomainRequest>,
+    ) -> Result<Response<libvirt_api::DefineDomainResponse>, Status> {
+        eprintln!("create_transient_domain");
+        let r = request.into_inner();
+
+        let mut flags = 0;
+        if r.start_paused {
+            flags |= VIR_DOMAIN_START_PAUSED;
+        }
+        if r.autodestroy {
+            flags |= VIR_DOMAIN_START_AUTODESTROY;
+        }
+        if r.validate {
+            flags |= VIR_DOMAIN_START_VALIDATE;
+        }
+
+        let domain = virt::domain::Domain::create_xml(&self.conn.lock(), &r.xml, flags);
+
+        return self.return_defined(domain);
+    }
+
+    async fn get_domain_xml(
+        &self,
+        request: Request<libvirt_api::GetDomainXmlRequest>,
+    ) -> Result<Response<libvirt_api::DomainXmlResponse>, Status> {
+        eprintln!("get_domain_xml");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let mut flags = 0;
+        if r.inactive {
+            flags |= VIR_DOMAIN_XML_INACTIVE;
+        }
+        if r.secure {
+            flags |= VIR_DOMAIN_XML_SECURE;
+        }
+        if r.migratable {
+            flags |= VIR_DOMAIN_XML_MIGRATABLE;
+        }
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return Ok(Response::new(match domain.get_xml_desc(flags) {
+            Ok(xml) => libvirt_api::DomainXmlResponse {
+                success: true,
+                error: None,
+                xml,
+            },
+            Err(e) => libvirt_api::DomainXmlResponse::failure(e.message),
+        }));
+    }
+
+    async fn update_domain_xml(
+        &self,
+        request: Request<libvirt_api::UpdateDomainXmlRequest>,
+    ) -> Result<Response<libvirt_api::UpdateDomainXmlResponse>, Status> {
+        eprintln!("update_domain_xml");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        // define_xml happily creates a new domain when the XML carries a different UUID or a new
+        // name and none, so settle that before defining anything.
+        let xml = match pin_domain_uuid(&r.xml, uuid) {
+            Ok(x) => x,
+            Err(e) => {
+                return Ok(Response::new(
+                    libvirt_api::UpdateDomainXmlResponse::failure(e),
+                ))
+            }
+        };
+
+        // Redefining only touches the persistent config, so diff against the inactive XML.
+        let mut xml_flags = VIR_DOMAIN_XML_INACTIVE;
+        if r.secure {
+            xml_flags |= VIR_DOMAIN_XML_SECURE;
+        }
+        let xml_before = match domain.get_xml_desc(xml_flags) {
+            Ok(x) => x,
+            Err(e) => {
+                return Ok(Response::new(
+                    libvirt_api::UpdateDomainXmlResponse::failure(e.message),
+                ))
+            }
+        };
+
+        let mut flags = 0;
+        if r.validate {
+            flags |= VIR_DOMAIN_DEFINE_VALIDATE;
+        }
+
+        let defined = virt::domain::Domain::define_xml_flags(&self.conn.lock(), &xml, flags);
+        let defined = match defined {
+            Ok(x) => x,
+            Err(e) => {
+                return Ok(Response::new(
+                    libvirt_api::UpdateDomainXmlResponse::failure(e.message),
+                ))
+            }
         };
+
+        return Ok(Response::new(match defined.get_xml_desc(xml_flags) {
+            Ok(xml_after) => libvirt_api::UpdateDomainXmlResponse {
+                success: true,
+                error: None,
+                xml_before,
+                xml_after,
+            },
+            Err(e) => libvirt_api::UpdateDomainXmlResponse::failure(e.message),
+        }));
     }
 
     type ListUSBDevicesStream = ReceiverStream<Result<libvirt_api::UsbDevice, Status>>;
@@ -207,22 +918,20 @@ impl LibvirtApi for LibvirtAPIService {
         _: Request<libvirt_api::ListUsbDevicesRequest>,
     ) -> Result<Response<Self::ListUSBDevicesStream>, Status> {
         eprintln!("list_usb_devices");
-        let devices = enumerate_usb_devices().unwrap();
+        let mut devices = list_host_devices().map_err(|e| Status::internal(e.to_string()))?;
+
+        let owners = usb_device_owners(&self.conn.lock(), &devices);
+        for device in devices.iter_mut() {
+            if let Some(uuid) = owners.get(&(device.bus, device.device_number)) {
+                device.attached_to = uuid.as_bytes().to_vec();
+            }
+        }
 
         let (tx, rx) = mpsc::channel(4);
 
         tokio::spawn(async move {
             for device in devices {
-                tx.send(Ok(libvirt_api::UsbDevice {
-                    device: device.device,
-                    vendor_id: device.vendor_id,
-                    product_id: device.product_id,
-                    model: device.model,
-                    vendor_name: device.vendor_name,
-                    model_name: device.model_name,
-                }))
-                .await
-                .unwrap();
+                tx.send(Ok(device)).await.unwrap();
             }
         });
 
@@ -237,21 +946,22 @@ impl LibvirtApi for LibvirtAPIService {
         let r = request.into_inner();
         let uuid = byte_vec_to_uuid(r.domain_uuid).unwrap();
 
+        let target = resolve_usb_target(&r.vendor_id, &r.product_id, r.selector)?;
+        let flags = scope_flags(r.scope)?;
+
+        if !r.force {
+            check_usb_owner(&self.conn.lock(), uuid, &target)?;
+        }
+
         let domain_r = self.try_get_domain(uuid);
-        if domain_r.success_response.is_some() {
-            return domain_r.success_response.unwrap();
+        if let Some(response) = domain_r.success_response {
+            return response;
         }
         let domain = domain_r.domain.unwrap();
 
-        let attach = domain.attach_device(&*format!(
-            "<hostdev mode='subsystem' type='usb' managed='no'><source><vendor id='0x{}'/><product id='0x{}'/></source></hostdev>", 
-            r.vendor_id,
-            r.product_id
-        ));
-
-        return match attach {
+        return match target.attach_to(&domain, flags) {
             Ok(_) => self.return_success(),
-            Err(e) => self.return_failure(e.message),
+            Err(e) => self.return_failure(e),
         };
     }
 
@@ -263,38 +973,1302 @@ impl LibvirtApi for LibvirtAPIService {
         let r = request.into_inner();
         let uuid = byte_vec_to_uuid(r.domain_uuid).unwrap();
 
+        let target = resolve_usb_target(&r.vendor_id, &r.product_id, r.selector)?;
+        let flags = scope_flags(r.scope)?;
+
         let domain_r = self.try_get_domain(uuid);
-        if domain_r.success_response.is_some() {
-            return domain_r.success_response.unwrap();
+        if let Some(response) = domain_r.success_response {
+            return response;
         }
         let domain = domain_r.domain.unwrap();
 
-        let attach = domain.detach_device(&*format!(
-            "<hostdev mode='subsystem' type='usb' managed='no'><source><vendor id='0x{}'/><product id='0x{}'/></source></hostdev>",
-            r.vendor_id,
-            r.product_id
-        ));
+        return match target.detach_from(&domain, flags) {
+            Ok(_) => self.return_success(),
+            Err(e) => self.return_failure(e),
+        };
+    }
+
+    type ListDomainDevicesStream = ReceiverStream<Result<libvirt_api::DomainDevice, Status>>;
+
+    async fn list_domain_devices(
+        &self,
+        request: Request<libvirt_api::ListDomainDevicesRequest>,
+    ) -> Result<Response<Self::ListDomainDevicesStream>, Status> {
+        eprintln!("list_domain_devices");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let mut flags = 0;
+        if r.inactive {
+            flags |= VIR_DOMAIN_XML_INACTIVE;
+        }
+
+        let domain =
+            virt::domain::Domain::lookup_by_uuid_string(&self.conn.lock(), &uuid.to_string())
+                .map_err(|e| {
+                    Status::not_found(format!(
+                        "failed to look up domain with UUID '{}': {}",
+                        uuid, e.message
+                    ))
+                })?;
+        let xml = domain
+            .get_xml_desc(flags)
+            .map_err(|e| Status::internal(e.message))?;
+
+        let host_devices = list_host_devices().map_err(|e| Status::internal(e.to_string()))?;
+        let devices = list_domain_devices(&xml, &host_devices)
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            for device in devices {
+                tx.send(Ok(device)).await.unwrap();
+            }
+        });
+
+        return Ok(Response::new(ReceiverStream::new(rx)));
+    }
+
+    // async fn ListUSBDevices(&self, request: Request<libvirt_api::ListUSBDevicesRequest>)
+
+    type WatchUSBDevicesStream = ReceiverStream<Result<libvirt_api::UsbDeviceEvent, Status>>;
+
+    async fn watch_usb_devices(
+        &self,
+        _: Request<libvirt_api::WatchUsbDevicesRequest>,
+    ) -> Result<Response<Self::WatchUSBDevicesStream>, Status> {
+        eprintln!("watch_usb_devices");
+        let (tx, rx) = mpsc::channel(4);
+        let conn = self.conn.clone();
+
+        // The udev monitor blocks, so it gets its own thread rather than a tokio task. It stops
+        // the first time a send fails or, while idle, once the client has gone away.
+        std::thread::spawn(move || {
+            let on_event = |action: USBDeviceAction, device: USBDevice| {
+                let mut device = to_proto_usb_device(device);
+
+                if action != USBDeviceAction::Remove {
+                    let host_devices = vec![device.clone()];
+                    let owners = usb_device_owners(&conn.lock(), &host_devices);
+                    if let Some(uuid) = owners.get(&(device.bus, device.device_number)) {
+                        device.attached_to = uuid.as_bytes().to_vec();
+                    }
+                }
 
-        return match attach {
+                let event = libvirt_api::UsbDeviceEvent {
+                    action: to_proto_usb_action(action),
+                    device: Some(device),
+                };
+
+                tx.blocking_send(Ok(event)).is_ok()
+            };
+
+            if let Err(e) = watch_usb_devices(on_event, || tx.is_closed()) {
+                let _ = tx.blocking_send(Err(Status::internal(e.to_string())));
+            }
+        });
+
+        return Ok(Response::new(ReceiverStream::new(rx)));
+    }
+
+    type ListUSBRulesStream = ReceiverStream<Result<libvirt_api::UsbRule, Status>>;
+
+    async fn list_usb_rules(
+        &self,
+        _: Request<libvirt_api::ListUsbRulesRequest>,
+    ) -> Result<Response<Self::ListUSBRulesStream>, Status> {
+        eprintln!("list_usb_rules");
+        let rules = self.usb_rules.list();
+
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            for rule in rules {
+                tx.send(Ok(to_proto_usb_rule(rule))).await.unwrap();
+            }
+        });
+
+        return Ok(Response::new(ReceiverStream::new(rx)));
+    }
+
+    async fn create_usb_rule(
+        &self,
+        request: Request<libvirt_api::CreateUsbRuleRequest>,
+    ) -> Result<Response<libvirt_api::UsbRuleResponse>, Status> {
+        eprintln!("create_usb_rule");
+        let r = request.into_inner();
+
+        let rule = match r.rule {
+            Some(x) => from_proto_usb_rule(x)?,
+            None => return Err(Status::invalid_argument("rule is required")),
+        };
+
+        return self.return_usb_rule(self.usb_rules.create(rule));
+    }
+
+    async fn update_usb_rule(
+        &self,
+        request: Request<libvirt_api::UpdateUsbRuleRequest>,
+    ) -> Result<Response<libvirt_api::UsbRuleResponse>, Status> {
+        eprintln!("update_usb_rule");
+        let r = request.into_inner();
+
+        let rule = match r.rule {
+            Some(x) => from_proto_usb_rule(x)?,
+            None => return Err(Status::invalid_argument("rule is required")),
+        };
+        if rule.id.is_nil() {
+            return Err(Status::invalid_argument("rule.id is required"));
+        }
+
+        return self.return_usb_rule(self.usb_rules.update(rule));
+    }
+
+    async fn delete_usb_rule(
+        &self,
+        request: Request<libvirt_api::DeleteUsbRuleRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("delete_usb_rule");
+        let r = request.into_inner();
+        let id = match optional_uuid(r.id, "id")? {
+            Some(x) => x,
+            None => return Err(Status::invalid_argument("id is required")),
+        };
+
+        return match self.usb_rules.delete(id) {
+            Ok(_) => self.return_success(),
+            Err(e) => self.return_failure(e),
+        };
+    }
+
+    type WatchDomainEventsStream = ReceiverStream<Result<libvirt_api::DomainEvent, Status>>;
+
+    async fn watch_domain_events(
+        &self,
+        request: Request<libvirt_api::WatchDomainEventsRequest>,
+    ) -> Result<Response<Self::WatchDomainEventsStream>, Status> {
+        eprintln!("watch_domain_events");
+        let r = request.into_inner();
+        let filter = optional_uuid(r.uuid, "uuid")?.map(|x| x.as_bytes().to_vec());
+
+        let mut events = self.events.subscribe();
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(x) => x,
+                    // A client that silently missed a crash would be worse off than one that is
+                    // told to resynchronise.
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        let _ = tx
+                            .send(Err(Status::data_loss(format!(
+                                "stream fell behind and dropped {} events",
+                                n
+                            ))))
+                            .await;
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                if let Some(uuid) = &filter {
+                    if *uuid != event.uuid {
+                        continue;
+                    }
+                }
+
+                if tx.send(Ok(event)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        return Ok(Response::new(ReceiverStream::new(rx)));
+    }
+
+    type GetDomainStatsStream = ReceiverStream<Result<libvirt_api::DomainStats, Status>>;
+
+    async fn get_domain_stats(
+        &self,
+        request: Request<libvirt_api::GetDomainStatsRequest>,
+    ) -> Result<Response<Self::GetDomainStatsStream>, Status> {
+        eprintln!("get_domain_stats");
+        let r = request.into_inner();
+
+        let groups = match r.stats {
+            0 => ALL_DOMAIN_STATS,
+            x => x,
+        };
+
+        let mut flags = 0;
+        if r.active_only {
+            flags |= VIR_CONNECT_GET_ALL_DOMAINS_STATS_ACTIVE;
+        }
+
+        let uuids = uuid_list(&r.uuids, "uuids")?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let stats = get_all_domain_stats(&self.conn.lock(), groups, flags)
+            .map_err(Status::internal)?
+            .iter()
+            .filter(|x| uuids.is_empty() || uuids.contains(&x.uuid))
+            .map(to_proto_domain_stats)
+            .collect::<Vec<_>>();
+
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            for v in stats {
+                if tx.send(Ok(v)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        return Ok(Response::new(ReceiverStream::new(rx)));
+    }
+
+    type StreamDomainMetricsStream = ReceiverStream<Result<libvirt_api::DomainMetrics, Status>>;
+
+    async fn stream_domain_metrics(
+        &self,
+        request: Request<libvirt_api::StreamDomainMetricsRequest>,
+    ) -> Result<Response<Self::StreamDomainMetricsStream>, Status> {
+        eprintln!("stream_domain_metrics");
+        let r = request.into_inner();
+
+        let interval = match r.interval_ms {
+            0 => SAMPLE_INTERVAL,
+            x => Duration::from_millis(x as u64),
+        };
+        if interval < SAMPLE_INTERVAL {
+            return Err(Status::invalid_argument(format!(
+                "interval_ms must be at least {}",
+                SAMPLE_INTERVAL.as_millis()
+            )));
+        }
+
+        let uuids = uuid_list(&r.uuids, "uuids")?;
+
+        let subscription = self.metrics.subscribe();
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            let mut previous = None;
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                // Without this a client that went away is only noticed on the next send, which
+                // never comes while none of its domains are running.
+                tokio::select! {
+                    _ = tx.closed() => return,
+                    _ = ticker.tick() => {}
+                }
+
+                let sample = match subscription.samples.borrow().clone() {
+                    Some(x) => x,
+                    None => continue,
+                };
+
+                if let Some(before) = &previous {
+                    if Arc::ptr_eq(before, &sample) {
+                        continue;
+                    }
+
+                    for metrics in domain_metrics(before, &sample, &uuids) {
+                        if tx.send(Ok(metrics)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+
+                previous = Some(sample);
+            }
+        });
+
+        return Ok(Response::new(ReceiverStream::new(rx)));
+    }
+
+    async fn create_snapshot(
+        &self,
+        request: Request<libvirt_api::CreateSnapshotRequest>,
+    ) -> Result<Response<libvirt_api::SnapshotResponse>, Status> {
+        eprintln!("create_snapshot");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        if r.quiesce && !r.disk_only {
+            return Err(Status::invalid_argument("quiesce requires disk_only"));
+        }
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        let xml = match r.xml.is_empty() {
+            true => match snapshot_xml(&r.name, &r.description) {
+                Ok(x) => x,
+                Err(e) => return Err(Status::invalid_argument(e.to_string())),
+            },
+            false => r.xml,
+        };
+
+        let mut flags = 0;
+        if r.disk_only {
+            flags |= VIR_DOMAIN_SNAPSHOT_CREATE_DISK_ONLY;
+        }
+        if r.quiesce {
+            flags |= VIR_DOMAIN_SNAPSHOT_CREATE_QUIESCE;
+        }
+        if r.atomic {
+            flags |= VIR_DOMAIN_SNAPSHOT_CREATE_ATOMIC;
+        }
+
+        let snapshot = DomainSnapshot::create_xml(&domain, &xml, flags).and_then(|x| x.get_name());
+
+        return Ok(Response::new(match snapshot {
+            Ok(name) => libvirt_api::SnapshotResponse {
+                success: true,
+                error: None,
+                name,
+            },
+            Err(e) => libvirt_api::SnapshotResponse::failure(e.message),
+        }));
+    }
+
+    type ListSnapshotsStream = ReceiverStream<Result<libvirt_api::Snapshot, Status>>;
+
+    async fn list_snapshots(
+        &self,
+        request: Request<libvirt_api::ListSnapshotsRequest>,
+    ) -> Result<Response<Self::ListSnapshotsStream>, Status> {
+        eprintln!("list_snapshots");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain =
+            virt::domain::Domain::lookup_by_uuid_string(&self.conn.lock(), &uuid.to_string())
+                .map_err(|e| {
+                    Status::not_found(format!(
+                        "failed to look up domain with UUID '{}': {}",
+                        uuid, e.message
+                    ))
+                })?;
+
+        let snapshots = list_snapshots(&domain).map_err(Status::internal)?;
+
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            for snapshot in snapshots {
+                tx.send(Ok(snapshot)).await.unwrap();
+            }
+        });
+
+        return Ok(Response::new(ReceiverStream::new(rx)));
+    }
+
+    async fn revert_to_snapshot(
+        &self,
+        request: Request<libvirt_api::RevertToSnapshotRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("revert_to_snapshot");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        if r.running && r.paused {
+            return Err(Status::invalid_argument(
+                "running and paused are mutually exclusive",
+            ));
+        }
+
+        let mut flags = 0;
+        if r.running {
+            flags |= VIR_DOMAIN_SNAPSHOT_REVERT_RUNNING;
+        }
+        if r.paused {
+            flags |= VIR_DOMAIN_SNAPSHOT_REVERT_PAUSED;
+        }
+        if r.force {
+            flags |= VIR_DOMAIN_SNAPSHOT_REVERT_FORCE;
+        }
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        let revert = DomainSnapshot::lookup_by_name(&domain, &r.name, 0)
+            .map_err(|e| e.message)
+            .and_then(|x| revert_to_snapshot(&x, flags));
+
+        return match revert {
+            Ok(_) => self.return_success(),
+            Err(e) => self.return_failure(e),
+        };
+    }
+
+    async fn delete_snapshot(
+        &self,
+        request: Request<libvirt_api::DeleteSnapshotRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("delete_snapshot");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        if r.children && r.children_only {
+            return Err(Status::invalid_argument(
+                "children and children_only are mutually exclusive",
+            ));
+        }
+
+        let mut flags = 0;
+        if r.children {
+            flags |= VIR_DOMAIN_SNAPSHOT_DELETE_CHILDREN;
+        }
+        if r.children_only {
+            flags |= VIR_DOMAIN_SNAPSHOT_DELETE_CHILDREN_ONLY;
+        }
+        if r.metadata_only {
+            flags |= VIR_DOMAIN_SNAPSHOT_DELETE_METADATA_ONLY;
+        }
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        let delete =
+            DomainSnapshot::lookup_by_name(&domain, &r.name, 0).and_then(|x| x.delete(flags));
+
+        return match delete {
             Ok(_) => self.return_success(),
             Err(e) => self.return_failure(e.message),
         };
     }
 
-    // async fn ListUSBDevices(&self, request: Request<libvirt_api::ListUSBDevicesRequest>)
+    async fn get_snapshot_xml(
+        &self,
+        request: Request<libvirt_api::GetSnapshotXmlRequest>,
+    ) -> Result<Response<libvirt_api::SnapshotXmlResponse>, Status> {
+        eprintln!("get_snapshot_xml");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let mut flags = 0;
+        if r.secure {
+            flags |= VIR_DOMAIN_XML_SECURE;
+        }
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        let xml =
+            DomainSnapshot::lookup_by_name(&domain, &r.name, 0).and_then(|x| x.get_xml_desc(flags));
+
+        return Ok(Response::new(match xml {
+            Ok(xml) => libvirt_api::SnapshotXmlResponse {
+                success: true,
+                error: None,
+                xml,
+            },
+            Err(e) => libvirt_api::SnapshotXmlResponse::failure(e.message),
+        }));
+    }
+
+    async fn managed_save(
+        &self,
+        request: Request<libvirt_api::ManagedSaveRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("managed_save");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+        let flags = save_flags(r.options)?;
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return match domain.managed_save(flags) {
+            Ok(_) => self.return_success(),
+            Err(e) => self.return_failure(e.message),
+        };
+    }
+
+    async fn has_managed_save(
+        &self,
+        request: Request<libvirt_api::HasManagedSaveRequest>,
+    ) -> Result<Response<libvirt_api::HasManagedSaveResponse>, Status> {
+        eprintln!("has_managed_save");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return Ok(Response::new(match domain.has_managed_save(0) {
+            Ok(has_managed_save) => libvirt_api::HasManagedSaveResponse {
+                success: true,
+                error: None,
+                has_managed_save,
+            },
+            Err(e) => libvirt_api::HasManagedSaveResponse::failure(e.message),
+        }));
+    }
+
+    async fn remove_managed_save(
+        &self,
+        request: Request<libvirt_api::RemoveManagedSaveRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("remove_managed_save");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return match domain.managed_save_remove(0) {
+            Ok(_) => self.return_success(),
+            Err(e) => self.return_failure(e.message),
+        };
+    }
+
+    async fn save_domain(
+        &self,
+        request: Request<libvirt_api::SaveDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("save_domain");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+        let flags = save_flags(r.options)?;
+        check_save_path(&r.path)?;
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return match save_domain(&domain, &r.path, flags) {
+            Ok(_) => self.return_success(),
+            Err(e) => self.return_failure(e),
+        };
+    }
+
+    async fn restore_domain(
+        &self,
+        request: Request<libvirt_api::RestoreDomainRequest>,
+    ) -> Result<Response<libvirt_api::SuccessResponse>, Status> {
+        eprintln!("restore_domain");
+        let r = request.into_inner();
+        let flags = save_flags(r.options)?;
+        check_save_path(&r.path)?;
+
+        let xml = if r.xml.is_empty() {
+            None
+        } else {
+            Some(r.xml.as_str())
+        };
+
+        return match restore_domain(&self.conn.lock(), &r.path, xml, flags) {
+            Ok(_) => self.return_success(),
+            Err(e) => self.return_failure(e),
+        };
+    }
+
+    type OpenConsoleStream = ReceiverStream<Result<libvirt_api::ConsoleOutput, Status>>;
+
+    async fn open_console(
+        &self,
+        request: Request<Streaming<libvirt_api::ConsoleInput>>,
+    ) -> Result<Response<Self::OpenConsoleStream>, Status> {
+        eprintln!("open_console");
+        let mut input = request.into_inner();
+
+        let open = match input.message().await? {
+            Some(libvirt_api::ConsoleInput {
+                input: Some(libvirt_api::console_input::Input::Open(x)),
+            }) => x,
+            _ => {
+                return Err(Status::invalid_argument(
+                    "the first message must open the console",
+                ))
+            }
+        };
+        let uuid = byte_vec_to_uuid(open.uuid).unwrap();
+
+        // SAFE refuses consoles whose driver cannot detect a second session, so that force
+        // really takes over rather than interleaving two clients' input.
+        let mut flags = VIR_DOMAIN_CONSOLE_SAFE;
+        if open.force {
+            flags |= VIR_DOMAIN_CONSOLE_FORCE;
+        }
+
+        let dev_name = if open.dev_name.is_empty() {
+            None
+        } else {
+            Some(open.dev_name.as_str())
+        };
+
+        let console = {
+            let conn = self.conn.lock();
+            let domain = virt::domain::Domain::lookup_by_uuid_string(&conn, &uuid.to_string())
+                .map_err(|e| {
+                    Status::not_found(format!(
+                        "failed to look up domain with UUID '{}': {}",
+                        uuid, e.message
+                    ))
+                })?;
+
+            VirStream::open_console(&conn, &domain, dev_name, flags)
+                .map_err(Status::failed_precondition)?
+        };
+
+        return Ok(Response::new(relay_console(console, input)));
+    }
+
+    async fn get_graphics_info(
+        &self,
+        request: Request<libvirt_api::GetGraphicsInfoRequest>,
+    ) -> Result<Response<libvirt_api::GraphicsInfoResponse>, Status> {
+        eprintln!("get_graphics_info");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        // Passwords are only in the secure XML; the response just says whether one is set.
+        let devices = domain
+            .get_xml_desc(VIR_DOMAIN_XML_SECURE)
+            .map_err(|e| e.message)
+            .and_then(|xml| list_graphics_devices(&xml).map_err(|e| e.to_string()));
+
+        return Ok(Response::new(match devices {
+            Ok(devices) => libvirt_api::GraphicsInfoResponse {
+                success: true,
+                error: None,
+                devices,
+            },
+            Err(e) => libvirt_api::GraphicsInfoResponse::failure(e),
+        }));
+    }
+
+    async fn create_graphics_token(
+        &self,
+        request: Request<libvirt_api::CreateGraphicsTokenRequest>,
+    ) -> Result<Response<libvirt_api::GraphicsTokenResponse>, Status> {
+        eprintln!("create_graphics_token");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let tokens = match &self.graphics_tokens {
+            Some(x) => x.clone(),
+            None => {
+                return Err(Status::failed_precondition(format!(
+                    "the graphics proxy is disabled; set {} to enable it",
+                    GRAPHICS_PROXY_ADDR_ENV
+                )))
+            }
+        };
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        if !domain.is_active().unwrap_or(false) {
+            return Ok(Response::new(libvirt_api::GraphicsTokenResponse::failure(
+                "domain is not running".to_string(),
+            )));
+        }
+
+        let devices = domain
+            .get_xml_desc(0)
+            .map_err(|e| e.message)
+            .and_then(|xml| list_graphics_devices(&xml).map_err(|e| e.to_string()));
+
+        let error = match devices {
+            Ok(devices) => match devices.get(r.index as usize) {
+                Some(x) if x.r#type == "vnc" => None,
+                Some(x) => Some(format!(
+                    "graphics device {} is {}; only VNC can be proxied",
+                    r.index, x.r#type
+                )),
+                None => Some(format!("domain has no graphics device {}", r.index)),
+            },
+            Err(e) => Some(e),
+        };
+        if let Some(e) = error {
+            return Ok(Response::new(libvirt_api::GraphicsTokenResponse::failure(
+                e,
+            )));
+        }
+
+        return Ok(Response::new(libvirt_api::GraphicsTokenResponse {
+            success: true,
+            error: None,
+            token: tokens.issue(uuid, r.index),
+            expires_in_s: GRAPHICS_TOKEN_TTL.as_secs() as u32,
+        }));
+    }
+
+    async fn get_screenshot(
+        &self,
+        request: Request<libvirt_api::GetScreenshotRequest>,
+    ) -> Result<Response<libvirt_api::ScreenshotResponse>, Status> {
+        eprintln!("get_screenshot");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return Ok(Response::new(
+            match take_screenshot(&self.conn, &domain, r.screen) {
+                Ok(x) => libvirt_api::ScreenshotResponse {
+                    success: true,
+                    error: None,
+                    png: x.png,
+                    width: x.width,
+                    height: x.height,
+                },
+                Err(e) => libvirt_api::ScreenshotResponse::failure(e),
+            },
+        ));
+    }
+
+    async fn get_interface_addresses(
+        &self,
+        request: Request<libvirt_api::GetInterfaceAddressesRequest>,
+    ) -> Result<Response<libvirt_api::InterfaceAddressesResponse>, Status> {
+        eprintln!("get_interface_addresses");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        // virDomainInterfaceAddressesSource starts at LEASE = 0, one below the proto enum.
+        let source = match libvirt_api::InterfaceAddressSource::from_i32(r.source) {
+            Some(libvirt_api::InterfaceAddressSource::Unspecified) => 0,
+            Some(x) => x as u32 - 1,
+            None => {
+                return Err(Status::invalid_argument(format!(
+                    "unknown interface address source {}",
+                    r.source
+                )))
+            }
+        };
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return Ok(Response::new(match guest_interfaces(&domain, source) {
+            Ok(interfaces) => libvirt_api::InterfaceAddressesResponse {
+                success: true,
+                error: None,
+                agent_not_connected: false,
+                interfaces,
+            },
+            Err(e) => libvirt_api::InterfaceAddressesResponse {
+                agent_not_connected: e.not_connected,
+                ..libvirt_api::InterfaceAddressesResponse::failure(e.message)
+            },
+        }));
+    }
+
+    async fn get_guest_info(
+        &self,
+        request: Request<libvirt_api::GetGuestInfoRequest>,
+    ) -> Result<Response<libvirt_api::GuestInfoResponse>, Status> {
+        eprintln!("get_guest_info");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return Ok(Response::new(
+            match guest_info(&domain, guest_info_flags(&r.types)?) {
+                Ok(x) => x,
+                Err(e) => libvirt_api::GuestInfoResponse {
+                    agent_not_connected: e.not_connected,
+                    ..libvirt_api::GuestInfoResponse::failure(e.message)
+                },
+            },
+        ));
+    }
+
+    type GuestExecStream = ReceiverStream<Result<libvirt_api::GuestExecOutput, Status>>;
+
+    async fn guest_exec(
+        &self,
+        request: Request<libvirt_api::GuestExecRequest>,
+    ) -> Result<Response<Self::GuestExecStream>, Status> {
+        eprintln!("guest_exec");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid.clone()).unwrap();
+
+        if r.path.is_empty() {
+            return Err(Status::invalid_argument("path is required"));
+        }
+
+        let domain =
+            virt::domain::Domain::lookup_by_uuid_string(&self.conn.lock(), &uuid.to_string())
+                .map_err(|e| {
+                    Status::not_found(format!(
+                        "failed to look up domain with UUID '{}': {}",
+                        uuid, e.message
+                    ))
+                })?;
+
+        let pid = start_guest_exec(&domain, &r).map_err(|e| e.to_status())?;
+        let timeout = match r.timeout_s {
+            0 => None,
+            x => Some(Duration::from_secs(x as u64)),
+        };
+
+        return Ok(Response::new(relay_guest_exec(
+            self.conn.clone(),
+            uuid,
+            pid,
+            timeout,
+        )));
+    }
+
+    type GuestReadFileStream = ReceiverStream<Result<libvirt_api::GuestFileChunk, Status>>;
+
+    async fn guest_read_file(
+        &self,
+        request: Request<libvirt_api::GuestReadFileRequest>,
+    ) -> Result<Response<Self::GuestReadFileStream>, Status> {
+        eprintln!("guest_read_file");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        if r.path.is_empty() {
+            return Err(Status::invalid_argument("path is required"));
+        }
+
+        // Only checks that the domain exists; the agent calls look it up again.
+        virt::domain::Domain::lookup_by_uuid_string(&self.conn.lock(), &uuid.to_string()).map_err(
+            |e| {
+                Status::not_found(format!(
+                    "failed to look up domain with UUID '{}': {}",
+                    uuid, e.message
+                ))
+            },
+        )?;
+
+        let file = GuestFile::open(self.conn.clone(), uuid, &r.path, "r")
+            .await
+            .map_err(|e| e.to_status())?;
+
+        return Ok(Response::new(relay_guest_file(file)));
+    }
+
+    async fn guest_write_file(
+        &self,
+        request: Request<Streaming<libvirt_api::GuestWriteFileRequest>>,
+    ) -> Result<Response<libvirt_api::GuestWriteFileResponse>, Status> {
+        use libvirt_api::guest_write_file_request::Input;
+
+        eprintln!("guest_write_file");
+        let mut input = request.into_inner();
+
+        let open = match input.message().await? {
+            Some(libvirt_api::GuestWriteFileRequest {
+                input: Some(Input::Open(x)),
+            }) => x,
+            _ => return Err(Status::invalid_argument("the first message must be `open`")),
+        };
+        let uuid = byte_vec_to_uuid(open.uuid).unwrap();
+
+        if open.path.is_empty() {
+            return Err(Status::invalid_argument("path is required"));
+        }
+
+        // Only checks that the domain exists; Domain isn't Send, so it can't live across the awaits
+        // below, and the agent calls look it up again anyway.
+        {
+            let domain_r = self.try_get_domain(uuid);
+            if let Some(response) = domain_r.success_response {
+                return response;
+            }
+        }
+
+        let mode = if open.append { "a" } else { "w" };
+        let file = match GuestFile::open(self.conn.clone(), uuid, &open.path, mode).await {
+            Ok(x) => x,
+            Err(e) => {
+                return Ok(Response::new(libvirt_api::GuestWriteFileResponse {
+                    agent_not_connected: e.not_connected,
+                    ..libvirt_api::GuestWriteFileResponse::failure(e.message)
+                }))
+            }
+        };
+
+        let mut bytes_written = 0;
+        let mut result = Ok(());
+        loop {
+            let data = match input.message().await {
+                Ok(Some(libvirt_api::GuestWriteFileRequest {
+                    input: Some(Input::Data(x)),
+                })) => x,
+                Ok(None) => break,
+                Ok(Some(_)) => {
+                    result = Err(AgentError::new("`open` may only be sent once".to_string()));
+                    break;
+                }
+                Err(e) => {
+                    result = Err(AgentError::new(format!(
+                        "upload interrupted: {}",
+                        e.message()
+                    )));
+                    break;
+                }
+            };
+
+            if let Err(e) = file.write_all(&data).await {
+                result = Err(e);
+                break;
+            }
+            bytes_written += data.len() as u64;
+        }
+
+        // Close even after a failure so the guest doesn't keep the handle open.
+        let result = result.and(file.close().await);
+
+        return Ok(Response::new(match result {
+            Ok(()) => libvirt_api::GuestWriteFileResponse {
+                success: true,
+                bytes_written,
+                ..Default::default()
+            },
+            Err(e) => libvirt_api::GuestWriteFileResponse {
+                agent_not_connected: e.not_connected,
+                bytes_written,
+                ..libvirt_api::GuestWriteFileResponse::failure(e.message)
+            },
+        }));
+    }
+
+    async fn fs_freeze(
+        &self,
+        request: Request<libvirt_api::FsFreezeRequest>,
+    ) -> Result<Response<libvirt_api::FsFreezeResponse>, Status> {
+        eprintln!("fs_freeze");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return Ok(Response::new(fs_freeze_response(freeze_filesystems(
+            &domain,
+            &r.mountpoints,
+        ))));
+    }
+
+    async fn fs_thaw(
+        &self,
+        request: Request<libvirt_api::FsThawRequest>,
+    ) -> Result<Response<libvirt_api::FsFreezeResponse>, Status> {
+        eprintln!("fs_thaw");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        if !r.mountpoints.is_empty() {
+            return Err(Status::invalid_argument(
+                "mountpoints must be empty: QEMU can only thaw every frozen filesystem at once",
+            ));
+        }
+
+        let domain_r = self.try_get_domain(uuid);
+        if let Some(response) = domain_r.success_response {
+            return response;
+        }
+        let domain = domain_r.domain.unwrap();
+
+        return Ok(Response::new(fs_freeze_response(thaw_filesystems(&domain))));
+    }
+
+    async fn create_quiesced_snapshot(
+        &self,
+        request: Request<libvirt_api::CreateQuiescedSnapshotRequest>,
+    ) -> Result<Response<libvirt_api::QuiescedSnapshotResponse>, Status> {
+        eprintln!("create_quiesced_snapshot");
+        let r = request.into_inner();
+        let uuid = byte_vec_to_uuid(r.uuid).unwrap();
+
+        let xml = match r.xml.is_empty() {
+            true => match snapshot_xml(&r.name, &r.description) {
+                Ok(x) => x,
+                Err(e) => return Err(Status::invalid_argument(e.to_string())),
+            },
+            false => r.xml,
+        };
+
+        // The filesystems are already frozen, so libvirt is not asked to quiesce them again.
+        let mut flags = VIR_DOMAIN_SNAPSHOT_CREATE_DISK_ONLY;
+        if r.atomic {
+            flags |= VIR_DOMAIN_SNAPSHOT_CREATE_ATOMIC;
+        }
+
+        return Ok(Response::new(
+            quiesced_snapshot(self.conn.clone(), uuid, xml, flags, r.mountpoints).await,
+        ));
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let addr = "[::1]:50051".parse()?;
-    let service = LibvirtAPIService::new("qemu:///system");
+    // libvirt only delivers domain events to connections opened after its event loop exists.
+    start_event_loop();
+    let usb_rules_path =
+        std::env::var(USB_RULES_PATH_ENV).unwrap_or_else(|_| DEFAULT_USB_RULES_PATH.to_string());
+    let mut service = LibvirtAPIService::new("qemu:///system", &usb_rules_path)?;
+
+    spawn_rule_engine(
+        service.conn.clone(),
+        service.usb_rules.clone(),
+        service.events.subscribe(),
+    );
+
+    let rpc_metrics = Arc::new(RPCMetrics::default());
+    if let Ok(metrics_addr) = std::env::var(METRICS_ADDR_ENV) {
+        let metrics_addr = metrics_addr.parse()?;
+        let conn = service.conn.clone();
+        let rpc_metrics = rpc_metrics.clone();
+
+        println!("Serving metrics on http://{}/metrics", metrics_addr);
+        tokio::spawn(async move {
+            if let Err(e) = serve_metrics(metrics_addr, conn, rpc_metrics).await {
+                eprintln!("metrics listener failed: {}", e);
+            }
+        });
+    }
+
+    if let Ok(proxy_addr) = std::env::var(GRAPHICS_PROXY_ADDR_ENV) {
+        let proxy_addr = proxy_addr.parse()?;
+        let conn = service.conn.clone();
+        let tokens = Arc::new(GraphicsTokens::default());
+        service.graphics_tokens = Some(tokens.clone());
+
+        println!("Serving graphics proxy on ws://{}/", proxy_addr);
+        tokio::spawn(async move {
+            if let Err(e) = serve_graphics_proxy(proxy_addr, conn, tokens).await {
+                eprintln!("graphics proxy failed: {}", e);
+            }
+        });
+    }
 
     println!("Listening");
 
     Server::builder()
-        .add_service(LibvirtApiServer::new(service))
+        .add_service(MeteredService::new(
+            LibvirtApiServer::new(service),
+            rpc_metrics,
+        ))
         .serve(addr)
         .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_flags_ors_modes() {
+        assert_eq!(mode_flags(&[]).unwrap(), 0);
+        assert_eq!(
+            mode_flags(&[
+                libvirt_api::ShutdownMode::AcpiPowerBtn as i32,
+                libvirt_api::ShutdownMode::GuestAgent as i32,
+            ])
+            .unwrap(),
+            1 | 2
+        );
+    }
+
+    #[test]
+    fn mode_flags_rejects_unknown_modes() {
+        let e = mode_flags(&[1, 32]).unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn guest_info_flags_validates_types() {
+        assert_eq!(
+            guest_info_flags(&[
+                libvirt_api::GuestInfoType::Os as i32,
+                libvirt_api::GuestInfoType::Disks as i32,
+            ])
+            .unwrap(),
+            2 | 32
+        );
+
+        let e = guest_info_flags(&[64]).unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn scope_flags_maps_scopes() {
+        use libvirt_api::DeviceScope;
+
+        assert_eq!(
+            scope_flags(DeviceScope::Unspecified as i32).unwrap(),
+            VIR_DOMAIN_AFFECT_LIVE
+        );
+        assert_eq!(
+            scope_flags(DeviceScope::Current as i32).unwrap(),
+            VIR_DOMAIN_AFFECT_CURRENT
+        );
+        assert_eq!(
+            scope_flags(DeviceScope::Live as i32).unwrap(),
+            VIR_DOMAIN_AFFECT_LIVE
+        );
+        assert_eq!(
+            scope_flags(DeviceScope::Config as i32).unwrap(),
+            VIR_DOMAIN_AFFECT_CONFIG
+        );
+        assert_eq!(
+            scope_flags(DeviceScope::Both as i32).unwrap(),
+            VIR_DOMAIN_AFFECT_LIVE | VIR_DOMAIN_AFFECT_CONFIG
+        );
+    }
+
+    #[test]
+    fn scope_flags_rejects_unknown_scopes() {
+        let e = scope_flags(5).unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn uuid_list_rejects_malformed_entries() {
+        let uuid = Uuid::parse_str(PINNED_UUID).unwrap();
+
+        assert_eq!(
+            uuid_list(&[uuid.as_bytes().to_vec()], "uuids").unwrap(),
+            vec![uuid]
+        );
+        assert!(uuid_list(&[], "uuids").unwrap().is_empty());
+
+        let e = uuid_list(&[uuid.as_bytes().to_vec(), vec![1, 2, 3]], "uuids").unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    const PINNED_UUID: &str = "6c8fb1b6-3a8e-4d5a-9d4f-2e7c0b3f9a41";
+
+    #[test]
+    fn pin_domain_uuid_fills_in_a_missing_uuid() {
+        let uuid = Uuid::parse_str(PINNED_UUID).unwrap();
+        let xml = pin_domain_uuid("<domain type='kvm'><name>vm</name></domain>", uuid).unwrap();
+
+        let root = parse_element(&xml).unwrap();
+        assert_eq!(
+            root.get_child("uuid").unwrap().get_text().unwrap(),
+            PINNED_UUID
+        );
+        assert_eq!(root.get_child("name").unwrap().get_text().unwrap(), "vm");
+    }
+
+    #[test]
+    fn pin_domain_uuid_keeps_a_matching_uuid() {
+        let uuid = Uuid::parse_str(PINNED_UUID).unwrap();
+        let xml = format!(
+            "<domain><uuid> {} </uuid></domain>",
+            PINNED_UUID.to_uppercase()
+        );
+
+        assert_eq!(pin_domain_uuid(&xml, uuid).unwrap(), xml);
+    }
+
+    #[test]
+    fn pin_domain_uuid_rejects_other_domains() {
+        let uuid = Uuid::parse_str(PINNED_UUID).unwrap();
+
+        let other = "<domain><uuid>00000000-0000-0000-0000-000000000001</uuid></domain>";
+        assert!(pin_domain_uuid(other, uuid).is_err());
+        assert!(pin_domain_uuid("<domain><uuid>nope</uuid></domain>", uuid).is_err());
+        assert!(pin_domain_uuid("<domain>", uuid).is_err());
+    }
+
+    #[test]
+    fn save_flags_maps_options() {
+        assert_eq!(save_flags(None).unwrap(), 0);
+        assert_eq!(
+            save_flags(Some(libvirt_api::SaveOptions {
+                bypass_cache: true,
+                running: true,
+                paused: false,
+            }))
+            .unwrap(),
+            VIR_DOMAIN_SAVE_BYPASS_CACHE | VIR_DOMAIN_SAVE_RUNNING
+        );
+        assert_eq!(
+            save_flags(Some(libvirt_api::SaveOptions {
+                paused: true,
+                ..Default::default()
+            }))
+            .unwrap(),
+            VIR_DOMAIN_SAVE_PAUSED
+        );
+    }
+
+    #[test]
+    fn save_flags_rejects_running_and_paused() {
+        let e = save_flags(Some(libvirt_api::SaveOptions {
+            running: true,
+            paused: true,
+            ..Default::default()
+        }))
+        .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn check_save_path_requires_absolute_paths() {
+        assert!(check_save_path("/var/lib/libvirt/save/vm.img").is_ok());
+
+        for path in &["vm.img", "./vm.img", "../save/vm.img", ""] {
+            let e = check_save_path(path).unwrap_err();
+            assert_eq!(e.code(), tonic::Code::InvalidArgument);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use virt::domain::Domain;
+use virt::domain_snapshot::DomainSnapshot;
+use xmltree::{Element, XMLNode};
+
+use crate::protoc::libvirt_api;
+use crate::schema::domain_xml::{parse_element, write_element, DomainXMLError};
+use crate::virt_sys::list_snapshot_names;
+
+// Builds a minimal <domainsnapshot> for libvirt to fill in; an empty name lets libvirt pick one
+// from the current time.
+pub fn snapshot_xml(name: &str, description: &str) -> Result<String, DomainXMLError> {
+    let mut root = Element::new("domainsnapshot");
+
+    for (tag, value) in [("name", name), ("description", description)].iter() {
+        if value.is_empty() {
+            continue;
+        }
+
+        let mut e = Element::new(tag);
+        e.children.push(XMLNode::Text(value.to_string()));
+        root.children.push(XMLNode::Element(e));
+    }
+
+    write_element(&root)
+}
+
+fn child_text(e: &Element, name: &str) -> Option<String> {
+    e.get_child(name)
+        .and_then(|x| x.get_text())
+        .map(|x| x.to_string())
+}
+
+// Everything ListSnapshots reports except `children` and `current`, which need the other
+// snapshots of the domain.
+fn parse_snapshot(xml: &str) -> Result<libvirt_api::Snapshot, DomainXMLError> {
+    let root = parse_element(xml)?;
+
+    Ok(libvirt_api::Snapshot {
+        name: child_text(&root, "name").unwrap_or_default(),
+        description: child_text(&root, "description").unwrap_or_default(),
+        parent: root.get_child("parent").and_then(|x| child_text(x, "name")),
+        children: vec![],
+        state: child_text(&root, "state").unwrap_or_default(),
+        creation_time: child_text(&root, "creationTime")
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(0),
+        current: false,
+    })
+}
+
+// All of the domain's snapshots with their place in the snapshot tree, oldest first.
+pub fn list_snapshots(domain: &Domain) -> Result<Vec<libvirt_api::Snapshot>, String> {
+    let mut snapshots = vec![];
+
+    for name in list_snapshot_names(domain)? {
+        let xml = DomainSnapshot::lookup_by_name(domain, &name, 0)
+            .and_then(|x| x.get_xml_desc(0))
+            .map_err(|e| e.message)?;
+
+        snapshots.push(parse_snapshot(&xml).map_err(|e| e.to_string())?);
+    }
+
+    let current = DomainSnapshot::current(domain, 0)
+        .and_then(|x| x.get_name())
+        .ok();
+
+    let mut children: HashMap<String, Vec<String>> = HashMap::new();
+    for snapshot in snapshots.iter() {
+        if let Some(parent) = &snapshot.parent {
+            children
+                .entry(parent.clone())
+                .or_default()
+                .push(snapshot.name.clone());
+        }
+    }
+
+    for snapshot in snapshots.iter_mut() {
+        snapshot.children = children.remove(&snapshot.name).unwrap_or_default();
+        snapshot.current = current.as_ref() == Some(&snapshot.name);
+    }
+
+    snapshots.sort_by(|a, b| {
+        a.creation_time
+            .cmp(&b.creation_time)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(snapshots)
+}
@@ -1,6 +1,6 @@
 extern crate libudev;
 
-mod schema;
+use libvirt_grpc_api::schema;
 
 use std::any::Any;
 
@@ -47,6 +47,20 @@ pub fn main() {
                 true => Some(props["ID_MODEL_FROM_DATABASE"].clone()),
                 false => None,
             },
+            bus: props
+                .get("BUSNUM")
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(0),
+            device_number: props
+                .get("DEVNUM")
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(0),
+            port_path: device
+                .sysname()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            serial: props.get("ID_SERIAL_SHORT").cloned(),
+            attached_to: None,
         };
 
         result.push(this);
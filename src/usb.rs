@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::error;
+
+use tonic::Status;
+use uuid::Uuid;
+use virt::connect::Connect;
+use virt::domain::Domain;
+
+use libvirt_grpc_api::schema::schema::{USBDevice, USBDeviceAction};
+use libvirt_grpc_api::{enumerate_usb_devices, normalize_usb_id};
+
+use crate::domain_devices::find_host_device;
+use crate::protoc::libvirt_api;
+use crate::protoc::libvirt_api::usb_device_selector::Selector;
+use crate::schema::domain_xml::{Address, Device, DomainXML, DomainXMLError, HostDev, XMLElement};
+
+pub fn to_proto_usb_device(device: USBDevice) -> libvirt_api::UsbDevice {
+    libvirt_api::UsbDevice {
+        device: device.device,
+        vendor_id: device.vendor_id,
+        product_id: device.product_id,
+        model: device.model,
+        vendor_name: device.vendor_name,
+        model_name: device.model_name,
+        bus: device.bus,
+        device_number: device.device_number,
+        port_path: device.port_path,
+        serial: device.serial,
+        attached_to: vec![],
+    }
+}
+
+pub fn to_proto_usb_action(action: USBDeviceAction) -> i32 {
+    let action = match action {
+        USBDeviceAction::Present => libvirt_api::UsbDeviceAction::Present,
+        USBDeviceAction::Add => libvirt_api::UsbDeviceAction::Add,
+        USBDeviceAction::Change => libvirt_api::UsbDeviceAction::Change,
+        USBDeviceAction::Remove => libvirt_api::UsbDeviceAction::Remove,
+    };
+
+    action as i32
+}
+
+pub fn list_host_devices() -> Result<Vec<libvirt_api::UsbDevice>, Box<dyn error::Error>> {
+    let devices = enumerate_usb_devices()?;
+
+    Ok(devices.into_iter().map(to_proto_usb_device).collect())
+}
+
+// Maps the bus/device address of every host device that some domain's hostdevs point at to that
+// domain. Running domains are checked against their live XML, others against their persistent
+// config, so a stopped domain keeps its claim on a device it will grab when it starts.
+pub fn usb_device_owners(
+    conn: &Connect,
+    host_devices: &[libvirt_api::UsbDevice],
+) -> HashMap<(u32, u32), Uuid> {
+    let mut owners = HashMap::new();
+
+    let domains = match conn.list_all_domains(0) {
+        Ok(x) => x,
+        Err(_) => return owners,
+    };
+
+    for domain in domains {
+        let uuid = match domain
+            .get_uuid_string()
+            .ok()
+            .and_then(|x| Uuid::parse_str(&x).ok())
+        {
+            Some(x) => x,
+            None => continue,
+        };
+        let xml = match domain.get_xml_desc(0) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let devices = match DomainXML::from_xml(&xml) {
+            Ok(x) => x.devices.map(|x| x.devices).unwrap_or_default(),
+            Err(_) => continue,
+        };
+
+        for device in devices {
+            if let Device::HostDev(hostdev) = device {
+                if let Some(x) = find_host_device(&hostdev, host_devices) {
+                    owners.insert((x.bus, x.device_number), uuid);
+                }
+            }
+        }
+    }
+
+    owners
+}
+
+// virDomainModificationImpact, the flags attach_to and detach_from take.
+pub const VIR_DOMAIN_AFFECT_CURRENT: u32 = 0;
+pub const VIR_DOMAIN_AFFECT_LIVE: u32 = 1 << 0;
+pub const VIR_DOMAIN_AFFECT_CONFIG: u32 = 1 << 1;
+
+// The device a hostdev should point at, resolved from an attach/detach request.
+pub struct USBTarget {
+    pub vendor_id: String,
+    pub product_id: String,
+    // Only set when the request picked a specific physical device; a bare vendor:product hostdev
+    // keeps following the device across replugs.
+    pub bus_address: Option<(u32, u32)>,
+    // The plugged-in device the request resolved to, if any.
+    pub host_device: Option<libvirt_api::UsbDevice>,
+}
+
+impl USBTarget {
+    // Pins the hostdev to one plugged-in device by its current bus address.
+    pub fn for_host_device(device: &libvirt_api::UsbDevice) -> USBTarget {
+        USBTarget {
+            vendor_id: device.vendor_id.to_ascii_lowercase(),
+            product_id: device.product_id.to_ascii_lowercase(),
+            bus_address: Some((device.bus, device.device_number)),
+            host_device: Some(device.clone()),
+        }
+    }
+
+    pub fn to_hostdev(&self) -> HostDev {
+        let mut hostdev = HostDev::usb(
+            format!("0x{}", self.vendor_id),
+            format!("0x{}", self.product_id),
+        );
+
+        if let Some((bus, device)) = self.bus_address {
+            hostdev.source.address = Some(Address::usb_host(bus, device));
+        }
+
+        hostdev
+    }
+
+    pub fn to_hostdev_xml(&self) -> Result<String, DomainXMLError> {
+        self.to_hostdev().to_xml()
+    }
+
+    pub fn attach_to(&self, domain: &Domain, flags: u32) -> Result<(), String> {
+        let hostdev = self.to_hostdev_xml().map_err(|e| e.to_string())?;
+
+        domain
+            .attach_device_flags(&hostdev, flags)
+            .map(|_| ())
+            .map_err(|e| e.message)
+    }
+
+    pub fn detach_from(&self, domain: &Domain, flags: u32) -> Result<(), String> {
+        let hostdev = self.to_hostdev_xml().map_err(|e| e.to_string())?;
+
+        domain
+            .detach_device_flags(&hostdev, flags)
+            .map(|_| ())
+            .map_err(|e| e.message)
+    }
+}
+
+// Refuses to hand a device to `uuid` while another domain's hostdevs already claim it.
+pub fn check_usb_owner(conn: &Connect, uuid: Uuid, target: &USBTarget) -> Result<(), Status> {
+    let device = match &target.host_device {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+
+    let owners = usb_device_owners(conn, std::slice::from_ref(device));
+
+    match owners.get(&(device.bus, device.device_number)) {
+        Some(owner) if *owner != uuid => Err(Status::failed_precondition(format!(
+            "USB device {}:{} on port {} is already attached to domain '{}'",
+            device.vendor_id, device.product_id, device.port_path, owner
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn optional_usb_id(field: &str, id: &str) -> Result<Option<String>, Status> {
+    if id.is_empty() {
+        return Ok(None);
+    }
+
+    match normalize_usb_id(id) {
+        Ok(x) => Ok(Some(x)),
+        Err(e) => Err(Status::invalid_argument(format!("{}: {}", field, e))),
+    }
+}
+
+// Matches the request against the devices currently plugged into the host. A selector must match
+// exactly one device; a bare vendor:product may match none (libvirt then reports the error) but
+// not several, since libvirt would silently pick one of them.
+pub fn resolve_usb_target(
+    vendor_id: &str,
+    product_id: &str,
+    selector: Option<libvirt_api::UsbDeviceSelector>,
+) -> Result<USBTarget, Status> {
+    let vendor_id = optional_usb_id("vendor_id", vendor_id)?;
+    let product_id = optional_usb_id("product_id", product_id)?;
+    let selector = selector.and_then(|x| x.selector);
+
+    if selector.is_none() && (vendor_id.is_none() || product_id.is_none()) {
+        return Err(Status::invalid_argument(
+            "vendor_id and product_id are required when no selector is given",
+        ));
+    }
+
+    let devices = match list_host_devices() {
+        Ok(x) => x,
+        Err(e) => {
+            return Err(Status::internal(format!(
+                "failed to enumerate USB devices: {}",
+                e
+            )))
+        }
+    };
+
+    let matches = devices
+        .into_iter()
+        .filter(|x| match &vendor_id {
+            Some(id) => x.vendor_id.to_ascii_lowercase() == *id,
+            None => true,
+        })
+        .filter(|x| match &product_id {
+            Some(id) => x.product_id.to_ascii_lowercase() == *id,
+            None => true,
+        })
+        .filter(|x| match &selector {
+            Some(Selector::BusAddress(a)) => x.bus == a.bus && x.device_number == a.device,
+            Some(Selector::PortPath(p)) => x.port_path == *p,
+            Some(Selector::Serial(s)) => x.serial.as_ref() == Some(s),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+
+    match (matches.len(), &selector) {
+        (1, _) => Ok(USBTarget {
+            vendor_id: matches[0].vendor_id.to_ascii_lowercase(),
+            product_id: matches[0].product_id.to_ascii_lowercase(),
+            bus_address: selector.map(|_| (matches[0].bus, matches[0].device_number)),
+            host_device: Some(matches[0].clone()),
+        }),
+        (0, None) => Ok(USBTarget {
+            vendor_id: vendor_id.unwrap(),
+            product_id: product_id.unwrap(),
+            bus_address: None,
+            host_device: None,
+        }),
+        (0, Some(_)) => Err(Status::not_found(
+            "no plugged-in USB device matches the selector",
+        )),
+        (n, _) => Err(Status::failed_precondition(format!(
+            "{} USB devices match; pick one by bus address, port path or serial: {}",
+            n,
+            matches
+                .iter()
+                .map(|x| format!(
+                    "{} {}:{} {} (port {})",
+                    x.device, x.vendor_id, x.product_id, x.model, x.port_path
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashSet;
+use std::error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+use virt::connect::{Connect, VIR_CONNECT_LIST_DOMAINS_ACTIVE};
+
+use libvirt_grpc_api::schema::schema::{USBDevice, USBDeviceAction};
+use libvirt_grpc_api::{normalize_usb_id, watch_usb_devices};
+
+use crate::protoc::libvirt_api;
+use crate::protoc::libvirt_api::domain_event::Event;
+use crate::thread_safe_virt_conn::ThreadSafeVirtConn;
+use crate::usb::{
+    list_host_devices, to_proto_usb_device, usb_device_owners, USBTarget, VIR_DOMAIN_AFFECT_LIVE,
+};
+
+// Hands every plugged-in device that matches all of the set criteria to `domain_uuid` whenever
+// that domain is running. Attachments are live only: a persistent hostdev would stop the domain
+// from starting while the device is unplugged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct USBRule {
+    pub id: Uuid,
+    pub domain_uuid: Uuid,
+    pub vendor_id: Option<String>,
+    pub product_id: Option<String>,
+    pub serial: Option<String>,
+    pub port_path: Option<String>,
+}
+
+impl USBRule {
+    // Drops empty criteria, normalizes IDs and rejects rules that would match every device.
+    pub fn normalized(self) -> Result<USBRule, String> {
+        let non_empty = |x: Option<String>| x.filter(|x| !x.is_empty());
+        let usb_id = |field: &str, x: Option<String>| match non_empty(x) {
+            Some(id) => normalize_usb_id(&id)
+                .map(Some)
+                .map_err(|e| format!("{}: {}", field, e)),
+            None => Ok(None),
+        };
+
+        let rule = USBRule {
+            id: self.id,
+            domain_uuid: self.domain_uuid,
+            vendor_id: usb_id("vendor_id", self.vendor_id)?,
+            product_id: usb_id("product_id", self.product_id)?,
+            serial: non_empty(self.serial),
+            port_path: non_empty(self.port_path),
+        };
+
+        if rule.vendor_id.is_none()
+            && rule.product_id.is_none()
+            && rule.serial.is_none()
+            && rule.port_path.is_none()
+        {
+            return Err(
+                "a rule needs at least one of vendor_id, product_id, serial or port_path".into(),
+            );
+        }
+
+        Ok(rule)
+    }
+
+    pub fn matches(&self, device: &libvirt_api::UsbDevice) -> bool {
+        let same = |expected: &Option<String>, actual: &str| match expected {
+            Some(x) => x.eq_ignore_ascii_case(actual),
+            None => true,
+        };
+
+        same(&self.vendor_id, &device.vendor_id)
+            && same(&self.product_id, &device.product_id)
+            && same(&self.port_path, &device.port_path)
+            && match &self.serial {
+                Some(x) => device.serial.as_ref() == Some(x),
+                None => true,
+            }
+    }
+}
+
+// The rule set, kept in memory and written back to a JSON file on every change.
+pub struct USBRuleStore {
+    path: PathBuf,
+    rules: Mutex<Vec<USBRule>>,
+}
+
+impl USBRuleStore {
+    pub fn open(path: PathBuf) -> Result<USBRuleStore, Box<dyn error::Error>> {
+        let rules = match fs::read_to_string(&path) {
+            Ok(x) => serde_json::from_str(&x)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(USBRuleStore {
+            path,
+            rules: Mutex::new(rules),
+        })
+    }
+
+    pub fn list(&self) -> Vec<USBRule> {
+        self.rules.lock().unwrap().clone()
+    }
+
+    pub fn matching(&self, device: &libvirt_api::UsbDevice) -> Vec<USBRule> {
+        self.list()
+            .into_iter()
+            .filter(|x| x.matches(device))
+            .collect()
+    }
+
+    pub fn create(&self, rule: USBRule) -> Result<USBRule, String> {
+        let rule = USBRule {
+            id: Uuid::new_v4(),
+            ..rule
+        };
+
+        self.modify(|rules| {
+            rules.push(rule.clone());
+            Ok(())
+        })?;
+
+        Ok(rule)
+    }
+
+    pub fn update(&self, rule: USBRule) -> Result<USBRule, String> {
+        self.modify(|rules| match rules.iter_mut().find(|x| x.id == rule.id) {
+            Some(x) => {
+                *x = rule.clone();
+                Ok(())
+            }
+            None => Err(format!("no USB rule with ID '{}'", rule.id)),
+        })?;
+
+        Ok(rule)
+    }
+
+    pub fn delete(&self, id: Uuid) -> Result<(), String> {
+        self.modify(|rules| match rules.iter().position(|x| x.id == id) {
+            Some(i) => {
+                rules.remove(i);
+                Ok(())
+            }
+            None => Err(format!("no USB rule with ID '{}'", id)),
+        })
+    }
+
+    // Applies `op` to a copy of the rules and only keeps the result once it is on disk, so a
+    // failed write never leaves memory and file disagreeing.
+    fn modify<F: FnOnce(&mut Vec<USBRule>) -> Result<(), String>>(
+        &self,
+        op: F,
+    ) -> Result<(), String> {
+        let mut rules = self.rules.lock().unwrap();
+        let mut updated = rules.clone();
+
+        op(&mut updated)?;
+
+        self.save(&updated).map_err(|e| {
+            format!(
+                "failed to save USB rules to '{}': {}",
+                self.path.display(),
+                e
+            )
+        })?;
+        *rules = updated;
+
+        Ok(())
+    }
+
+    fn save(&self, rules: &[USBRule]) -> Result<(), Box<dyn error::Error>> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(rules)?)?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+}
+
+// Attaches `device` to the rule's domain if that domain is running and nothing claims the device
+// yet. Failures are only logged: there is no caller to report them to.
+fn apply_rule(conn: &Connect, rule: &USBRule, device: &libvirt_api::UsbDevice) {
+    let domain =
+        match virt::domain::Domain::lookup_by_uuid_string(conn, &rule.domain_uuid.to_string()) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("usb rule {}: {}", rule.id, e.message);
+                return;
+            }
+        };
+
+    if !domain.is_active().unwrap_or(false) {
+        return;
+    }
+
+    let owners = usb_device_owners(conn, std::slice::from_ref(device));
+    match owners.get(&(device.bus, device.device_number)) {
+        Some(owner) if *owner == rule.domain_uuid => return,
+        Some(owner) => {
+            eprintln!(
+                "usb rule {}: {}:{} on port {} is already attached to domain '{}'",
+                rule.id, device.vendor_id, device.product_id, device.port_path, owner
+            );
+            return;
+        }
+        None => {}
+    }
+
+    if let Err(e) = USBTarget::for_host_device(device).attach_to(&domain, VIR_DOMAIN_AFFECT_LIVE) {
+        eprintln!("usb rule {}: {}", rule.id, e);
+    }
+}
+
+// Drops the live hostdev a rule created once its device is unplugged, so the domain doesn't keep
+// pointing at a bus address the next device plugged in may reuse.
+fn release_rule(conn: &Connect, rule: &USBRule, device: &libvirt_api::UsbDevice) {
+    let owners = usb_device_owners(conn, std::slice::from_ref(device));
+    if owners.get(&(device.bus, device.device_number)) != Some(&rule.domain_uuid) {
+        return;
+    }
+
+    if let Ok(domain) =
+        virt::domain::Domain::lookup_by_uuid_string(conn, &rule.domain_uuid.to_string())
+    {
+        let _ = USBTarget::for_host_device(device).detach_from(&domain, VIR_DOMAIN_AFFECT_LIVE);
+    }
+}
+
+fn on_device_event(
+    conn: &ThreadSafeVirtConn,
+    rules: &USBRuleStore,
+    action: USBDeviceAction,
+    device: &libvirt_api::UsbDevice,
+) {
+    for rule in rules.matching(device) {
+        match action {
+            USBDeviceAction::Present | USBDeviceAction::Add => {
+                apply_rule(&conn.lock(), &rule, device)
+            }
+            USBDeviceAction::Remove => release_rule(&conn.lock(), &rule, device),
+            USBDeviceAction::Change => {}
+        }
+    }
+}
+
+fn on_domain_started(conn: &ThreadSafeVirtConn, rules: &USBRuleStore, uuid: Uuid) {
+    let rules = rules
+        .list()
+        .into_iter()
+        .filter(|x| x.domain_uuid == uuid)
+        .collect::<Vec<_>>();
+
+    if rules.is_empty() {
+        return;
+    }
+
+    let devices = match list_host_devices() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("usb rules: failed to enumerate USB devices: {}", e);
+            return;
+        }
+    };
+
+    for device in devices {
+        for rule in rules.iter().filter(|x| x.matches(&device)) {
+            apply_rule(&conn.lock(), rule, &device);
+        }
+    }
+}
+
+fn active_domains(conn: &Connect) -> HashSet<Uuid> {
+    conn.list_all_domains(VIR_CONNECT_LIST_DOMAINS_ACTIVE)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|x| x.get_uuid_string().ok())
+        .filter_map(|x| Uuid::parse_str(&x).ok())
+        .collect()
+}
+
+// Starts the background work that applies the rules: a thread following udev hotplug events (which
+// also handles devices already plugged in at startup), and a task applying a domain's rules when
+// `events` reports that it started.
+pub fn spawn_rule_engine(
+    conn: Arc<ThreadSafeVirtConn>,
+    rules: Arc<USBRuleStore>,
+    mut events: broadcast::Receiver<libvirt_api::DomainEvent>,
+) {
+    {
+        let conn = conn.clone();
+        let rules = rules.clone();
+
+        thread::spawn(move || {
+            let on_event = |action: USBDeviceAction, device: USBDevice| {
+                on_device_event(&conn, &rules, action, &to_proto_usb_device(device));
+                true
+            };
+
+            if let Err(e) = watch_usb_devices(on_event, || false) {
+                eprintln!("usb rules: udev monitor stopped: {}", e);
+            }
+        });
+    }
+
+    // Domains already running at startup are covered by the `Present` events above.
+    tokio::spawn(async move {
+        let started = libvirt_api::DomainLifecycleEventType::Started as i32;
+
+        loop {
+            // None when events were dropped, in which case every running domain is gone over.
+            let uuid = match events.recv().await {
+                Ok(libvirt_api::DomainEvent {
+                    uuid,
+                    event: Some(Event::Lifecycle(x)),
+                    ..
+                }) if x.r#type == started => match Uuid::from_slice(&uuid) {
+                    Ok(x) => Some(x),
+                    Err(_) => continue,
+                },
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => None,
+                Err(RecvError::Closed) => return,
+            };
+
+            let conn = conn.clone();
+            let rules = rules.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                let uuids = match uuid {
+                    Some(x) => vec![x],
+                    None => active_domains(&conn.lock()).into_iter().collect(),
+                };
+
+                for uuid in uuids {
+                    on_domain_started(&conn, &rules, uuid);
+                }
+            })
+            .await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        vendor_id: Option<&str>,
+        product_id: Option<&str>,
+        serial: Option<&str>,
+        port_path: Option<&str>,
+    ) -> USBRule {
+        USBRule {
+            id: Uuid::nil(),
+            domain_uuid: Uuid::nil(),
+            vendor_id: vendor_id.map(String::from),
+            product_id: product_id.map(String::from),
+            serial: serial.map(String::from),
+            port_path: port_path.map(String::from),
+        }
+    }
+
+    fn device(vendor_id: &str, product_id: &str, serial: Option<&str>) -> libvirt_api::UsbDevice {
+        libvirt_api::UsbDevice {
+            vendor_id: vendor_id.to_string(),
+            product_id: product_id.to_string(),
+            port_path: "1-2".to_string(),
+            serial: serial.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn normalized_rejects_match_all_rules() {
+        assert!(rule(None, None, None, None).normalized().is_err());
+        assert!(rule(Some(""), Some(""), Some(""), Some(""))
+            .normalized()
+            .is_err());
+    }
+
+    #[test]
+    fn normalized_cleans_up_criteria() {
+        let normalized = rule(Some("0x046D"), Some(""), Some("ABC123"), None)
+            .normalized()
+            .unwrap();
+
+        assert_eq!(normalized.vendor_id.as_deref(), Some("046d"));
+        assert_eq!(normalized.product_id, None);
+        assert_eq!(normalized.serial.as_deref(), Some("ABC123"));
+
+        assert!(rule(Some("46d"), None, None, None).normalized().is_err());
+    }
+
+    #[test]
+    fn matches_ids_case_insensitively() {
+        let rule = rule(Some("046d"), Some("c52b"), None, None);
+
+        assert!(rule.matches(&device("046D", "C52B", None)));
+        assert!(!rule.matches(&device("046d", "c52c", None)));
+    }
+
+    #[test]
+    fn matches_serial_exactly() {
+        let rule = rule(None, None, Some("ABC123"), Some("1-2"));
+
+        assert!(rule.matches(&device("046d", "c52b", Some("ABC123"))));
+        assert!(!rule.matches(&device("046d", "c52b", Some("abc123"))));
+        assert!(!rule.matches(&device("046d", "c52b", None)));
+    }
+}
@@ -0,0 +1,723 @@
+// Raw libvirt bindings for the parts of the API the virt crate does not wrap.
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_double, c_int, c_longlong, c_uint, c_ulonglong, c_void};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use uuid::Uuid;
+use virt::connect::Connect;
+use virt::domain::Domain;
+use virt::domain_snapshot::DomainSnapshot;
+
+pub type VirConnectPtr = *mut c_void;
+pub type VirDomainPtr = *mut c_void;
+type VirDomainSnapshotPtr = *mut c_void;
+type VirStreamPtr = *mut c_void;
+
+const VIR_UUID_STRING_BUFLEN: usize = 37;
+const VIR_TYPED_PARAM_FIELD_LENGTH: usize = 80;
+
+// virTypedParameterType
+const VIR_TYPED_PARAM_INT: c_int = 1;
+const VIR_TYPED_PARAM_UINT: c_int = 2;
+const VIR_TYPED_PARAM_LLONG: c_int = 3;
+const VIR_TYPED_PARAM_ULLONG: c_int = 4;
+const VIR_TYPED_PARAM_DOUBLE: c_int = 5;
+const VIR_TYPED_PARAM_BOOLEAN: c_int = 6;
+const VIR_TYPED_PARAM_STRING: c_int = 7;
+
+#[repr(C)]
+union VirTypedParameterValue {
+    i: c_int,
+    ui: c_uint,
+    l: c_longlong,
+    ul: c_ulonglong,
+    d: c_double,
+    b: c_char,
+    s: *mut c_char,
+}
+
+#[repr(C)]
+struct VirTypedParameter {
+    field: [c_char; VIR_TYPED_PARAM_FIELD_LENGTH],
+    r#type: c_int,
+    value: VirTypedParameterValue,
+}
+
+#[repr(C)]
+struct VirDomainStatsRecord {
+    dom: VirDomainPtr,
+    params: *mut VirTypedParameter,
+    nparams: c_int,
+}
+
+#[repr(C)]
+struct VirDomainIPAddress {
+    r#type: c_int,
+    addr: *mut c_char,
+    prefix: c_uint,
+}
+
+#[repr(C)]
+struct VirDomainInterface {
+    name: *mut c_char,
+    hwaddr: *mut c_char,
+    naddrs: c_uint,
+    addrs: *mut VirDomainIPAddress,
+}
+
+// virIPAddrType
+const VIR_IP_ADDR_TYPE_IPV6: c_int = 1;
+
+#[link(name = "virt")]
+extern "C" {
+    fn virDomainGetUUIDString(dom: VirDomainPtr, buf: *mut c_char) -> c_int;
+    fn virDomainGetName(dom: VirDomainPtr) -> *const c_char;
+    fn virConnectGetAllDomainStats(
+        conn: VirConnectPtr,
+        stats: c_uint,
+        ret_stats: *mut *mut *mut VirDomainStatsRecord,
+        flags: c_uint,
+    ) -> c_int;
+    fn virDomainStatsRecordListFree(stats: *mut *mut VirDomainStatsRecord);
+    fn virDomainListAllSnapshots(
+        domain: VirDomainPtr,
+        snaps: *mut *mut VirDomainSnapshotPtr,
+        flags: c_uint,
+    ) -> c_int;
+    fn virDomainSnapshotGetName(snapshot: VirDomainSnapshotPtr) -> *const c_char;
+    fn virDomainSnapshotFree(snapshot: VirDomainSnapshotPtr) -> c_int;
+    fn virDomainRevertToSnapshot(snapshot: VirDomainSnapshotPtr, flags: c_uint) -> c_int;
+    fn virDomainShutdownFlags(domain: VirDomainPtr, flags: c_uint) -> c_int;
+    fn virDomainReboot(domain: VirDomainPtr, flags: c_uint) -> c_int;
+    fn virDomainUndefineFlags(domain: VirDomainPtr, flags: c_uint) -> c_int;
+    fn virDomainSaveFlags(
+        domain: VirDomainPtr,
+        to: *const c_char,
+        dxml: *const c_char,
+        flags: c_uint,
+    ) -> c_int;
+    fn virDomainRestoreFlags(
+        conn: VirConnectPtr,
+        from: *const c_char,
+        dxml: *const c_char,
+        flags: c_uint,
+    ) -> c_int;
+    fn virStreamNew(conn: VirConnectPtr, flags: c_uint) -> VirStreamPtr;
+    fn virStreamSend(stream: VirStreamPtr, data: *const c_char, nbytes: libc::size_t) -> c_int;
+    fn virStreamRecv(stream: VirStreamPtr, data: *mut c_char, nbytes: libc::size_t) -> c_int;
+    fn virStreamFinish(stream: VirStreamPtr) -> c_int;
+    fn virStreamAbort(stream: VirStreamPtr) -> c_int;
+    fn virStreamFree(stream: VirStreamPtr) -> c_int;
+    fn virDomainScreenshot(
+        domain: VirDomainPtr,
+        stream: VirStreamPtr,
+        screen: c_uint,
+        flags: c_uint,
+    ) -> *mut c_char;
+    fn virDomainOpenGraphicsFD(domain: VirDomainPtr, idx: c_uint, flags: c_uint) -> c_int;
+    fn virDomainInterfaceAddresses(
+        domain: VirDomainPtr,
+        ifaces: *mut *mut *mut VirDomainInterface,
+        source: c_uint,
+        flags: c_uint,
+    ) -> c_int;
+    fn virDomainInterfaceFree(iface: *mut VirDomainInterface);
+    fn virDomainGetGuestInfo(
+        domain: VirDomainPtr,
+        types: c_uint,
+        params: *mut *mut VirTypedParameter,
+        nparams: *mut c_int,
+        flags: c_uint,
+    ) -> c_int;
+    fn virTypedParamsFree(params: *mut VirTypedParameter, nparams: c_int);
+    fn virDomainOpenConsole(
+        domain: VirDomainPtr,
+        dev_name: *const c_char,
+        stream: VirStreamPtr,
+        flags: c_uint,
+    ) -> c_int;
+    fn virDomainFSFreeze(
+        domain: VirDomainPtr,
+        mountpoints: *mut *const c_char,
+        nmountpoints: c_uint,
+        flags: c_uint,
+    ) -> c_int;
+    fn virDomainFSThaw(
+        domain: VirDomainPtr,
+        mountpoints: *mut *const c_char,
+        nmountpoints: c_uint,
+        flags: c_uint,
+    ) -> c_int;
+}
+
+#[link(name = "virt-qemu")]
+extern "C" {
+    fn virDomainQemuAgentCommand(
+        domain: VirDomainPtr,
+        cmd: *const c_char,
+        timeout: c_int,
+        flags: c_uint,
+    ) -> *mut c_char;
+}
+
+pub unsafe fn c_string(s: *const c_char) -> String {
+    match s.is_null() {
+        true => String::new(),
+        false => CStr::from_ptr(s).to_string_lossy().to_string(),
+    }
+}
+
+pub unsafe fn domain_uuid(dom: VirDomainPtr) -> Uuid {
+    let mut buf = [0 as c_char; VIR_UUID_STRING_BUFLEN];
+
+    match virDomainGetUUIDString(dom, buf.as_mut_ptr()) {
+        0 => Uuid::parse_str(&c_string(buf.as_ptr())).unwrap_or_else(|_| Uuid::nil()),
+        _ => Uuid::nil(),
+    }
+}
+
+pub unsafe fn domain_name(dom: VirDomainPtr) -> String {
+    c_string(virDomainGetName(dom))
+}
+
+#[derive(Debug, Clone)]
+pub enum TypedParam {
+    Int(i64),
+    UInt(u64),
+    Double(f64),
+    Bool(bool),
+    String(String),
+}
+
+impl TypedParam {
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            TypedParam::Int(x) if x >= 0 => Some(x as u64),
+            TypedParam::UInt(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            TypedParam::Int(x) => Some(x),
+            TypedParam::UInt(x) => Some(x as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            TypedParam::Int(x) => Some(x as f64),
+            TypedParam::UInt(x) => Some(x as f64),
+            TypedParam::Double(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            TypedParam::Bool(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TypedParam::String(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+unsafe fn typed_param(param: &VirTypedParameter) -> Option<TypedParam> {
+    Some(match param.r#type {
+        VIR_TYPED_PARAM_INT => TypedParam::Int(param.value.i as i64),
+        VIR_TYPED_PARAM_UINT => TypedParam::UInt(param.value.ui as u64),
+        VIR_TYPED_PARAM_LLONG => TypedParam::Int(param.value.l),
+        VIR_TYPED_PARAM_ULLONG => TypedParam::UInt(param.value.ul),
+        VIR_TYPED_PARAM_DOUBLE => TypedParam::Double(param.value.d),
+        VIR_TYPED_PARAM_BOOLEAN => TypedParam::Bool(param.value.b != 0),
+        VIR_TYPED_PARAM_STRING => TypedParam::String(c_string(param.value.s)),
+        _ => return None,
+    })
+}
+
+// One domain's share of a virConnectGetAllDomainStats result, keyed by libvirt's dotted field
+// names ("cpu.time", "block.0.rd.bytes", ...).
+#[derive(Debug, Clone)]
+pub struct DomainStatsRecord {
+    pub uuid: Uuid,
+    pub name: String,
+    pub params: HashMap<String, TypedParam>,
+}
+
+pub fn get_all_domain_stats(
+    conn: &Connect,
+    stats: u32,
+    flags: u32,
+) -> Result<Vec<DomainStatsRecord>, String> {
+    let mut records: *mut *mut VirDomainStatsRecord = ptr::null_mut();
+
+    let count = unsafe {
+        virConnectGetAllDomainStats(conn.as_ptr() as VirConnectPtr, stats, &mut records, flags)
+    };
+    if count < 0 {
+        return Err(virt::error::Error::new().message);
+    }
+
+    let mut result = vec![];
+    if records.is_null() {
+        return Ok(result);
+    }
+
+    unsafe {
+        for record in slice::from_raw_parts(records, count as usize) {
+            let record = &**record;
+            let params = slice::from_raw_parts(record.params, record.nparams as usize)
+                .iter()
+                .filter_map(|x| Some((c_string(x.field.as_ptr()), typed_param(x)?)))
+                .collect();
+
+            result.push(DomainStatsRecord {
+                uuid: domain_uuid(record.dom),
+                name: domain_name(record.dom),
+                params,
+            });
+        }
+
+        virDomainStatsRecordListFree(records);
+    }
+
+    Ok(result)
+}
+
+#[derive(Debug, Clone)]
+pub struct DomainIPAddress {
+    pub addr: String,
+    pub prefix: u32,
+    pub ipv6: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DomainInterface {
+    pub name: String,
+    pub hwaddr: Option<String>,
+    pub addrs: Vec<DomainIPAddress>,
+}
+
+// The guest agent calls below keep libvirt's error code, which callers need to tell an absent
+// agent from a failed command.
+pub fn interface_addresses(
+    domain: &Domain,
+    source: u32,
+) -> Result<Vec<DomainInterface>, virt::error::Error> {
+    let mut ifaces: *mut *mut VirDomainInterface = ptr::null_mut();
+
+    let count = unsafe {
+        virDomainInterfaceAddresses(domain.as_ptr() as VirDomainPtr, &mut ifaces, source, 0)
+    };
+    if count < 0 {
+        return Err(virt::error::Error::new());
+    }
+
+    let mut result = vec![];
+    if ifaces.is_null() {
+        return Ok(result);
+    }
+
+    unsafe {
+        for iface in slice::from_raw_parts(ifaces, count as usize) {
+            let addrs: &[VirDomainIPAddress] = match (**iface).addrs.is_null() {
+                true => &[],
+                false => slice::from_raw_parts((**iface).addrs, (**iface).naddrs as usize),
+            };
+
+            result.push(DomainInterface {
+                name: c_string((**iface).name),
+                hwaddr: match (**iface).hwaddr.is_null() {
+                    true => None,
+                    false => Some(c_string((**iface).hwaddr)),
+                },
+                addrs: addrs
+                    .iter()
+                    .map(|x| DomainIPAddress {
+                        addr: c_string(x.addr),
+                        prefix: x.prefix,
+                        ipv6: x.r#type == VIR_IP_ADDR_TYPE_IPV6,
+                    })
+                    .collect(),
+            });
+
+            virDomainInterfaceFree(*iface);
+        }
+
+        libc::free(ifaces as *mut c_void);
+    }
+
+    Ok(result)
+}
+
+// Guest agent information as libvirt's dotted fields ("os.name", "fs.0.mountpoint", ...).
+pub fn get_guest_info(
+    domain: &Domain,
+    types: u32,
+) -> Result<HashMap<String, TypedParam>, virt::error::Error> {
+    let mut params: *mut VirTypedParameter = ptr::null_mut();
+    let mut nparams: c_int = 0;
+
+    let result = unsafe {
+        virDomainGetGuestInfo(
+            domain.as_ptr() as VirDomainPtr,
+            types,
+            &mut params,
+            &mut nparams,
+            0,
+        )
+    };
+    if result < 0 {
+        return Err(virt::error::Error::new());
+    }
+
+    if params.is_null() {
+        return Ok(HashMap::new());
+    }
+
+    unsafe {
+        let result = slice::from_raw_parts(params, nparams as usize)
+            .iter()
+            .filter_map(|x| Some((c_string(x.field.as_ptr()), typed_param(x)?)))
+            .collect();
+
+        virTypedParamsFree(params, nparams);
+
+        Ok(result)
+    }
+}
+
+// Sends a raw JSON command to the guest agent and returns its raw JSON reply. `timeout` is in
+// seconds, or one of the VIR_DOMAIN_QEMU_AGENT_COMMAND_* values.
+pub fn qemu_agent_command(
+    domain: &Domain,
+    cmd: &str,
+    timeout: i32,
+) -> Result<String, virt::error::Error> {
+    // Serialized JSON escapes NUL, so this cannot fail for commands built with serde_json.
+    let cmd = CString::new(cmd).unwrap();
+
+    unsafe {
+        let result =
+            virDomainQemuAgentCommand(domain.as_ptr() as VirDomainPtr, cmd.as_ptr(), timeout, 0);
+        if result.is_null() {
+            return Err(virt::error::Error::new());
+        }
+
+        let reply = c_string(result);
+        libc::free(result as *mut c_void);
+
+        Ok(reply)
+    }
+}
+
+// Freezes the given guest filesystems, or all of them when `mountpoints` is empty, and returns
+// how many were frozen.
+pub fn fs_freeze(domain: &Domain, mountpoints: &[CString]) -> Result<u32, virt::error::Error> {
+    let mut ptrs: Vec<*const c_char> = mountpoints.iter().map(|x| x.as_ptr()).collect();
+
+    let count = unsafe {
+        virDomainFSFreeze(
+            domain.as_ptr() as VirDomainPtr,
+            match ptrs.is_empty() {
+                true => ptr::null_mut(),
+                false => ptrs.as_mut_ptr(),
+            },
+            ptrs.len() as c_uint,
+            0,
+        )
+    };
+    if count < 0 {
+        return Err(virt::error::Error::new());
+    }
+
+    Ok(count as u32)
+}
+
+// The counterpart of fs_freeze; returns how many filesystems were thawed. QEMU refuses a list of
+// mountpoints here, so every frozen filesystem is thawed.
+pub fn fs_thaw(domain: &Domain) -> Result<u32, virt::error::Error> {
+    let count = unsafe { virDomainFSThaw(domain.as_ptr() as VirDomainPtr, ptr::null_mut(), 0, 0) };
+    if count < 0 {
+        return Err(virt::error::Error::new());
+    }
+
+    Ok(count as u32)
+}
+
+// `flags` is a mask of virDomainShutdownFlagValues; 0 lets the hypervisor pick the method.
+pub fn shutdown_domain_flags(domain: &Domain, flags: u32) -> Result<(), String> {
+    match unsafe { virDomainShutdownFlags(domain.as_ptr() as VirDomainPtr, flags) } {
+        0 => Ok(()),
+        _ => Err(virt::error::Error::new().message),
+    }
+}
+
+// `flags` is a mask of virDomainRebootFlagValues; 0 lets the hypervisor pick the method.
+pub fn reboot_domain_flags(domain: &Domain, flags: u32) -> Result<(), String> {
+    match unsafe { virDomainReboot(domain.as_ptr() as VirDomainPtr, flags) } {
+        0 => Ok(()),
+        _ => Err(virt::error::Error::new().message),
+    }
+}
+
+// `flags` is a mask of virDomainUndefineFlagsValues.
+pub fn undefine_domain_flags(domain: &Domain, flags: u32) -> Result<(), String> {
+    match unsafe { virDomainUndefineFlags(domain.as_ptr() as VirDomainPtr, flags) } {
+        0 => Ok(()),
+        _ => Err(virt::error::Error::new().message),
+    }
+}
+
+// Names of all of the domain's snapshots, in no particular order.
+pub fn list_snapshot_names(domain: &Domain) -> Result<Vec<String>, String> {
+    let mut snapshots: *mut VirDomainSnapshotPtr = ptr::null_mut();
+
+    let count =
+        unsafe { virDomainListAllSnapshots(domain.as_ptr() as VirDomainPtr, &mut snapshots, 0) };
+    if count < 0 {
+        return Err(virt::error::Error::new().message);
+    }
+
+    let mut names = vec![];
+    if snapshots.is_null() {
+        return Ok(names);
+    }
+
+    unsafe {
+        for snapshot in slice::from_raw_parts(snapshots, count as usize) {
+            names.push(c_string(virDomainSnapshotGetName(*snapshot)));
+            virDomainSnapshotFree(*snapshot);
+        }
+
+        libc::free(snapshots as *mut c_void);
+    }
+
+    Ok(names)
+}
+
+// `flags` is a mask of virDomainSnapshotRevertFlags.
+pub fn revert_to_snapshot(snapshot: &DomainSnapshot, flags: u32) -> Result<(), String> {
+    match unsafe { virDomainRevertToSnapshot(snapshot.as_ptr() as VirDomainSnapshotPtr, flags) } {
+        0 => Ok(()),
+        _ => Err(virt::error::Error::new().message),
+    }
+}
+
+fn c_path(path: &str) -> Result<CString, String> {
+    CString::new(path).map_err(|_| format!("path '{}' contains a NUL byte", path))
+}
+
+// Writes the domain's memory to `path` and stops it.
+pub fn save_domain(domain: &Domain, path: &str, flags: u32) -> Result<(), String> {
+    let path = c_path(path)?;
+
+    let result = unsafe {
+        virDomainSaveFlags(
+            domain.as_ptr() as VirDomainPtr,
+            path.as_ptr(),
+            ptr::null(),
+            flags,
+        )
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(virt::error::Error::new().message),
+    }
+}
+
+// Starts a domain from an image written by save_domain, optionally with an updated definition.
+pub fn restore_domain(
+    conn: &Connect,
+    path: &str,
+    xml: Option<&str>,
+    flags: u32,
+) -> Result<(), String> {
+    let path = c_path(path)?;
+    let xml = match xml {
+        Some(x) => Some(CString::new(x).map_err(|_| "xml contains a NUL byte".to_string())?),
+        None => None,
+    };
+
+    let result = unsafe {
+        virDomainRestoreFlags(
+            conn.as_ptr() as VirConnectPtr,
+            path.as_ptr(),
+            xml.as_ref().map_or(ptr::null(), |x| x.as_ptr()),
+            flags,
+        )
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(virt::error::Error::new().message),
+    }
+}
+
+// A socket already connected to the domain's idx-th graphics device, owned by the caller.
+pub fn open_graphics_fd(domain: &Domain, idx: u32, flags: u32) -> Result<c_int, String> {
+    let fd = unsafe { virDomainOpenGraphicsFD(domain.as_ptr() as VirDomainPtr, idx, flags) };
+
+    match fd {
+        x if x >= 0 => Ok(x),
+        _ => Err(virt::error::Error::new().message),
+    }
+}
+
+// A blocking libvirt stream. libvirt locks streams internally, so one thread may receive while
+// another sends or aborts.
+pub struct VirStream {
+    ptr: VirStreamPtr,
+    aborted: AtomicBool,
+}
+
+unsafe impl Send for VirStream {}
+unsafe impl Sync for VirStream {}
+
+impl VirStream {
+    fn new(conn: &Connect) -> Result<VirStream, String> {
+        let ptr = unsafe { virStreamNew(conn.as_ptr() as VirConnectPtr, 0) };
+        if ptr.is_null() {
+            return Err(virt::error::Error::new().message);
+        }
+
+        Ok(VirStream {
+            ptr,
+            aborted: AtomicBool::new(false),
+        })
+    }
+
+    // Connects a new stream to the domain's console, or its first one if `dev_name` is None.
+    pub fn open_console(
+        conn: &Connect,
+        domain: &Domain,
+        dev_name: Option<&str>,
+        flags: u32,
+    ) -> Result<VirStream, String> {
+        let stream = VirStream::new(conn)?;
+        let dev_name = match dev_name {
+            Some(x) => {
+                Some(CString::new(x).map_err(|_| "device name contains a NUL byte".to_string())?)
+            }
+            None => None,
+        };
+
+        let result = unsafe {
+            virDomainOpenConsole(
+                domain.as_ptr() as VirDomainPtr,
+                dev_name.as_ref().map_or(ptr::null(), |x| x.as_ptr()),
+                stream.ptr,
+                flags,
+            )
+        };
+
+        match result {
+            0 => Ok(stream),
+            _ => Err(virt::error::Error::new().message),
+        }
+    }
+
+    // Captures the given screen into a new stream and returns the image's MIME type.
+    pub fn screenshot(
+        conn: &Connect,
+        domain: &Domain,
+        screen: u32,
+    ) -> Result<(VirStream, String), String> {
+        let stream = VirStream::new(conn)?;
+
+        let mime =
+            unsafe { virDomainScreenshot(domain.as_ptr() as VirDomainPtr, stream.ptr, screen, 0) };
+        if mime.is_null() {
+            return Err(virt::error::Error::new().message);
+        }
+
+        let mime_type = unsafe {
+            let x = c_string(mime);
+            libc::free(mime as *mut c_void);
+            x
+        };
+
+        Ok((stream, mime_type))
+    }
+
+    // Reads until the other end finishes the stream.
+    pub fn recv_all(&self) -> Result<Vec<u8>, String> {
+        let mut data = vec![];
+        let mut buf = vec![0; 64 * 1024];
+
+        loop {
+            match self.recv(&mut buf)? {
+                0 => break,
+                n => data.extend_from_slice(&buf[..n]),
+            }
+        }
+
+        self.finish()?;
+        Ok(data)
+    }
+
+    // Blocks until data arrives; Ok(0) means the other end closed the stream.
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize, String> {
+        let result = unsafe { virStreamRecv(self.ptr, buf.as_mut_ptr() as *mut c_char, buf.len()) };
+
+        match result {
+            x if x >= 0 => Ok(x as usize),
+            _ => Err(virt::error::Error::new().message),
+        }
+    }
+
+    pub fn send_all(&self, mut data: &[u8]) -> Result<(), String> {
+        while !data.is_empty() {
+            let result =
+                unsafe { virStreamSend(self.ptr, data.as_ptr() as *const c_char, data.len()) };
+            if result < 0 {
+                return Err(virt::error::Error::new().message);
+            }
+
+            data = &data[result as usize..];
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(&self) -> Result<(), String> {
+        match unsafe { virStreamFinish(self.ptr) } {
+            0 => Ok(()),
+            _ => Err(virt::error::Error::new().message),
+        }
+    }
+
+    // Tears the stream down, waking up a blocked recv.
+    pub fn abort(&self) {
+        if !self.aborted.swap(true, Ordering::SeqCst) {
+            unsafe {
+                virStreamAbort(self.ptr);
+            }
+        }
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for VirStream {
+    fn drop(&mut self) {
+        unsafe {
+            virStreamFree(self.ptr);
+        }
+    }
+}